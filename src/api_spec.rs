@@ -0,0 +1,245 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::examples;
+use crate::models::{Constraints, Entity, Enum, ProjectModel, Union};
+use crate::project::{
+    APIConfiguration, APIDefinition, DataType, Project, ProjectArgument, StatusCode,
+};
+
+#[derive(Debug, Serialize)]
+pub struct Argument {
+    name: String,
+    description: String,
+    data_type: DataType,
+    required: bool,
+    default_value: String,
+    constraints: Constraints,
+}
+
+#[derive(Debug, Serialize)]
+pub struct APIEndpoint {
+    description: String,
+    operation: String,
+    use_cases: Vec<String>,
+    params: Vec<Argument>,
+    query_strings: Vec<Argument>,
+    headers: Vec<Argument>,
+    tags: Vec<String>,
+    status_codes: Vec<StatusCode>,
+    produces: Vec<String>,
+    consumes: Vec<String>,
+    example: Option<Vec<examples::Example>>,
+    request_object: Option<Entity>,
+    request_enum: Option<Enum>,
+    request_union: Option<Union>,
+    response_object: Option<Entity>,
+    response_enum: Option<Enum>,
+    response_union: Option<Union>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct APISpec {
+    title: String,
+    version: String,
+    spec: HashMap<String, API>,
+    models: Option<ProjectModel>,
+}
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Serialize)]
+pub struct API {
+    position: isize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    get: Option<APIEndpoint>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    post: Option<APIEndpoint>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    put: Option<APIEndpoint>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    delete: Option<APIEndpoint>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    patch: Option<APIEndpoint>,
+}
+
+impl APIEndpoint {
+    fn argument_from_project(argument: &ProjectArgument) -> Argument {
+        Argument {
+            name: argument.name.clone(),
+            description: argument.description.clone(),
+            required: argument.required,
+            data_type: argument.data_type.clone(),
+            default_value: argument.default_value.clone(),
+            constraints: argument.constraints.clone(),
+        }
+    }
+
+    fn transform_arguments(arguments: Vec<&ProjectArgument>) -> Vec<Argument> {
+        let mut args = Vec::new();
+        for argument in arguments {
+            let arg = APIEndpoint::argument_from_project(argument);
+            args.push(arg);
+        }
+        args
+    }
+
+    fn get_arguments(section: &str, project: &Project, from_list: &[String]) -> Vec<Argument> {
+        let args = Vec::new();
+        match section {
+            "headers" => {
+                let headers = project.get_headers(from_list);
+                return APIEndpoint::transform_arguments(headers);
+            }
+            "query" => {
+                let query_strings = project.get_query_strings(from_list);
+                return APIEndpoint::transform_arguments(query_strings);
+            }
+            "params" => {
+                let params = project.get_path_params(from_list);
+                return APIEndpoint::transform_arguments(params);
+            }
+            _ => {
+                // invalid section name
+            }
+        }
+        args
+    }
+
+    fn new_from_api_configuration(
+        configuration: &Option<APIConfiguration>,
+        project: &Project,
+    ) -> Option<APIEndpoint> {
+        match configuration {
+            Some(config) => {
+                let mut request: Option<Entity> = None;
+                let mut request_enum: Option<Enum> = None;
+                let mut request_union: Option<Union> = None;
+                let mut response: Option<Entity> = None;
+                let mut response_enum: Option<Enum> = None;
+                let mut response_union: Option<Union> = None;
+                let mut ex: Option<Vec<examples::Example>> = None;
+                if let Some(example) = project.examples.get(config.example.as_str()) {
+                    ex = Some(example.clone());
+                }
+                match &project.models {
+                    Some(models) => {
+                        if let Some(m) = models.entities.get(config.request_model.as_str()) {
+                            request = Some(m.clone());
+                        }
+                        if let Some(m) = models.enums.get(config.request_model.as_str()) {
+                            request_enum = Some(m.clone());
+                        }
+                        if let Some(m) = models.unions.get(config.request_model.as_str()) {
+                            request_union = Some(m.clone());
+                        }
+                        if let Some(m) = models.entities.get(config.response_model.as_str()) {
+                            response = Some(m.clone());
+                        }
+                        if let Some(m) = models.enums.get(config.response_model.as_str()) {
+                            response_enum = Some(m.clone());
+                        }
+                        if let Some(m) = models.unions.get(config.response_model.as_str()) {
+                            response_union = Some(m.clone());
+                        }
+                    }
+                    _ => {
+                        // ignore
+                    }
+                }
+                let endpoint = APIEndpoint {
+                    description: config.description.to_owned(),
+                    operation: config.operation.to_owned(),
+                    use_cases: config.use_cases.clone(),
+                    params: APIEndpoint::get_arguments("params", project, &config.path_params),
+                    query_strings: APIEndpoint::get_arguments(
+                        "query",
+                        project,
+                        &config.query_string,
+                    ),
+                    headers: APIEndpoint::get_arguments("headers", project, &config.headers),
+                    tags: config.tags.clone(),
+                    status_codes: project.get_status_codes(&config.status_codes),
+                    produces: get_mime_types(&config.produces),
+                    consumes: get_mime_types(&config.consumes),
+                    example: ex,
+                    request_object: request,
+                    request_enum,
+                    request_union,
+                    response_object: response,
+                    response_enum,
+                    response_union,
+                };
+                Some(endpoint)
+            }
+            None => None,
+        }
+    }
+}
+
+impl API {
+    fn new_from_api_definition(def: &APIDefinition, project: &Project) -> API {
+        API {
+            position: def.position,
+            get: APIEndpoint::new_from_api_configuration(&def.get, project),
+            post: APIEndpoint::new_from_api_configuration(&def.post, project),
+            put: APIEndpoint::new_from_api_configuration(&def.put, project),
+            delete: APIEndpoint::new_from_api_configuration(&def.delete, project),
+            patch: APIEndpoint::new_from_api_configuration(&def.patch, project),
+        }
+    }
+
+    pub fn new_project_spec(project: &Project) -> APISpec {
+        let mut api = HashMap::new();
+        for endpoint in &project.endpoints {
+            let api_path = endpoint.0.to_owned();
+            let api_def = API::new_from_api_definition(endpoint.1, project);
+            api.insert(api_path, api_def);
+        }
+        let mut models: Option<ProjectModel> = None;
+        if let Some(x) = &project.models {
+            models = Some(x.clone());
+        }
+        APISpec {
+            title: project.title.to_owned(),
+            version: project.version.to_owned(),
+            spec: api,
+            models,
+        }
+    }
+}
+
+pub fn get_mime_types(list: &[String]) -> Vec<String> {
+    let mut mime_types = Vec::new();
+    let mime_map: HashMap<String, String> = [
+        ("json".to_owned(), "application/json".to_owned()),
+        ("xml".to_owned(), "application/xml".to_owned()),
+        ("text".to_owned(), "text/plain".to_owned()),
+        ("css".to_owned(), "text/css".to_owned()),
+        ("html".to_owned(), "text/html".to_owned()),
+        ("javascript".to_owned(), "application/javascript".to_owned()),
+        ("js".to_owned(), "application/javascript".to_owned()),
+        ("multipart".to_owned(), "multipart/form-data".to_owned()),
+        ("binary".to_owned(), "application/octet-stream".to_owned()),
+        ("mp4".to_owned(), "video/mp4".to_owned()),
+        // image formats
+        ("jpg".to_owned(), "image/jpeg".to_owned()),
+        ("png".to_owned(), "image/png".to_owned()),
+        ("svg".to_owned(), "image/svg+xml".to_owned()),
+    ]
+    .iter()
+    .cloned()
+    .collect();
+    for item in list {
+        let trimmed = item.trim().to_owned();
+        match mime_map.get(&trimmed) {
+            Some(mime_type) => {
+                mime_types.push(mime_type.to_owned());
+            }
+            None => {
+                mime_types.push(trimmed.clone());
+            }
+        }
+    }
+    mime_types
+}
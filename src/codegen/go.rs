@@ -0,0 +1,631 @@
+//! Go target: structs with JSON tags, typed enum constants, and a handler interface per
+//! operation with `net/http` adapters routed by `NewServeMux` (Go 1.22 patterns)
+//!
+//! The `go:` field tag sets the Go field name
+
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use crate::codegen::{
+    arguments, default_literal, doc, operations, pascal_case, primitive, reaches, Literal,
+    Operation, Primitive, Types,
+};
+use crate::models::{Field, ProjectModel};
+use crate::project::Project;
+
+const PACKAGE: &str = "api";
+
+const UNION_RUNTIME: &str = "func decodeStrict(data []byte, value any) error {
+	decoder := json.NewDecoder(bytes.NewReader(data))
+	decoder.DisallowUnknownFields()
+	return decoder.Decode(value)
+}
+";
+
+const HANDLER_RUNTIME: &str = "// HandlerError is returned by handlers to answer with a status code
+type HandlerError struct {
+	Status  int
+	Message string
+}
+
+func (e *HandlerError) Error() string {
+	return e.Message
+}
+
+func writeJSON(w http.ResponseWriter, status int, value any) {
+	w.Header().Set(\"Content-Type\", \"application/json\")
+	w.WriteHeader(status)
+	_ = json.NewEncoder(w).Encode(value)
+}
+
+func writeError(w http.ResponseWriter, err error) {
+	var handlerError *HandlerError
+	if !errors.As(err, &handlerError) {
+		handlerError = &HandlerError{Status: http.StatusInternalServerError, Message: err.Error()}
+	}
+	http.Error(w, handlerError.Message, handlerError.Status)
+}
+
+func argument(r *http.Request, location, name string) (string, bool) {
+	switch location {
+	case \"path\":
+		value := r.PathValue(name)
+		return value, value != \"\"
+	case \"query\":
+		if !r.URL.Query().Has(name) {
+			return \"\", false
+		}
+		return r.URL.Query().Get(name), true
+	default:
+		values := r.Header.Values(name)
+		if len(values) == 0 {
+			return \"\", false
+		}
+		return values[0], true
+	}
+}
+
+func requiredString(r *http.Request, location, name string) (string, error) {
+	value, ok := argument(r, location, name)
+	if !ok {
+		return \"\", &HandlerError{Status: http.StatusBadRequest, Message: \"missing \" + name}
+	}
+	return value, nil
+}
+
+func optionalString(r *http.Request, location, name string) (*string, error) {
+	value, ok := argument(r, location, name)
+	if !ok {
+		return nil, nil
+	}
+	return &value, nil
+}
+
+func requiredFloat(r *http.Request, location, name string) (float64, error) {
+	value, err := requiredString(r, location, name)
+	if err != nil {
+		return 0, err
+	}
+	parsed, err := strconv.ParseFloat(value, 64)
+	if err != nil {
+		return 0, &HandlerError{Status: http.StatusBadRequest, Message: \"invalid \" + name}
+	}
+	return parsed, nil
+}
+
+func optionalFloat(r *http.Request, location, name string) (*float64, error) {
+	value, ok := argument(r, location, name)
+	if !ok {
+		return nil, nil
+	}
+	parsed, err := strconv.ParseFloat(value, 64)
+	if err != nil {
+		return nil, &HandlerError{Status: http.StatusBadRequest, Message: \"invalid \" + name}
+	}
+	return &parsed, nil
+}
+
+func requiredBool(r *http.Request, location, name string) (bool, error) {
+	value, err := requiredString(r, location, name)
+	if err != nil {
+		return false, err
+	}
+	parsed, err := strconv.ParseBool(value)
+	if err != nil {
+		return false, &HandlerError{Status: http.StatusBadRequest, Message: \"invalid \" + name}
+	}
+	return parsed, nil
+}
+
+func optionalBool(r *http.Request, location, name string) (*bool, error) {
+	value, ok := argument(r, location, name)
+	if !ok {
+		return nil, nil
+	}
+	parsed, err := strconv.ParseBool(value)
+	if err != nil {
+		return nil, &HandlerError{Status: http.StatusBadRequest, Message: \"invalid \" + name}
+	}
+	return &parsed, nil
+}
+";
+
+/// Go names of the model types
+struct Go;
+
+impl Types for Go {
+    fn primitive(&self, primitive: Primitive) -> &'static str {
+        match primitive {
+            Primitive::String => "string",
+            Primitive::Int | Primitive::Long => "int64",
+            Primitive::Float | Primitive::Double => "float64",
+            Primitive::Bool => "bool",
+        }
+    }
+
+    fn unknown(&mut self) -> String {
+        "any".to_owned()
+    }
+}
+
+/// Go field name, the `go:` tag when present
+fn field_name(field: &Field) -> String {
+    match field.tags.get("go") {
+        Some(name) if !name.is_empty() => name.to_owned(),
+        _ => pascal_case(&field.identifier),
+    }
+}
+
+fn field_type(models: &ProjectModel, entity: &str, field: &Field) -> String {
+    let mut name = Go.type_name(Some(models), &field.data_type);
+    if field.is_array {
+        name = format!("[]{}", name);
+    }
+    if field.is_map {
+        let key_type = primitive(&field.map_key_type).map_or("string", |p| Go.primitive(p));
+        return format!("map[{}]{}", key_type, name);
+    }
+    let recursive =
+        !field.is_array && reaches(models, &field.data_type, entity, &mut HashSet::new());
+    if !field.is_array && (field.is_optional || field.is_nullable || recursive) && name != "any" {
+        name = format!("*{}", name);
+    }
+    name
+}
+
+/// Aligns the columns of struct fields like gofmt
+fn struct_body(out: &mut String, rows: &[(String, Vec<String>)]) {
+    let mut widths = [0; 2];
+    for (_, columns) in rows {
+        for (index, column) in columns.iter().take(2).enumerate() {
+            widths[index] = widths[index].max(column.len());
+        }
+    }
+    for (comment, columns) in rows {
+        out.push_str(comment);
+        let mut line = String::from("\t");
+        for (index, column) in columns.iter().enumerate() {
+            if index + 1 < columns.len() && index < 2 {
+                let _ = write!(line, "{:width$} ", column, width = widths[index]);
+            } else {
+                line.push_str(column);
+            }
+        }
+        let _ = writeln!(out, "{}", line.trim_end());
+    }
+}
+
+fn models_source(out: &mut String, models: &ProjectModel) {
+    let mut names: Vec<&String> = models.enums.keys().collect();
+    names.sort();
+    for name in names {
+        let definition = &models.enums[name];
+        let type_name = pascal_case(name);
+        let base = match definition.value_type == "int" {
+            true => "int64",
+            false => "string",
+        };
+        let _ = writeln!(out, "type {} {}\n\nconst (", type_name, base);
+        let width = definition
+            .members
+            .iter()
+            .map(|m| type_name.len() + pascal_case(&m.name).len())
+            .max()
+            .unwrap_or(0);
+        for member in &definition.members {
+            let constant = format!("{}{}", type_name, pascal_case(&member.name));
+            doc(
+                out,
+                "\t",
+                &format!("// {}", constant),
+                &member.description,
+                "",
+            );
+            if member.deprecated {
+                let _ = writeln!(
+                    out,
+                    "\t// Deprecated: {} is kept for compatibility",
+                    constant
+                );
+            }
+            let value = match definition.value_type == "int" {
+                true => member.value.to_owned(),
+                false => format!("{:?}", member.value),
+            };
+            let _ = writeln!(
+                out,
+                "\t{:width$} {} = {}",
+                constant,
+                type_name,
+                value,
+                width = width
+            );
+        }
+        out.push_str(")\n\n");
+    }
+    let mut names: Vec<&String> = models.unions.keys().collect();
+    names.sort();
+    for name in names {
+        let union = &models.unions[name];
+        let type_name = pascal_case(name);
+        let variants: Vec<(String, String)> = union
+            .variants
+            .iter()
+            .map(|v| (pascal_case(v), Go.type_name(Some(models), v)))
+            .collect();
+        let names: Vec<&str> = variants.iter().map(|(n, _)| n.as_str()).collect();
+        let _ = writeln!(
+            out,
+            "// {} is one of {}, only one of them is set\ntype {} struct {{",
+            type_name,
+            names.join(", "),
+            type_name
+        );
+        let rows: Vec<(String, Vec<String>)> = variants
+            .iter()
+            .map(|(variant, variant_type)| {
+                (
+                    String::new(),
+                    vec![
+                        variant.to_owned(),
+                        format!("*{}", variant_type),
+                        "`json:\"-\"`".to_owned(),
+                    ],
+                )
+            })
+            .collect();
+        struct_body(out, &rows);
+        out.push_str("}\n\n");
+        let _ = writeln!(
+            out,
+            "func (u {}) MarshalJSON() ([]byte, error) {{",
+            type_name
+        );
+        for (variant, _) in &variants {
+            let _ = writeln!(
+                out,
+                "\tif u.{variant} != nil {{\n\t\treturn json.Marshal(u.{variant})\n\t}}",
+                variant = variant
+            );
+        }
+        out.push_str("\treturn []byte(\"null\"), nil\n}\n\n");
+        let _ = writeln!(
+            out,
+            "func (u *{}) UnmarshalJSON(data []byte) error {{",
+            type_name
+        );
+        if !union.discriminator.is_empty() {
+            // the discriminator holds the variant name, variants may share every field
+            let _ = writeln!(
+                out,
+                "\tvar probe struct {{\n\t\tDiscriminator string `json:{:?}`\n\t}}\n\tif err := json.Unmarshal(data, &probe); err != nil {{\n\t\treturn err\n\t}}\n\t*u = {}{{}}\n\tswitch probe.Discriminator {{",
+                union.discriminator, type_name
+            );
+            for ((variant, variant_type), name) in variants.iter().zip(&union.variants) {
+                let _ = writeln!(
+                    out,
+                    "\tcase {:?}:\n\t\tu.{} = new({})\n\t\treturn json.Unmarshal(data, u.{})",
+                    name, variant, variant_type, variant
+                );
+            }
+            let _ = writeln!(
+                out,
+                "\t}}\n\treturn fmt.Errorf(\"{}: unknown {} %q\", probe.Discriminator)\n}}\n",
+                type_name, union.discriminator
+            );
+            continue;
+        }
+        for (variant, variant_type) in &variants {
+            let _ = writeln!(
+                out,
+                "\tvar {lower} {variant_type}\n\tif decodeStrict(data, &{lower}) == nil {{\n\t\tu.{variant} = &{lower}\n\t\treturn nil\n\t}}",
+                lower = format!("as{}", variant),
+                variant = variant,
+                variant_type = variant_type
+            );
+        }
+        let _ = writeln!(
+            out,
+            "\treturn fmt.Errorf(\"{}: no variant matches\")\n}}\n",
+            type_name
+        );
+    }
+    let mut names: Vec<&String> = models.entities.keys().collect();
+    names.sort();
+    for name in names {
+        let entity = &models.entities[name];
+        let _ = writeln!(out, "type {} struct {{", pascal_case(name));
+        let mut rows = vec![];
+        for field in entity.ordered_fields() {
+            let mut comment = String::new();
+            doc(
+                &mut comment,
+                "\t",
+                &format!("// {}", field_name(field)),
+                &field.description,
+                "",
+            );
+            let omit = match field.is_optional {
+                true => ",omitempty",
+                false => "",
+            };
+            rows.push((
+                comment,
+                vec![
+                    field_name(field),
+                    field_type(models, name, field),
+                    format!("`json:\"{}{}\"`", field.identifier, omit),
+                ],
+            ));
+        }
+        struct_body(out, &rows);
+        out.push_str("}\n\n");
+        defaults_source(out, models, name, entity.ordered_fields());
+    }
+}
+
+/// `New<Struct>()` returning the `= value` defaults of a struct, decoding JSON into it
+/// keeps them for absent fields
+fn defaults_source(out: &mut String, models: &ProjectModel, name: &str, fields: Vec<&Field>) {
+    let mut variables = String::new();
+    let mut values = vec![];
+    for field in fields {
+        let pointer = field_type(models, name, field).starts_with('*');
+        let mut value = match default_literal(models, field) {
+            Some(Literal::Text(text)) => format!("{:?}", text),
+            Some(Literal::Int(number)) if pointer => format!("int64({})", number),
+            Some(Literal::Int(number)) => number.to_string(),
+            Some(Literal::Float(number)) => format!("{:?}", number),
+            Some(Literal::Bool(value)) => value.to_string(),
+            Some(Literal::Member(member)) => format!(
+                "{}{}",
+                pascal_case(&field.data_type),
+                pascal_case(&member.name)
+            ),
+            None => continue,
+        };
+        if pointer {
+            let variable = format!("default{}", field_name(field));
+            let _ = writeln!(variables, "	{} := {}", variable, value);
+            value = format!("&{}", variable);
+        }
+        values.push((format!("{}:", field_name(field)), value));
+    }
+    if values.is_empty() {
+        return;
+    }
+    let type_name = pascal_case(name);
+    let _ = writeln!(
+        out,
+        "// New{name} returns a {name} holding the declared defaults\nfunc New{name}() {name} {{\n{}\treturn {name}{{",
+        variables,
+        name = type_name
+    );
+    let width = values.iter().map(|(key, _)| key.len()).max().unwrap_or(0);
+    for (key, value) in values {
+        let _ = writeln!(out, "\t\t{:width$} {},", key, value, width = width);
+    }
+    out.push_str("\t}\n}\n\n");
+}
+
+/// Suffix of the runtime functions reading an argument
+fn argument_kind(primitive: Primitive) -> &'static str {
+    match primitive {
+        Primitive::Float | Primitive::Double => "Float",
+        Primitive::Bool => "Bool",
+        _ => "String",
+    }
+}
+
+fn operation_source(out: &mut String, project: &Project, operation: &Operation) {
+    let config = operation.config;
+    let models = project.models.as_ref();
+    let name = pascal_case(&operation.name);
+    let arguments = arguments(operation, pascal_case);
+    let mut parameters = vec!["ctx context.Context".to_owned()];
+    if !arguments.is_empty() {
+        let _ = writeln!(
+            out,
+            "// {name}Params holds the path params, query items and headers of {name}\ntype {name}Params struct {{",
+            name = name
+        );
+        let rows: Vec<(String, Vec<String>)> = arguments
+            .iter()
+            .map(|a| {
+                let pointer = if a.required { "" } else { "*" };
+                (
+                    String::new(),
+                    vec![
+                        a.name.to_owned(),
+                        format!("{}{}", pointer, Go.primitive(a.primitive)),
+                    ],
+                )
+            })
+            .collect();
+        struct_body(out, &rows);
+        out.push_str("}\n\n");
+        parameters.push(format!("params {}Params", name));
+    }
+    if !config.request_model.is_empty() {
+        parameters.push(format!(
+            "body {}",
+            Go.type_name(models, &config.request_model)
+        ));
+    }
+    let results = match config.response_model.is_empty() {
+        true => "error".to_owned(),
+        false => format!("(*{}, error)", Go.type_name(models, &config.response_model)),
+    };
+    let description = match config.description.is_empty() {
+        true => String::new(),
+        false => format!(": {}", config.description),
+    };
+    let _ = writeln!(
+        out,
+        "// {name}Handler handles {verb} {path}{description}\ntype {name}Handler interface {{\n\t{name}({parameters}) {results}\n}}\n",
+        name = name,
+        verb = operation.verb.to_uppercase(),
+        path = operation.path,
+        description = description,
+        parameters = parameters.join(", "),
+        results = results
+    );
+
+    let _ = writeln!(
+        out,
+        "// New{name}Handler adapts a {name}Handler to net/http\nfunc New{name}Handler(h {name}Handler) http.HandlerFunc {{\n\treturn func(w http.ResponseWriter, r *http.Request) {{",
+        name = name
+    );
+    let mut call = vec!["r.Context()"];
+    if !arguments.is_empty() {
+        out.push_str(&format!(
+            "\t\tvar params {}Params\n\t\tvar err error\n",
+            name
+        ));
+        for argument in &arguments {
+            let requirement = if argument.required {
+                "required"
+            } else {
+                "optional"
+            };
+            let _ = writeln!(
+                out,
+                "\t\tif params.{}, err = {}{}(r, {:?}, {:?}); err != nil {{\n\t\t\twriteError(w, err)\n\t\t\treturn\n\t\t}}",
+                argument.name, requirement, argument_kind(argument.primitive), argument.location, argument.wire
+            );
+        }
+        call.push("params");
+    }
+    if !config.request_model.is_empty() {
+        let _ = writeln!(
+            out,
+            "\t\tvar body {}\n\t\tif err := json.NewDecoder(r.Body).Decode(&body); err != nil {{\n\t\t\twriteError(w, &HandlerError{{Status: http.StatusBadRequest, Message: err.Error()}})\n\t\t\treturn\n\t\t}}",
+            Go.type_name(models, &config.request_model)
+        );
+        call.push("body");
+    }
+    let status = project
+        .get_status_codes(&config.status_codes)
+        .iter()
+        .find(|c| c.code.starts_with('2'))
+        .map_or("200".to_owned(), |c| c.code.to_owned());
+    match config.response_model.is_empty() {
+        true => {
+            let _ = writeln!(
+                out,
+                "\t\tif err := h.{}({}); err != nil {{\n\t\t\twriteError(w, err)\n\t\t\treturn\n\t\t}}\n\t\tw.WriteHeader({})",
+                name,
+                call.join(", "),
+                status
+            );
+        }
+        false => {
+            let _ = writeln!(
+                out,
+                "\t\tresult, err := h.{}({})\n\t\tif err != nil {{\n\t\t\twriteError(w, err)\n\t\t\treturn\n\t\t}}\n\t\twriteJSON(w, {}, result)",
+                name,
+                call.join(", "),
+                status
+            );
+        }
+    }
+    out.push_str("\t}\n}\n\n");
+}
+
+/// Go source of the `api` package
+pub fn generate(project: &Project) -> String {
+    let operations = operations(project);
+    let unions = project.models.as_ref().map(|models| &models.unions);
+    let has_unions = unions.is_some_and(|unions| !unions.is_empty());
+    // unions without a discriminator try each variant with `decodeStrict`
+    let has_untagged = unions
+        .into_iter()
+        .flat_map(|unions| unions.values())
+        .any(|union| union.discriminator.is_empty());
+    let mut imports = vec![];
+    if has_unions {
+        imports.push("fmt");
+    }
+    if has_untagged {
+        imports.push("bytes");
+    }
+    if !operations.is_empty() {
+        imports.extend(["context", "errors", "net/http", "strconv"]);
+    }
+    if has_unions || !operations.is_empty() {
+        imports.push("encoding/json");
+    }
+    imports.sort();
+
+    let mut out = format!(
+        "// Code generated by apish from {} {}. DO NOT EDIT.\n\npackage {}\n\n",
+        project.title, project.version, PACKAGE
+    );
+    if !imports.is_empty() {
+        out.push_str("import (\n");
+        for import in imports {
+            let _ = writeln!(out, "\t{:?}", import);
+        }
+        out.push_str(")\n\n");
+    }
+    if let Some(models) = &project.models {
+        models_source(&mut out, models);
+    }
+    if has_untagged {
+        out.push_str(UNION_RUNTIME);
+        out.push('\n');
+    }
+    if operations.is_empty() {
+        return format!("{}\n", out.trim_end());
+    }
+    out.push_str(HANDLER_RUNTIME);
+    out.push('\n');
+    for operation in &operations {
+        operation_source(&mut out, project, operation);
+    }
+    out.push_str("// API groups the handlers of every operation\ntype API interface {\n");
+    for operation in &operations {
+        let _ = writeln!(out, "\t{}Handler", pascal_case(&operation.name));
+    }
+    out.push_str("}\n\n// NewServeMux routes every operation to api\nfunc NewServeMux(api API) *http.ServeMux {\n\tmux := http.NewServeMux()\n");
+    for operation in &operations {
+        let _ = writeln!(
+            out,
+            "\tmux.Handle(\"{} {}\", New{}Handler(api))",
+            operation.verb.to_uppercase(),
+            operation.path,
+            pascal_case(&operation.name)
+        );
+    }
+    out.push_str("\treturn mux\n}\n");
+    out
+}
+
+#[test]
+fn test_generate_go() {
+    use super::{assert_blocks, pets};
+
+    // Card and Bank have the same fields, only the discriminator tells them apart
+    let source = generate(&pets("union Loose = Card | Bank\n"));
+    assert_blocks(
+        &source,
+        &[
+            "func (u *Payment) UnmarshalJSON(data []byte) error {\n\tvar probe struct {\n\t\tDiscriminator string `json:\"type\"`\n\t}\n\tif err := json.Unmarshal(data, &probe); err != nil {\n\t\treturn err\n\t}\n\t*u = Payment{}\n\tswitch probe.Discriminator {\n\tcase \"Card\":\n\t\tu.Card = new(Card)\n\t\treturn json.Unmarshal(data, u.Card)\n\tcase \"Bank\":\n\t\tu.Bank = new(Bank)\n\t\treturn json.Unmarshal(data, u.Bank)\n\t}\n\treturn fmt.Errorf(\"Payment: unknown type %q\", probe.Discriminator)\n}\n",
+            "func (u *Loose) UnmarshalJSON(data []byte) error {\n\tvar asCard Card\n\tif decodeStrict(data, &asCard) == nil {\n\t\tu.Card = &asCard\n\t\treturn nil\n\t}\n\tvar asBank Bank\n\tif decodeStrict(data, &asBank) == nil {\n\t\tu.Bank = &asBank\n\t\treturn nil\n\t}\n\treturn fmt.Errorf(\"Loose: no variant matches\")\n}\n",
+            "import (\n\t\"bytes\"\n\t\"context\"\n\t\"encoding/json\"\n\t\"errors\"\n\t\"fmt\"\n\t\"net/http\"\n\t\"strconv\"\n)\n",
+            "type Kind string\n",
+            "const (\n\tKindDog Kind = \"dog\"\n\t// Deprecated: KindCat is kept for compatibility\n\tKindCat Kind = \"CAT\"\n)\n",
+            "type Pet struct {\n\tId       int64            `json:\"id\"`\n\t// FullName Pet name\n\tFullName string           `json:\"name\"`\n\tType     Kind             `json:\"type\"`\n\tTags     []string         `json:\"tags,omitempty\"`\n\tOwner    *Owner           `json:\"owner\"`\n\tParent   *Pet             `json:\"parent\"`\n\tNickName *string          `json:\"nickName\"`\n\tExtra    map[string]int64 `json:\"extra\"`\n\tBorn     string           `json:\"born\"`\n}\n",
+            "// NewSettings returns a Settings holding the declared defaults\nfunc NewSettings() Settings {\n\tdefaultRetries := int64(3)\n\tdefaultKind := KindDog\n\tdefaultSize := SizeSmall\n\treturn Settings{\n\t\tTheme:   \"dark\",\n\t\tRetries: &defaultRetries,\n\t\tKind:    &defaultKind,\n\t\tSize:    &defaultSize,\n\t}\n}\n",
+            "type Error struct {\n\tCode int64 `json:\"code\"`\n}\n",
+            "// HandlerError is returned by handlers to answer with a status code\ntype HandlerError struct {\n\tStatus  int\n\tMessage string\n}\n",
+            "// UpdatePetParams holds the path params, query items and headers of UpdatePet\ntype UpdatePetParams struct {\n\tId       float64\n\tType     *string\n\tPageSize *float64\n\tXAuth    string\n}\n",
+            "// UpdatePetHandler handles PUT /pets/{id}: Updates a pet\ntype UpdatePetHandler interface {\n\tUpdatePet(ctx context.Context, params UpdatePetParams, body Pet) (*Pet, error)\n}\n",
+            "// NewUpdatePetHandler adapts a UpdatePetHandler to net/http\nfunc NewUpdatePetHandler(h UpdatePetHandler) http.HandlerFunc {\n\treturn func(w http.ResponseWriter, r *http.Request) {\n\t\tvar params UpdatePetParams\n\t\tvar err error\n\t\tif params.Id, err = requiredFloat(r, \"path\", \"id\"); err != nil {\n\t\t\twriteError(w, err)\n\t\t\treturn\n\t\t}\n\t\tif params.Type, err = optionalString(r, \"query\", \"type\"); err != nil {\n\t\t\twriteError(w, err)\n\t\t\treturn\n\t\t}\n\t\tif params.PageSize, err = optionalFloat(r, \"query\", \"page-size\"); err != nil {\n\t\t\twriteError(w, err)\n\t\t\treturn\n\t\t}\n\t\tif params.XAuth, err = requiredString(r, \"header\", \"x-auth\"); err != nil {\n\t\t\twriteError(w, err)\n\t\t\treturn\n\t\t}\n\t\tvar body Pet\n\t\tif err := json.NewDecoder(r.Body).Decode(&body); err != nil {\n\t\t\twriteError(w, &HandlerError{Status: http.StatusBadRequest, Message: err.Error()})\n\t\t\treturn\n\t\t}\n\t\tresult, err := h.UpdatePet(r.Context(), params, body)\n\t\tif err != nil {\n\t\t\twriteError(w, err)\n\t\t\treturn\n\t\t}\n\t\twriteJSON(w, 201, result)\n\t}\n}\n",
+            "// NewDeletePetsIdHandler adapts a DeletePetsIdHandler to net/http\nfunc NewDeletePetsIdHandler(h DeletePetsIdHandler) http.HandlerFunc {\n\treturn func(w http.ResponseWriter, r *http.Request) {\n\t\tvar params DeletePetsIdParams\n\t\tvar err error\n\t\tif params.Id, err = requiredFloat(r, \"path\", \"id\"); err != nil {\n\t\t\twriteError(w, err)\n\t\t\treturn\n\t\t}\n\t\tif err := h.DeletePetsId(r.Context(), params); err != nil {\n\t\t\twriteError(w, err)\n\t\t\treturn\n\t\t}\n\t\tw.WriteHeader(200)\n\t}\n}\n",
+            "// NewServeMux routes every operation to api\nfunc NewServeMux(api API) *http.ServeMux {\n\tmux := http.NewServeMux()\n\tmux.Handle(\"GET /pets/{id}\", NewGetPetHandler(api))\n\tmux.Handle(\"PUT /pets/{id}\", NewUpdatePetHandler(api))\n\tmux.Handle(\"DELETE /pets/{id}\", NewDeletePetsIdHandler(api))\n\tmux.Handle(\"GET /health\", NewGetHealthHandler(api))\n\treturn mux\n}\n",
+        ],
+    );
+    let source = generate(&pets(""));
+    assert!(!source.contains("decodeStrict") && !source.contains("\"bytes\""));
+}
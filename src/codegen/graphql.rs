@@ -0,0 +1,281 @@
+//! GraphQL target: an SDL schema with object types for the models, input types for the
+//! request models, and `Query`/`Mutation` fields for the operations
+
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+use crate::codegen::{
+    arguments, camel_case, default_literal, operations, pascal_case, Literal, Operation, Primitive,
+    Types,
+};
+use crate::models::{Field, ProjectModel, TypeKind};
+use crate::project::Project;
+
+/// Custom scalar for maps and types without a GraphQL equivalent
+const JSON_SCALAR: &str = "JSON";
+
+fn description(out: &mut String, indent: &str, text: &str) {
+    if !text.is_empty() {
+        let _ = writeln!(out, "{}{:?}", indent, text);
+    }
+}
+
+/// GraphQL names of the model types, `input` picks the input variant of structs and `json`
+/// records that the JSON scalar was used
+#[derive(Default)]
+struct GraphQL {
+    input: bool,
+    json: bool,
+}
+
+impl Types for GraphQL {
+    fn primitive(&self, primitive: Primitive) -> &'static str {
+        match primitive {
+            Primitive::String => "String",
+            Primitive::Int | Primitive::Long => "Int",
+            Primitive::Float | Primitive::Double => "Float",
+            Primitive::Bool => "Boolean",
+        }
+    }
+
+    fn model(&mut self, name: &str, kind: TypeKind) -> String {
+        match kind {
+            TypeKind::Struct if self.input => format!("{}Input", pascal_case(name)),
+            // input types cannot hold unions
+            TypeKind::Union if self.input => self.unknown(),
+            _ => pascal_case(name),
+        }
+    }
+
+    fn unknown(&mut self) -> String {
+        self.json = true;
+        JSON_SCALAR.to_owned()
+    }
+}
+
+fn field_type(types: &mut GraphQL, models: &ProjectModel, field: &Field) -> String {
+    let mut name = match field.is_map {
+        true => types.unknown(),
+        false => types.type_name(Some(models), &field.data_type),
+    };
+    if field.is_array && !field.is_map {
+        name = format!("[{}!]", name);
+    }
+    if !field.is_optional && !field.is_nullable {
+        name.push('!');
+    }
+    name
+}
+
+/// GraphQL literal of a default, enum members are written by name
+fn value(literal: &Literal) -> String {
+    match literal {
+        Literal::Text(text) => serde_json::Value::from(*text).to_string(),
+        Literal::Int(number) => number.to_string(),
+        Literal::Float(number) => number.to_string(),
+        Literal::Bool(value) => value.to_string(),
+        Literal::Member(member) => member.name.to_owned(),
+    }
+}
+
+/// Structs reachable from the request models, they need an input type
+fn input_entities(project: &Project, models: &ProjectModel) -> BTreeSet<String> {
+    let mut pending: Vec<String> = operations(project)
+        .iter()
+        .map(|operation| operation.config.request_model.to_owned())
+        .collect();
+    let mut found = BTreeSet::new();
+    while let Some(name) = pending.pop() {
+        let entity = match models.entities.get(&name) {
+            Some(entity) => entity,
+            None => continue,
+        };
+        if found.insert(name) {
+            pending.extend(entity.fields.values().map(|f| f.data_type.to_owned()));
+        }
+    }
+    found
+}
+
+fn models_source(out: &mut String, types: &mut GraphQL, project: &Project, models: &ProjectModel) {
+    let mut names: Vec<&String> = models.enums.keys().collect();
+    names.sort();
+    for name in names {
+        let definition = &models.enums[name];
+        let _ = writeln!(out, "enum {} {{", pascal_case(name));
+        for member in &definition.members {
+            description(out, "  ", &member.description);
+            let deprecated = if member.deprecated {
+                " @deprecated"
+            } else {
+                ""
+            };
+            let _ = writeln!(out, "  {}{}", member.name, deprecated);
+        }
+        out.push_str("}\n\n");
+    }
+    let mut names: Vec<&String> = models.unions.keys().collect();
+    names.sort();
+    for name in names {
+        let variants: Vec<String> = models.unions[name]
+            .variants
+            .iter()
+            .map(|variant| pascal_case(variant))
+            .collect();
+        let _ = writeln!(
+            out,
+            "union {} = {}\n",
+            pascal_case(name),
+            variants.join(" | ")
+        );
+    }
+    let inputs = input_entities(project, models);
+    let mut names: Vec<&String> = models.entities.keys().collect();
+    names.sort();
+    for name in names {
+        let entity = &models.entities[name];
+        for input in [false, true] {
+            if input && !inputs.contains(name) {
+                continue;
+            }
+            let kind = if input { "input" } else { "type" };
+            types.input = input;
+            let _ = writeln!(out, "{} {} {{", kind, types.type_name(Some(models), name));
+            for field in entity.ordered_fields() {
+                description(out, "  ", &field.description);
+                let _ = write!(
+                    out,
+                    "  {}: {}",
+                    field.identifier,
+                    field_type(types, models, field)
+                );
+                // only input fields take default values
+                if let Some(literal) = default_literal(models, field).filter(|_| input) {
+                    let _ = write!(out, " = {}", value(&literal));
+                }
+                out.push('\n');
+            }
+            out.push_str("}\n\n");
+        }
+    }
+}
+
+/// Field of `Query` or `Mutation` for an operation
+fn operation_field(
+    out: &mut String,
+    types: &mut GraphQL,
+    project: &Project,
+    operation: &Operation,
+) {
+    let config = operation.config;
+    let models = project.models.as_ref();
+    // headers are not part of the schema
+    let mut arguments: Vec<String> = arguments(operation, camel_case)
+        .iter()
+        .filter(|a| a.location != "header")
+        .map(|a| {
+            let required = if a.required { "!" } else { "" };
+            format!("{}: {}{}", a.name, types.primitive(a.primitive), required)
+        })
+        .collect();
+    if !config.request_model.is_empty() {
+        types.input = true;
+        let input = types.type_name(models, &config.request_model);
+        let name = match arguments.iter().any(|a| a.starts_with("input:")) {
+            true => camel_case("input body"),
+            false => "input".to_owned(),
+        };
+        arguments.push(format!("{}: {}!", name, input));
+    }
+    types.input = false;
+    let response = match config.response_model.is_empty() {
+        true => "Boolean".to_owned(),
+        false => types.type_name(models, &config.response_model),
+    };
+    description(out, "  ", &config.description);
+    let arguments = match arguments.is_empty() {
+        true => String::new(),
+        false => format!("({})", arguments.join(", ")),
+    };
+    let _ = writeln!(out, "  {}{}: {}", operation.name, arguments, response);
+}
+
+/// SDL schema of the models and operations
+pub fn generate(project: &Project) -> String {
+    let mut types = GraphQL::default();
+    let mut body = String::new();
+    if let Some(models) = &project.models {
+        models_source(&mut body, &mut types, project, models);
+    }
+    let operations = operations(project);
+    for (root, read) in [("Query", true), ("Mutation", false)] {
+        let fields: Vec<&Operation> = operations
+            .iter()
+            .filter(|operation| (operation.verb == "get") == read)
+            .collect();
+        if fields.is_empty() {
+            continue;
+        }
+        let _ = writeln!(body, "type {} {{", root);
+        for operation in fields {
+            operation_field(&mut body, &mut types, project, operation);
+        }
+        body.push_str("}\n\n");
+    }
+
+    let mut out = format!(
+        "# Generated by apish from {} {}, do not edit\n\n",
+        project.title, project.version
+    );
+    if types.json {
+        let _ = writeln!(out, "scalar {}\n", JSON_SCALAR);
+    }
+    out.push_str(&body);
+    format!("{}\n", out.trim_end())
+}
+
+#[test]
+fn test_json_scalar_when_used() {
+    let api = "title: \"Notes\"\nversion: \"1\"\n\napis:\n  /notes:\n    get: \"Lists notes\"\n      response: Note\n";
+    let models = "struct Note {\n text string \"Format: JSON\"\n}\n";
+    let project = Project::new_from_sources(api, Some(models), None).unwrap();
+    assert!(!generate(&project).contains("scalar JSON"));
+    assert!(generate(&super::pets("")).contains("scalar JSON\n"));
+}
+
+#[test]
+fn test_arguments_and_defaults() {
+    use super::assert_blocks;
+
+    let api = "params:\n  id number: \"Id\"\nquery:\n  id string: \"Other id\"\n  input string: \"Input\"\n\napis:\n  /settings/{id}:\n    put: \"Saves settings\"\n      operation: save\n      params: id\n      query: id input\n      consumes: json\n      request: Settings\n";
+    let models = "struct Settings {\n theme string = \"dark\"\n retries? int = 3\n size Size = SMALL\n}\n\nenum Size int {\n SMALL = 1\n}\n";
+    let project = Project::new_from_sources(api, Some(models), None).unwrap();
+    assert_blocks(
+        &generate(&project),
+        &[
+            "type Settings {\n  theme: String!\n  retries: Int\n  size: Size!\n}\n",
+            "input SettingsInput {\n  theme: String! = \"dark\"\n  retries: Int = 3\n  size: Size! = SMALL\n}\n",
+            "type Mutation {\n  \"Saves settings\"\n  save(id: Float!, idQuery: String, input: String, inputBody: SettingsInput!): Boolean\n}\n",
+        ],
+    );
+}
+
+#[test]
+fn test_generate_graphql() {
+    use super::{assert_blocks, pets};
+
+    let source = generate(&pets(""));
+    assert_blocks(
+        &source,
+        &[
+            "scalar JSON\n",
+            "enum Kind {\n  DOG\n  CAT @deprecated\n}\n",
+            "union Payment = Card | Bank\n",
+            "type Pet {\n  id: Int!\n  \"Pet name\"\n  name: String!\n  type: Kind!\n  tags: [String!]\n  owner: Owner\n  parent: Pet\n  nickName: String\n  extra: JSON!\n  born: String!\n}\n",
+            "input PetInput {\n  id: Int!\n  \"Pet name\"\n  name: String!\n  type: Kind!\n  tags: [String!]\n  owner: OwnerInput\n  parent: PetInput\n  nickName: String\n  extra: JSON!\n  born: String!\n}\n",
+            "input OwnerInput {\n  name: String!\n}\n",
+            "type Query {\n  \"Finds a pet\"\n  getPet(id: Float!, pageSize: Float): Pet\n  \"Health\"\n  getHealth: Boolean\n}\n",
+            "type Mutation {\n  \"Updates a pet\"\n  updatePet(id: Float!, type: String, pageSize: Float, input: PetInput!): Pet\n  \"Removes a pet\"\n  deletePetsId(id: Float!): Boolean\n}\n",
+        ],
+    );
+}
@@ -0,0 +1,342 @@
+//! Code generators, one module per target language. Every target turns a `Project` and
+//! its models into a single source file
+
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use crate::models::{EnumMember, Field, ProjectModel, TypeKind};
+use crate::project::{APIConfiguration, DataType, Project, ProjectArgument};
+
+pub mod go;
+pub mod graphql;
+pub mod proto;
+pub mod rust;
+pub mod typescript;
+
+/// Target names accepted by `generate`
+pub const TARGETS: [&str; 5] = ["typescript", "rust", "go", "proto", "graphql"];
+
+/// Source of the project for a target
+pub fn generate(target: &str, project: &Project) -> Result<String, String> {
+    match target {
+        "typescript" | "ts" => Ok(typescript::generate(project)),
+        "rust" | "rs" => Ok(rust::generate(project)),
+        "go" | "golang" => Ok(go::generate(project)),
+        "proto" | "protobuf" => proto::generate(project),
+        "graphql" | "gql" => Ok(graphql::generate(project)),
+        _ => Err(format!(
+            "Unknown target `{}`, use one of {}",
+            target,
+            TARGETS.join(", ")
+        )),
+    }
+}
+
+/// Words of an identifier, split on anything but letters and digits and on lower to upper
+/// case changes
+pub fn words(text: &str) -> Vec<String> {
+    let mut words = vec![];
+    let mut current = String::new();
+    let mut previous_lower = false;
+    for c in text.chars() {
+        if !c.is_ascii_alphanumeric() {
+            if !current.is_empty() {
+                words.push(current.to_owned());
+                current.clear();
+            }
+            previous_lower = false;
+            continue;
+        }
+        if c.is_ascii_uppercase() && previous_lower {
+            words.push(current.to_owned());
+            current.clear();
+        }
+        previous_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        current.push(c);
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_uppercase().to_string() + &chars.as_str().to_lowercase(),
+        None => String::new(),
+    }
+}
+
+pub fn pascal_case(text: &str) -> String {
+    words(text).iter().map(|word| capitalize(word)).collect()
+}
+
+pub fn camel_case(text: &str) -> String {
+    let pascal = pascal_case(text);
+    let mut chars = pascal.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+        None => pascal,
+    }
+}
+
+pub fn snake_case(text: &str) -> String {
+    let words: Vec<String> = words(text).iter().map(|w| w.to_lowercase()).collect();
+    words.join("_")
+}
+
+/// Model primitive types, each target names them
+#[derive(Clone, Copy, PartialEq)]
+pub enum Primitive {
+    String,
+    Int,
+    Long,
+    Float,
+    Double,
+    Bool,
+}
+
+/// Primitive of a model type name, `date` and `datetime` are strings
+pub fn primitive(data_type: &str) -> Option<Primitive> {
+    match data_type {
+        "string" | "date" | "datetime" => Some(Primitive::String),
+        "int" | "integer" => Some(Primitive::Int),
+        "long" => Some(Primitive::Long),
+        "float" => Some(Primitive::Float),
+        "double" | "number" => Some(Primitive::Double),
+        "bool" | "boolean" => Some(Primitive::Bool),
+        _ => None,
+    }
+}
+
+/// Type names of a target
+pub trait Types {
+    fn primitive(&self, primitive: Primitive) -> &'static str;
+
+    /// Name of a declared struct, enum or union
+    fn model(&mut self, name: &str, _kind: TypeKind) -> String {
+        pascal_case(name)
+    }
+
+    /// Name of a type that is neither a primitive nor declared
+    fn unknown(&mut self) -> String;
+
+    fn type_name(&mut self, models: Option<&ProjectModel>, data_type: &str) -> String {
+        if let Some(primitive) = primitive(data_type) {
+            return self.primitive(primitive).to_owned();
+        }
+        match models.map_or(TypeKind::Unknown, |models| models.type_kind(data_type)) {
+            TypeKind::Primitive | TypeKind::Unknown => self.unknown(),
+            kind => self.model(data_type, kind),
+        }
+    }
+}
+
+/// Writes a one line comment between `open` and `close`, nothing when `text` is empty
+pub fn doc(out: &mut String, indent: &str, open: &str, text: &str, close: &str) {
+    if !text.is_empty() {
+        let _ = writeln!(out, "{}{} {}{}", indent, open, text, close);
+    }
+}
+
+/// Operation to generate, its name comes from `operation:` or from the verb and path
+pub struct Operation<'a> {
+    pub name: String,
+    pub verb: &'static str,
+    pub path: &'a str,
+    pub config: &'a APIConfiguration,
+    /// placeholders of the path with their declared param, when there is one
+    pub path_params: Vec<(String, Option<&'a ProjectArgument>)>,
+    pub query: Vec<&'a ProjectArgument>,
+    pub headers: Vec<&'a ProjectArgument>,
+}
+
+/// Operations in declaration order with unique camelCase names
+pub fn operations(project: &Project) -> Vec<Operation<'_>> {
+    let mut result: Vec<Operation> = vec![];
+    for (verb, path, config) in project.operations() {
+        let base = match config.operation.is_empty() {
+            true => camel_case(&format!("{} {}", verb, path)),
+            false => camel_case(&config.operation),
+        };
+        let mut name = base.to_owned();
+        let mut suffix = 2;
+        while result.iter().any(|operation| operation.name == name) {
+            name = format!("{}{}", base, suffix);
+            suffix += 1;
+        }
+        let declared = project.get_path_params(&config.path_params);
+        let path_params = path
+            .split('/')
+            .filter(|s| s.starts_with('{') && s.ends_with('}'))
+            .map(|s| {
+                let placeholder = s[1..s.len() - 1].to_owned();
+                let param = declared
+                    .iter()
+                    .find(|p| p.name == placeholder || p.alias == placeholder)
+                    .copied();
+                (placeholder, param)
+            })
+            .collect();
+        result.push(Operation {
+            name,
+            verb,
+            path,
+            config,
+            path_params,
+            query: project.get_query_strings(&config.query_string),
+            headers: project.get_headers(&config.headers),
+        });
+    }
+    result
+}
+
+/// Path param, query item or header of an operation
+pub struct Argument {
+    /// unique among the operation arguments, suffixed by the location on clashes
+    pub name: String,
+    pub wire: String,
+    pub location: &'static str,
+    pub primitive: Primitive,
+    pub required: bool,
+}
+
+/// Primitive of an argument, undeclared path params are strings
+pub fn argument_primitive(argument: Option<&ProjectArgument>) -> Primitive {
+    match argument.map(|a| &a.data_type) {
+        Some(DataType::Number) => Primitive::Double,
+        Some(DataType::Boolean) => Primitive::Bool,
+        _ => Primitive::String,
+    }
+}
+
+/// Path params, query items and headers of an operation named by `name`
+pub fn arguments(operation: &Operation, name: fn(&str) -> String) -> Vec<Argument> {
+    let mut arguments: Vec<Argument> = vec![];
+    let mut push = |wire: &str, location: &'static str, primitive, required| {
+        let mut unique = name(wire);
+        if arguments.iter().any(|a| a.name == unique) {
+            unique = name(&format!("{} {}", wire, location));
+        }
+        arguments.push(Argument {
+            name: unique,
+            wire: wire.to_owned(),
+            location,
+            primitive,
+            required,
+        });
+    };
+    for (placeholder, param) in &operation.path_params {
+        push(placeholder, "path", argument_primitive(*param), true);
+    }
+    for query in &operation.query {
+        push(
+            &query.name,
+            "query",
+            argument_primitive(Some(query)),
+            query.required,
+        );
+    }
+    for header in &operation.headers {
+        let primitive = argument_primitive(Some(header));
+        push(&header.name, "header", primitive, header.required);
+    }
+    arguments
+}
+
+/// Whether `from` holds `target` through fields that are not behind a `Vec` or a map
+pub fn reaches(
+    models: &ProjectModel,
+    from: &str,
+    target: &str,
+    visited: &mut HashSet<String>,
+) -> bool {
+    if from == target {
+        return true;
+    }
+    if !visited.insert(from.to_owned()) {
+        return false;
+    }
+    let next: Vec<&String> = match (models.entities.get(from), models.unions.get(from)) {
+        (Some(entity), _) => entity
+            .fields
+            .values()
+            .filter(|f| !f.is_array && !f.is_map)
+            .map(|f| &f.data_type)
+            .collect(),
+        (_, Some(union)) => union.variants.iter().collect(),
+        _ => vec![],
+    };
+    next.iter()
+        .any(|name| reaches(models, name, target, visited))
+}
+
+/// Default value of a field typed after the field
+pub enum Literal<'a> {
+    Text(&'a str),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    /// member of the enum named by the field type
+    Member(&'a EnumMember),
+}
+
+/// The `= value` of a primitive or enum field, `None` when it has none or it does not fit
+/// the type. Arrays, maps and structs get no default
+pub fn default_literal<'a>(models: &'a ProjectModel, field: &'a Field) -> Option<Literal<'a>> {
+    let value = field.default_value.as_str();
+    if value.is_empty() || field.is_array || field.is_map {
+        return None;
+    }
+    if let Some(definition) = models.enums.get(&field.data_type) {
+        return definition.member(value).map(Literal::Member);
+    }
+    match field.data_type.as_str() {
+        "string" | "date" | "datetime" => Some(Literal::Text(value)),
+        "int" | "integer" | "long" => value.parse().ok().map(Literal::Int),
+        "float" | "double" | "number" => value
+            .parse()
+            .ok()
+            .filter(|v: &f64| v.is_finite())
+            .map(Literal::Float),
+        "bool" | "boolean" => value.parse().ok().map(Literal::Bool),
+        _ => None,
+    }
+}
+
+/// Project every generator test builds from, `extra_models` is appended to its models
+#[cfg(test)]
+pub(crate) fn pets(extra_models: &str) -> Project {
+    let api = "title: \"Pets\"\nversion: \"1.0.0\"\nheaders:\n  x-auth string required: \"Token\"\nparams:\n  id number: \"Pet id\"\nquery:\n  type string: \"Kind filter\"\n  page-size number: \"Page size\"\n\napis:\n  /pets/{id}:\n    get: \"Finds a pet\"\n      operation: getPet\n      params: id\n      query: page-size\n      response: Pet\n    put: \"Updates a pet\"\n      operation: updatePet\n      headers: x-auth\n      params: id\n      query: type page-size\n      status_codes: 201\n      consumes: json\n      request: Pet\n      response: Pet\n    delete: \"Removes a pet\"\n      params: id\n  /health:\n    get: \"Health\"\n";
+    let models = "struct Base {\n id int\n}\n\nstruct Pet extends Base {\n name string `proto: 2` `go: FullName` `serde: alias = \"title\"` \"Pet name\"\n type Kind\n tags? []string\n owner Owner?\n parent Pet?\n nickName string?\n extra map[string]int\n born date `rust: String`\n}\n\nstruct Owner {\n name string\n}\n\nstruct Settings {\n theme string = \"dark\"\n retries? int = 3\n kind Kind? = DOG\n size Size? = SMALL\n}\n\nstruct Error {\n code int\n}\n\nenum Kind {\n DOG = \"dog\"\n CAT deprecated\n}\n\nenum Size int {\n SMALL = 1\n BIG\n}\n\nunion Payment = Card | Bank discriminator type\n\nstruct Card {\n type string required\n}\n\nstruct Bank {\n type string required\n}\n";
+    let models = format!("{}{}", models, extra_models);
+    Project::new_from_sources(api, Some(&models), None).unwrap()
+}
+
+/// Checks that each expected block is a whole declaration of `source`, blocks are separated
+/// by blank lines. A missing block is compared with the one sharing most leading lines
+#[cfg(test)]
+pub(crate) fn assert_blocks(source: &str, expected: &[&str]) {
+    let blocks: Vec<&str> = source.split("\n\n").map(|b| b.trim_matches('\n')).collect();
+    for block in expected {
+        let block = block.trim_matches('\n');
+        if blocks.contains(&block) {
+            continue;
+        }
+        let shared = |other: &&str| {
+            let lines = other.lines().zip(block.lines());
+            lines.take_while(|(a, b)| a == b).count()
+        };
+        let similar = blocks.iter().copied().max_by_key(shared);
+        assert_eq!(similar, Some(block), "in\n{}", source);
+    }
+}
+
+#[test]
+fn test_case_conversions() {
+    assert_eq!(words("getPet by-ID"), vec!["get", "Pet", "by", "ID"]);
+    assert_eq!(pascal_case("x-my-auth"), "XMyAuth");
+    assert_eq!(camel_case("get /api/contact/{id}"), "getApiContactId");
+    assert_eq!(snake_case("favoriteFood"), "favorite_food");
+}
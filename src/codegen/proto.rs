@@ -0,0 +1,496 @@
+//! Protocol Buffers target: proto3 messages and enums for the models and a gRPC service with
+//! `google.api.http` annotations for the operations
+//!
+//! The `proto:` field tag fixes the field number, other fields take the next free number in
+//! declaration order
+
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::ops::RangeInclusive;
+
+use crate::codegen::{
+    arguments, doc, operations, pascal_case, primitive, snake_case, Argument, Operation, Primitive,
+    Types,
+};
+use crate::models::{Entity, EnumMember, Field, ProjectModel};
+use crate::project::Project;
+
+const PACKAGE: &str = "api";
+
+/// Proto names of the model types, noting the imports they need
+#[derive(Default)]
+struct Imports {
+    empty: bool,
+    value: bool,
+}
+
+fn screaming_case(text: &str) -> String {
+    snake_case(text).to_uppercase()
+}
+
+impl Types for Imports {
+    fn primitive(&self, primitive: Primitive) -> &'static str {
+        match primitive {
+            Primitive::String => "string",
+            Primitive::Int => "int32",
+            Primitive::Long => "int64",
+            Primitive::Float => "float",
+            Primitive::Double => "double",
+            Primitive::Bool => "bool",
+        }
+    }
+
+    fn unknown(&mut self) -> String {
+        self.value = true;
+        "google.protobuf.Value".to_owned()
+    }
+}
+
+/// `json_name` option when the proto field name changes the wire name
+fn json_name(wire: &str, name: &str) -> String {
+    match wire == name {
+        true => String::new(),
+        false => format!(" [json_name = {:?}]", wire),
+    }
+}
+
+/// Highest field number protobuf accepts
+const MAX_FIELD_NUMBER: u32 = 536_870_911;
+
+/// Field numbers protobuf keeps for itself
+const RESERVED_FIELD_NUMBERS: RangeInclusive<u32> = 19_000..=19_999;
+
+/// Field numbers from the `proto:` tags, the rest numbered in order skipping taken and
+/// reserved ones. Tags that are not a free and valid number are an error
+fn field_numbers(entity: &str, fields: &[&Field]) -> Result<Vec<u32>, String> {
+    let mut tagged: Vec<Option<u32>> = vec![];
+    for field in fields {
+        let tag = match field.tags.get("proto") {
+            Some(tag) => tag.trim(),
+            None => {
+                tagged.push(None);
+                continue;
+            }
+        };
+        let number = tag
+            .parse::<u32>()
+            .ok()
+            .filter(|number| (1..=MAX_FIELD_NUMBER).contains(number))
+            .ok_or_else(|| {
+                format!(
+                    "Invalid proto field number `{}` of `{}.{}`, use 1 to {}",
+                    tag, entity, field.identifier, MAX_FIELD_NUMBER
+                )
+            })?;
+        if RESERVED_FIELD_NUMBERS.contains(&number) {
+            return Err(format!(
+                "Proto field number {} of `{}.{}` is reserved, 19000 to 19999 cannot be used",
+                number, entity, field.identifier
+            ));
+        }
+        if tagged.contains(&Some(number)) {
+            return Err(format!(
+                "Duplicate proto field number {} in `{}`",
+                number, entity
+            ));
+        }
+        tagged.push(Some(number));
+    }
+    let mut next = 1;
+    Ok(tagged
+        .iter()
+        .map(|number| match number {
+            Some(number) => *number,
+            None => {
+                while tagged.contains(&Some(next)) || RESERVED_FIELD_NUMBERS.contains(&next) {
+                    next += 1;
+                }
+                next += 1;
+                next - 1
+            }
+        })
+        .collect())
+}
+
+fn message_source(
+    out: &mut String,
+    models: &ProjectModel,
+    name: &str,
+    entity: &Entity,
+    imports: &mut Imports,
+) -> Result<(), String> {
+    let _ = writeln!(out, "message {} {{", pascal_case(name));
+    let fields = entity.ordered_fields();
+    for (field, number) in fields.iter().zip(field_numbers(name, &fields)?) {
+        let mut field_type = imports.type_name(Some(models), &field.data_type);
+        let mut label = "";
+        if field.is_map {
+            if field.is_array {
+                // map values cannot be repeated
+                imports.value = true;
+                field_type = "google.protobuf.ListValue".to_owned();
+            }
+            // floating point keys are not allowed
+            let key_type = primitive(&field.map_key_type)
+                .filter(|key| !matches!(key, Primitive::Float | Primitive::Double))
+                .map_or("string", |key| imports.primitive(key));
+            field_type = format!("map<{}, {}>", key_type, field_type);
+        } else if field.is_array {
+            label = "repeated ";
+        } else if (field.is_optional || field.is_nullable)
+            && (primitive(&field.data_type).is_some()
+                || models.enums.contains_key(&field.data_type))
+        {
+            label = "optional ";
+        }
+        let field_name = snake_case(&field.identifier);
+        doc(out, "  ", "//", &field.description, "");
+        let _ = writeln!(
+            out,
+            "  {}{} {} = {}{};",
+            label,
+            field_type,
+            field_name,
+            number,
+            json_name(&field.identifier, &field_name)
+        );
+    }
+    out.push_str("}\n\n");
+    Ok(())
+}
+
+fn models_source(
+    out: &mut String,
+    models: &ProjectModel,
+    imports: &mut Imports,
+) -> Result<(), String> {
+    let mut names: Vec<&String> = models.enums.keys().collect();
+    names.sort();
+    for name in names {
+        let definition = &models.enums[name];
+        let prefix = screaming_case(name);
+        let _ = writeln!(out, "enum {} {{", pascal_case(name));
+        // proto3 enums start with zero, string enums number their members in order
+        let numbered =
+            definition.value_type == "int" && definition.members.iter().any(|m| m.value == "0");
+        if !numbered {
+            let _ = writeln!(out, "  {}_UNSPECIFIED = 0;", prefix);
+        }
+        let mut members: Vec<(usize, &EnumMember)> =
+            definition.members.iter().enumerate().collect();
+        members.sort_by_key(|(_, member)| numbered && member.value != "0");
+        for (index, member) in members {
+            let number = match definition.value_type == "int" {
+                true => match member.value.parse::<i32>() {
+                    Ok(number) => number.to_string(),
+                    Err(_) => {
+                        return Err(format!(
+                            "Value {} of `{}.{}` does not fit a proto enum, use {} to {}",
+                            member.value,
+                            name,
+                            member.name,
+                            i32::MIN,
+                            i32::MAX
+                        ))
+                    }
+                },
+                false => (index + 1).to_string(),
+            };
+            let option = if member.deprecated {
+                " [deprecated = true]"
+            } else {
+                ""
+            };
+            doc(out, "  ", "//", &member.description, "");
+            let _ = writeln!(
+                out,
+                "  {}_{} = {}{};",
+                prefix,
+                screaming_case(&member.name),
+                number,
+                option
+            );
+        }
+        out.push_str("}\n\n");
+    }
+    let mut names: Vec<&String> = models.unions.keys().collect();
+    names.sort();
+    for name in names {
+        let union = &models.unions[name];
+        let _ = writeln!(out, "message {} {{\n  oneof value {{", pascal_case(name));
+        for (index, variant) in union.variants.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "    {} {} = {};",
+                imports.type_name(Some(models), variant),
+                snake_case(variant),
+                index + 1
+            );
+        }
+        out.push_str("  }\n}\n\n");
+    }
+    let mut names: Vec<&String> = models.entities.keys().collect();
+    names.sort();
+    for name in names {
+        message_source(out, models, name, &models.entities[name], imports)?;
+    }
+    Ok(())
+}
+
+/// Request message of an operation and its `google.api.http` path template
+fn request_source(
+    out: &mut String,
+    project: &Project,
+    operation: &Operation,
+    message: &str,
+    imports: &mut Imports,
+) -> (String, String) {
+    let config = operation.config;
+    let mut path = operation.path.to_owned();
+    // headers travel as gRPC metadata
+    let arguments: Vec<Argument> = arguments(operation, snake_case)
+        .into_iter()
+        .filter(|a| a.location != "header")
+        .collect();
+    let _ = writeln!(out, "message {} {{", message);
+    for (index, argument) in arguments.iter().enumerate() {
+        let label = match argument.location {
+            "query" if !argument.required => "optional ",
+            _ => "",
+        };
+        let json_name = match argument.location {
+            "path" => {
+                let placeholder = format!("{{{}}}", argument.wire);
+                path = path.replace(&placeholder, &format!("{{{}}}", argument.name));
+                String::new()
+            }
+            _ => json_name(&argument.wire, &argument.name),
+        };
+        let _ = writeln!(
+            out,
+            "  {}{} {} = {}{};",
+            label,
+            imports.primitive(argument.primitive),
+            argument.name,
+            index + 1,
+            json_name
+        );
+    }
+    let body = match arguments.iter().any(|a| a.name == "body") {
+        true => snake_case("body request"),
+        false => "body".to_owned(),
+    };
+    if !config.request_model.is_empty() {
+        let _ = writeln!(
+            out,
+            "  {} {} = {};",
+            imports.type_name(project.models.as_ref(), &config.request_model),
+            body,
+            arguments.len() + 1
+        );
+    }
+    out.push_str("}\n\n");
+    (path, body)
+}
+
+/// `<Operation>Request` message names, numbered when a model or an earlier request message
+/// already has the name
+fn request_names(project: &Project, operations: &[Operation]) -> Vec<String> {
+    let mut taken: HashSet<String> = HashSet::new();
+    if let Some(models) = &project.models {
+        let names = models.entities.keys().chain(models.enums.keys());
+        taken.extend(
+            names
+                .chain(models.unions.keys())
+                .map(|name| pascal_case(name)),
+        );
+    }
+    operations
+        .iter()
+        .map(|operation| {
+            let base = format!("{}Request", pascal_case(&operation.name));
+            let mut name = base.to_owned();
+            let mut suffix = 2;
+            while !taken.insert(name.to_owned()) {
+                name = format!("{}{}", base, suffix);
+                suffix += 1;
+            }
+            name
+        })
+        .collect()
+}
+
+fn service_name(project: &Project) -> String {
+    match pascal_case(&project.title).as_str() {
+        "" => "Api".to_owned(),
+        name => name.to_owned(),
+    }
+}
+
+/// proto3 file with the models as messages and the operations as a gRPC service
+pub fn generate(project: &Project) -> Result<String, String> {
+    let mut imports = Imports::default();
+    let mut body = String::new();
+    if let Some(models) = &project.models {
+        models_source(&mut body, models, &mut imports)?;
+    }
+    let operations = operations(project);
+    let mut rpcs = String::new();
+    let requests = request_names(project, &operations);
+    for (operation, request) in operations.iter().zip(&requests) {
+        let config = operation.config;
+        let name = pascal_case(&operation.name);
+        let (path, body_field) =
+            request_source(&mut body, project, operation, request, &mut imports);
+        let response = match config.response_model.is_empty() {
+            true => {
+                imports.empty = true;
+                "google.protobuf.Empty".to_owned()
+            }
+            false => imports.type_name(project.models.as_ref(), &config.response_model),
+        };
+        doc(&mut rpcs, "  ", "//", &config.description, "");
+        let _ = writeln!(
+            rpcs,
+            "  rpc {name}({request}) returns ({response}) {{\n    option (google.api.http) = {{\n      {verb}: {path:?}",
+            name = name,
+            request = request,
+            response = response,
+            verb = operation.verb,
+            path = path
+        );
+        if !config.request_model.is_empty() && operation.verb != "get" && operation.verb != "delete"
+        {
+            let _ = writeln!(rpcs, "      body: {:?}", body_field);
+        }
+        rpcs.push_str("    };\n  }\n");
+    }
+
+    let mut out = format!(
+        "// Generated by apish from {} {}, do not edit\n\nsyntax = \"proto3\";\n\npackage {};\n\n",
+        project.title, project.version, PACKAGE
+    );
+    let mut files = vec![];
+    if !operations.is_empty() {
+        files.push("google/api/annotations.proto");
+    }
+    if imports.empty {
+        files.push("google/protobuf/empty.proto");
+    }
+    if imports.value {
+        files.push("google/protobuf/struct.proto");
+    }
+    for file in &files {
+        let _ = writeln!(out, "import {:?};", file);
+    }
+    if !files.is_empty() {
+        out.push('\n');
+    }
+    out.push_str(&body);
+    if !operations.is_empty() {
+        let _ = write!(out, "service {} {{\n{}}}\n", service_name(project), rpcs);
+    }
+    Ok(format!("{}\n", out.trim_end()))
+}
+
+#[test]
+fn test_field_numbers() {
+    use super::pets;
+
+    for (tag, error) in [
+        (
+            "x",
+            "Invalid proto field number `x` of `Tagged.b`, use 1 to 536870911",
+        ),
+        (
+            "0",
+            "Invalid proto field number `0` of `Tagged.b`, use 1 to 536870911",
+        ),
+        (
+            "19500",
+            "Proto field number 19500 of `Tagged.b` is reserved, 19000 to 19999 cannot be used",
+        ),
+        ("1", "Duplicate proto field number 1 in `Tagged`"),
+    ] {
+        let models = format!(
+            "struct Tagged {{\n a int `proto: 1`\n b int `proto: {}`\n}}\n",
+            tag
+        );
+        assert_eq!(generate(&pets(&models)), Err(error.to_owned()));
+    }
+}
+
+#[test]
+fn test_request_names() {
+    use super::{assert_blocks, pets};
+
+    let source = generate(&pets("struct UpdatePetRequest {\n id int\n}\n")).unwrap();
+    assert_blocks(
+        &source,
+        &[
+            "message UpdatePetRequest {\n  int32 id = 1;\n}\n",
+            "message UpdatePetRequest2 {\n  double id = 1;\n  optional string type = 2;\n  optional double page_size = 3 [json_name = \"page-size\"];\n  Pet body = 4;\n}\n",
+        ],
+    );
+    assert!(source.contains("  rpc UpdatePet(UpdatePetRequest2) returns (Pet) {\n"));
+}
+
+#[test]
+fn test_request_fields() {
+    use super::assert_blocks;
+
+    let api = "params:\n  id number: \"Pet id\"\nquery:\n  id string: \"Other id\"\n  body string: \"Body\"\n\napis:\n  /pets/{id}:\n    put: \"Updates a pet\"\n      operation: update\n      params: id\n      query: id body\n      consumes: json\n      request: Pet\n";
+    let models = "struct Pet {\n name string\n}\n";
+    let project = Project::new_from_sources(api, Some(models), None).unwrap();
+    let source = generate(&project).unwrap();
+    assert_blocks(
+        &source,
+        &["message UpdateRequest {\n  double id = 1;\n  optional string id_query = 2 [json_name = \"id\"];\n  optional string body = 3;\n  Pet body_request = 4;\n}\n"],
+    );
+    assert!(source.contains("      put: \"/pets/{id}\"\n      body: \"body_request\"\n"));
+}
+
+#[test]
+fn test_enum_int32() {
+    use super::pets;
+
+    let error = generate(&pets("enum Huge int {\n BIG = 2147483648\n}\n")).unwrap_err();
+    assert_eq!(
+        error,
+        "Value 2147483648 of `Huge.BIG` does not fit a proto enum, use -2147483648 to 2147483647"
+    );
+}
+
+#[test]
+fn test_enum_zero_first() {
+    use super::{assert_blocks, pets};
+
+    let source = generate(&pets(
+        "enum Level int {\n LOW = 1\n NONE = 0\n HIGH = 2\n}\n",
+    ))
+    .unwrap();
+    assert_blocks(
+        &source,
+        &["enum Level {\n  LEVEL_NONE = 0;\n  LEVEL_LOW = 1;\n  LEVEL_HIGH = 2;\n}\n"],
+    );
+}
+
+#[test]
+fn test_generate_proto() {
+    use super::{assert_blocks, pets};
+
+    let source = generate(&pets("")).unwrap();
+    assert_blocks(
+        &source,
+        &[
+            "import \"google/api/annotations.proto\";\nimport \"google/protobuf/empty.proto\";\n",
+            "enum Kind {\n  KIND_UNSPECIFIED = 0;\n  KIND_DOG = 1;\n  KIND_CAT = 2 [deprecated = true];\n}\n",
+            "enum Size {\n  SIZE_UNSPECIFIED = 0;\n  SIZE_SMALL = 1;\n  SIZE_BIG = 2;\n}\n",
+            "message Payment {\n  oneof value {\n    Card card = 1;\n    Bank bank = 2;\n  }\n}\n",
+            "message Pet {\n  int32 id = 1;\n  // Pet name\n  string name = 2;\n  Kind type = 3;\n  repeated string tags = 4;\n  Owner owner = 5;\n  Pet parent = 6;\n  optional string nick_name = 7 [json_name = \"nickName\"];\n  map<string, int32> extra = 8;\n  string born = 9;\n}\n",
+            "message Settings {\n  string theme = 1;\n  optional int32 retries = 2;\n  optional Kind kind = 3;\n  optional Size size = 4;\n}\n",
+            "message UpdatePetRequest {\n  double id = 1;\n  optional string type = 2;\n  optional double page_size = 3 [json_name = \"page-size\"];\n  Pet body = 4;\n}\n",
+            "service Pets {\n  // Finds a pet\n  rpc GetPet(GetPetRequest) returns (Pet) {\n    option (google.api.http) = {\n      get: \"/pets/{id}\"\n    };\n  }\n  // Updates a pet\n  rpc UpdatePet(UpdatePetRequest) returns (Pet) {\n    option (google.api.http) = {\n      put: \"/pets/{id}\"\n      body: \"body\"\n    };\n  }\n  // Removes a pet\n  rpc DeletePetsId(DeletePetsIdRequest) returns (google.protobuf.Empty) {\n    option (google.api.http) = {\n      delete: \"/pets/{id}\"\n    };\n  }\n  // Health\n  rpc GetHealth(GetHealthRequest) returns (google.protobuf.Empty) {\n    option (google.api.http) = {\n      get: \"/health\"\n    };\n  }\n}\n",
+        ],
+    );
+}
@@ -0,0 +1,710 @@
+//! Rust target: serde models, an `Api` trait with an async method per operation, a reqwest
+//! `client::Client` implementing it and an axum `server::router` serving any implementation
+//!
+//! Field tags honoured: `serde:` is added to the field `#[serde(...)]` attribute and `rust:`
+//! replaces the field type
+
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use crate::codegen::{
+    arguments, default_literal, doc, operations, pascal_case, primitive, reaches, snake_case,
+    Argument, Literal, Operation, Primitive, Types,
+};
+use crate::models::{Field, ProjectModel};
+use crate::project::Project;
+
+const KEYWORDS: [&str; 52] = [
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type",
+    "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+const RUNTIME: &str = "/// Failed call, `status` is zero when the service could not be reached
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiError {
+    pub status: u16,
+    pub message: String,
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, \"{} {}\", self.status, self.message)
+    }
+}
+
+impl std::error::Error for ApiError {}
+";
+
+const CLIENT_RUNTIME: &str = "    /// Calls the service with reqwest
+    #[derive(Debug, Clone)]
+    pub struct Client {
+        pub base_url: String,
+        pub http: reqwest::Client,
+    }
+
+    impl Client {
+        pub fn new(base_url: impl Into<String>) -> Client {
+            Client {
+                base_url: base_url.into().trim_end_matches('/').to_owned(),
+                http: reqwest::Client::new(),
+            }
+        }
+    }
+
+    fn encode(value: &str) -> String {
+        value
+            .bytes()
+            .map(|b| match b {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                    (b as char).to_string()
+                }
+                _ => format!(\"%{:02X}\", b),
+            })
+            .collect()
+    }
+
+    fn failure(error: reqwest::Error) -> ApiError {
+        ApiError {
+            status: error.status().map_or(0, |s| s.as_u16()),
+            message: error.to_string(),
+        }
+    }
+
+    async fn read<T: serde::de::DeserializeOwned>(response: reqwest::Response) -> Result<T, ApiError> {
+        let status = response.status().as_u16();
+        let text = response.text().await.map_err(failure)?;
+        if !(200..300).contains(&status) {
+            return Err(ApiError { status, message: text });
+        }
+        serde_json::from_str(&text).map_err(|e| ApiError {
+            status,
+            message: e.to_string(),
+        })
+    }
+
+    async fn read_empty(response: reqwest::Response) -> Result<(), ApiError> {
+        let status = response.status().as_u16();
+        if !(200..300).contains(&status) {
+            let message = response.text().await.map_err(failure)?;
+            return Err(ApiError { status, message });
+        }
+        Ok(())
+    }
+";
+
+const SERVER_RUNTIME: &str = "    impl axum::response::IntoResponse for ApiError {
+        fn into_response(self) -> axum::response::Response {
+            let status = axum::http::StatusCode::from_u16(self.status)
+                .unwrap_or(axum::http::StatusCode::INTERNAL_SERVER_ERROR);
+            (status, self.message).into_response()
+        }
+    }
+
+    fn argument<T: std::str::FromStr>(value: Option<&str>, name: &str) -> Result<Option<T>, ApiError> {
+        match value {
+            Some(value) => value.parse().map(Some).map_err(|_| ApiError {
+                status: 400,
+                message: format!(\"Invalid {}\", name),
+            }),
+            None => Ok(None),
+        }
+    }
+
+    fn required<T>(value: Option<T>, name: &str) -> Result<T, ApiError> {
+        value.ok_or_else(|| ApiError {
+            status: 400,
+            message: format!(\"Missing {}\", name),
+        })
+    }
+
+    fn header<'a>(headers: &'a axum::http::HeaderMap, name: &str) -> Option<&'a str> {
+        headers.get(name).and_then(|value| value.to_str().ok())
+    }
+";
+
+/// Field or argument name, raw when it is a keyword
+fn identifier(name: &str) -> String {
+    let name = snake_case(name);
+    let name = match name.chars().next() {
+        Some(c) if c.is_ascii_digit() => format!("_{}", name),
+        Some(_) => name,
+        None => "value".to_owned(),
+    };
+    match KEYWORDS.contains(&name.as_str()) {
+        true if name == "self" || name == "Self" || name == "crate" || name == "super" => {
+            format!("{}_", name)
+        }
+        true => format!("r#{}", name),
+        false => name,
+    }
+}
+
+/// Rust names of the model types
+struct Rust;
+
+impl Types for Rust {
+    fn primitive(&self, primitive: Primitive) -> &'static str {
+        match primitive {
+            Primitive::String => "String",
+            Primitive::Int | Primitive::Long => "i64",
+            Primitive::Float | Primitive::Double => "f64",
+            Primitive::Bool => "bool",
+        }
+    }
+
+    fn unknown(&mut self) -> String {
+        "serde_json::Value".to_owned()
+    }
+}
+
+fn field_type(models: &ProjectModel, entity: &str, field: &Field) -> String {
+    if let Some(rust_type) = field.tags.get("rust") {
+        return rust_type.to_owned();
+    }
+    let mut name = Rust.type_name(Some(models), &field.data_type);
+    if field.is_array {
+        name = format!("Vec<{}>", name);
+    } else if !field.is_map && reaches(models, &field.data_type, entity, &mut HashSet::new()) {
+        name = format!("Box<{}>", name);
+    }
+    if field.is_map {
+        let key_type = primitive(&field.map_key_type).map_or("String", |p| Rust.primitive(p));
+        name = format!("std::collections::HashMap<{}, {}>", key_type, name);
+    }
+    if field.is_optional || field.is_nullable {
+        name = format!("Option<{}>", name);
+    }
+    name
+}
+
+fn enum_source(out: &mut String, models: &ProjectModel, name: &str) {
+    let definition = &models.enums[name];
+    let type_name = pascal_case(name);
+    let variants: Vec<String> = definition
+        .members
+        .iter()
+        .map(|m| pascal_case(&m.name))
+        .collect();
+    if definition.value_type == "int" {
+        let _ = writeln!(
+            out,
+            "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]\n#[serde(try_from = \"i64\", into = \"i64\")]\npub enum {} {{",
+            type_name
+        );
+    } else {
+        let _ = writeln!(
+            out,
+            "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]\npub enum {} {{",
+            type_name
+        );
+    }
+    for (member, variant) in definition.members.iter().zip(&variants) {
+        doc(out, "    ", "///", &member.description, "");
+        if member.deprecated {
+            out.push_str("    #[deprecated]\n");
+        }
+        if definition.value_type == "int" {
+            let _ = writeln!(out, "    {} = {},", variant, member.value);
+        } else {
+            let _ = writeln!(
+                out,
+                "    #[serde(rename = {:?})]\n    {},",
+                member.value, variant
+            );
+        }
+    }
+    out.push_str("}\n\n");
+    if definition.value_type != "int" {
+        return;
+    }
+    let _ = writeln!(
+        out,
+        "impl From<{name}> for i64 {{\n    fn from(value: {name}) -> i64 {{\n        value as i64\n    }}\n}}\n",
+        name = type_name
+    );
+    let _ = writeln!(
+        out,
+        "impl TryFrom<i64> for {name} {{\n    type Error = String;\n\n    fn try_from(value: i64) -> Result<Self, Self::Error> {{\n        match value {{",
+        name = type_name
+    );
+    for (member, variant) in definition.members.iter().zip(&variants) {
+        let _ = writeln!(
+            out,
+            "            {} => Ok({}::{}),",
+            member.value, type_name, variant
+        );
+    }
+    let _ = writeln!(
+        out,
+        "            _ => Err(format!(\"Invalid {} {{}}\", value)),\n        }}\n    }}\n}}\n",
+        name
+    );
+}
+
+/// `Deserialize` of a union with a discriminator, the field holds the variant name as in
+/// the OpenAPI default mapping. Untagged decoding would pick the first variant that fits
+fn union_deserialize_source(
+    out: &mut String,
+    name: &str,
+    variants: &[String],
+    discriminator: &str,
+) {
+    let type_name = pascal_case(name);
+    let _ = writeln!(
+        out,
+        "impl<'de> Deserialize<'de> for {} {{\n    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {{\n        use serde::de::Error;\n        let value = serde_json::Value::deserialize(deserializer)?;\n        match value.get({:?}).and_then(serde_json::Value::as_str) {{",
+        type_name, discriminator
+    );
+    for variant in variants {
+        let _ = writeln!(
+            out,
+            "            Some({:?}) => serde_json::from_value(value).map(Self::{}).map_err(D::Error::custom),",
+            variant,
+            pascal_case(variant)
+        );
+    }
+    let _ = writeln!(
+        out,
+        "            Some(other) => Err(D::Error::custom(format!(\"Unknown {} {} `{{}}`\", other))),\n            None => Err(D::Error::missing_field({:?})),\n        }}\n    }}\n}}\n",
+        name, discriminator, discriminator
+    );
+}
+
+fn models_source(out: &mut String, models: &ProjectModel) {
+    let mut names: Vec<&String> = models.enums.keys().collect();
+    names.sort();
+    for name in names {
+        enum_source(out, models, name);
+    }
+    let mut names: Vec<&String> = models.unions.keys().collect();
+    names.sort();
+    for name in names {
+        let union = &models.unions[name];
+        let derives = match union.discriminator.is_empty() {
+            true => "Serialize, Deserialize",
+            false => "Serialize",
+        };
+        let _ = writeln!(
+            out,
+            "#[derive(Debug, Clone, PartialEq, {})]\n#[serde(untagged)]\npub enum {} {{",
+            derives,
+            pascal_case(name)
+        );
+        for variant in &union.variants {
+            let variant_type = Rust.type_name(Some(models), variant);
+            let _ = writeln!(out, "    {}({}),", pascal_case(variant), variant_type);
+        }
+        out.push_str("}\n\n");
+        if !union.discriminator.is_empty() {
+            union_deserialize_source(out, name, &union.variants, &union.discriminator);
+        }
+    }
+    let mut names: Vec<&String> = models.entities.keys().collect();
+    names.sort();
+    for name in names {
+        let entity = &models.entities[name];
+        let _ = writeln!(
+            out,
+            "#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]\npub struct {} {{",
+            pascal_case(name)
+        );
+        let mut defaults = vec![];
+        for field in entity.ordered_fields() {
+            doc(out, "    ", "///", &field.description, "");
+            let field_name = identifier(&field.identifier);
+            let mut attributes = vec![];
+            let serde_tag = field.tags.get("serde").map_or("", |t| t.as_str());
+            if field_name.trim_start_matches("r#") != field.identifier
+                && !serde_tag.contains("rename")
+            {
+                attributes.push(format!("rename = {:?}", field.identifier));
+            }
+            let default = match serde_tag.contains("default") {
+                true => None,
+                false => default_function(models, name, field),
+            };
+            if let Some((function, _)) = &default {
+                attributes.push(format!("default = {:?}", function));
+            } else if field.is_optional && !serde_tag.contains("default") {
+                attributes.push("default".to_owned());
+            }
+            if field.is_optional && !serde_tag.contains("default") {
+                attributes.push("skip_serializing_if = \"Option::is_none\"".to_owned());
+            }
+            defaults.extend(default);
+            if !serde_tag.is_empty() {
+                attributes.push(serde_tag.to_owned());
+            }
+            if !attributes.is_empty() {
+                let _ = writeln!(out, "    #[serde({})]", attributes.join(", "));
+            }
+            let _ = writeln!(
+                out,
+                "    pub {}: {},",
+                field_name,
+                field_type(models, name, field)
+            );
+        }
+        out.push_str("}\n\n");
+        for (_, function) in defaults {
+            let _ = writeln!(out, "{}", function);
+        }
+    }
+}
+
+/// Name and source of the function giving the `= value` default of a field
+fn default_function(
+    models: &ProjectModel,
+    entity: &str,
+    field: &Field,
+) -> Option<(String, String)> {
+    if field.tags.contains_key("rust") {
+        return None;
+    }
+    let mut value = match default_literal(models, field)? {
+        Literal::Text(text) => format!("{:?}.to_owned()", text),
+        Literal::Int(number) => number.to_string(),
+        Literal::Float(number) => format!("{:?}", number),
+        Literal::Bool(value) => value.to_string(),
+        Literal::Member(member) => format!(
+            "{}::{}",
+            pascal_case(&field.data_type),
+            pascal_case(&member.name)
+        ),
+    };
+    if field.is_optional || field.is_nullable {
+        value = format!("Some({})", value);
+    }
+    let name = format!(
+        "default_{}_{}",
+        snake_case(entity),
+        snake_case(&field.identifier)
+    );
+    let source = format!(
+        "fn {}() -> {} {{\n    {}\n}}\n",
+        name,
+        field_type(models, entity, field),
+        value
+    );
+    Some((name, source))
+}
+
+fn params_type(operation: &Operation) -> String {
+    format!("{}Params", pascal_case(&operation.name))
+}
+
+/// `(&self, params, body)` part of a method signature
+fn signature(project: &Project, operation: &Operation) -> String {
+    let mut signature = String::from("&self");
+    if !arguments(operation, identifier).is_empty() {
+        let _ = write!(signature, ", params: {}", params_type(operation));
+    }
+    if !operation.config.request_model.is_empty() {
+        let body = Rust.type_name(project.models.as_ref(), &operation.config.request_model);
+        let _ = write!(signature, ", body: {}", body);
+    }
+    signature
+}
+
+fn response_type(project: &Project, operation: &Operation) -> String {
+    match operation.config.response_model.is_empty() {
+        true => "()".to_owned(),
+        false => Rust.type_name(project.models.as_ref(), &operation.config.response_model),
+    }
+}
+
+fn params_source(out: &mut String, operation: &Operation) {
+    let arguments = arguments(operation, identifier);
+    if arguments.is_empty() {
+        return;
+    }
+    let _ = writeln!(
+        out,
+        "/// Path params, query items and headers of `{}`\n#[derive(Debug, Clone, PartialEq, Default)]\npub struct {} {{",
+        operation.name,
+        params_type(operation)
+    );
+    for argument in &arguments {
+        let rust_type = match argument.required {
+            true => Rust.primitive(argument.primitive).to_owned(),
+            false => format!("Option<{}>", Rust.primitive(argument.primitive)),
+        };
+        let _ = writeln!(out, "    pub {}: {},", argument.name, rust_type);
+    }
+    out.push_str("}\n\n");
+}
+
+fn trait_source(out: &mut String, project: &Project, operations: &[Operation]) {
+    out.push_str("/// Operations of the API, implemented by `client::Client` and served by `server::router`\npub trait Api {\n");
+    for operation in operations {
+        doc(out, "    ", "///", &operation.config.description, "");
+        let _ = writeln!(
+            out,
+            "    fn {}({}) -> impl std::future::Future<Output = Result<{}, ApiError>> + Send;",
+            identifier(&operation.name),
+            signature(project, operation),
+            response_type(project, operation)
+        );
+    }
+    out.push_str("}\n\n");
+}
+
+fn client_source(out: &mut String, project: &Project, operations: &[Operation]) {
+    out.push_str("pub mod client {\n    use super::*;\n\n");
+    out.push_str(CLIENT_RUNTIME);
+    out.push_str("\n    impl Api for Client {\n");
+    for operation in operations {
+        let arguments = arguments(operation, identifier);
+        let _ = writeln!(
+            out,
+            "        async fn {}({}) -> Result<{}, ApiError> {{",
+            identifier(&operation.name),
+            signature(project, operation),
+            response_type(project, operation)
+        );
+        let mut path = operation.path.to_owned();
+        let mut values = vec![];
+        for argument in arguments.iter().filter(|a| a.location == "path") {
+            path = path.replace(&format!("{{{}}}", argument.wire), "{}");
+            values.push(format!("encode(&params.{}.to_string())", argument.name));
+        }
+        let _ = writeln!(
+            out,
+            "            let url = format!(\"{{}}{}\", self.base_url{});",
+            path,
+            values
+                .iter()
+                .map(|v| format!(", {}", v))
+                .collect::<String>()
+        );
+        let query: Vec<&Argument> = arguments.iter().filter(|a| a.location == "query").collect();
+        let modified = !query.is_empty()
+            || arguments.iter().any(|a| a.location == "header")
+            || !operation.config.request_model.is_empty();
+        let _ = writeln!(
+            out,
+            "            let {}request = self.http.request(reqwest::Method::{}, url);",
+            if modified { "mut " } else { "" },
+            operation.verb.to_uppercase()
+        );
+
+        if !query.is_empty() {
+            out.push_str("            let mut query: Vec<(&str, String)> = vec![];\n");
+            for argument in &query {
+                match argument.required {
+                    true => {
+                        let _ = writeln!(
+                            out,
+                            "            query.push(({:?}, params.{}.to_string()));",
+                            argument.wire, argument.name
+                        );
+                    }
+                    false => {
+                        let _ = writeln!(
+                            out,
+                            "            if let Some(value) = &params.{} {{\n                query.push(({:?}, value.to_string()));\n            }}",
+                            argument.name, argument.wire
+                        );
+                    }
+                }
+            }
+            out.push_str("            request = request.query(&query);\n");
+        }
+        for argument in arguments.iter().filter(|a| a.location == "header") {
+            match argument.required {
+                true => {
+                    let _ = writeln!(
+                        out,
+                        "            request = request.header({:?}, params.{}.to_string());",
+                        argument.wire, argument.name
+                    );
+                }
+                false => {
+                    let _ = writeln!(
+                        out,
+                        "            if let Some(value) = &params.{} {{\n                request = request.header({:?}, value.to_string());\n            }}",
+                        argument.name, argument.wire
+                    );
+                }
+            }
+        }
+        if !operation.config.request_model.is_empty() {
+            out.push_str("            request = request.json(&body);\n");
+        }
+        out.push_str("            let response = request.send().await.map_err(failure)?;\n");
+        match operation.config.response_model.is_empty() {
+            true => out.push_str("            read_empty(response).await\n"),
+            false => out.push_str("            read(response).await\n"),
+        }
+        out.push_str("        }\n\n");
+    }
+    trim_blank_line(out);
+    out.push_str("    }\n}\n\n");
+}
+
+fn trim_blank_line(out: &mut String) {
+    if out.ends_with("\n\n") {
+        out.pop();
+    }
+}
+
+fn handler_name(operation: &Operation) -> String {
+    format!("handle_{}", snake_case(&operation.name))
+}
+
+fn server_source(out: &mut String, project: &Project, operations: &[Operation]) {
+    out.push_str("pub mod server {\n    use super::*;\n\n");
+    out.push_str(SERVER_RUNTIME);
+    out.push('\n');
+    for operation in operations {
+        let arguments = arguments(operation, identifier);
+        let locations: Vec<&str> = arguments.iter().map(|a| a.location).collect();
+        let _ = writeln!(
+            out,
+            "    async fn {}<S: Api + Clone + Send + Sync + 'static>(\n        axum::extract::State(service): axum::extract::State<S>,",
+            handler_name(operation)
+        );
+        if locations.contains(&"path") {
+            out.push_str("        axum::extract::Path(path): axum::extract::Path<std::collections::HashMap<String, String>>,\n");
+        }
+        if locations.contains(&"query") {
+            out.push_str("        axum::extract::Query(query): axum::extract::Query<std::collections::HashMap<String, String>>,\n");
+        }
+        if locations.contains(&"header") {
+            out.push_str("        headers: axum::http::HeaderMap,\n");
+        }
+        if !operation.config.request_model.is_empty() {
+            let _ = writeln!(
+                out,
+                "        axum::Json(body): axum::Json<{}>,",
+                Rust.type_name(project.models.as_ref(), &operation.config.request_model)
+            );
+        }
+        out.push_str(
+            "    ) -> Result<axum::response::Response, ApiError> {\n        use axum::response::IntoResponse;\n",
+        );
+        let mut call_arguments = vec![];
+        if !arguments.is_empty() {
+            let _ = writeln!(out, "        let params = {} {{", params_type(operation));
+            for argument in &arguments {
+                let source = match argument.location {
+                    "path" => format!("path.get({:?}).map(String::as_str)", argument.wire),
+                    "query" => format!("query.get({:?}).map(String::as_str)", argument.wire),
+                    _ => format!("header(&headers, {:?})", argument.wire),
+                };
+                let mut value = format!("argument({}, {:?})?", source, argument.wire);
+                if argument.required {
+                    value = format!("required({}, {:?})?", value, argument.wire);
+                }
+                let _ = writeln!(out, "            {}: {},", argument.name, value);
+            }
+            out.push_str("        };\n");
+            call_arguments.push("params");
+        }
+        if !operation.config.request_model.is_empty() {
+            call_arguments.push("body");
+        }
+        let status = project
+            .get_status_codes(&operation.config.status_codes)
+            .iter()
+            .find(|c| c.code.starts_with('2'))
+            .map_or("200".to_owned(), |c| c.code.to_owned());
+        let _ = writeln!(
+            out,
+            "        let result = service.{}({}).await?;\n        let status = axum::http::StatusCode::from_u16({}).unwrap_or(axum::http::StatusCode::OK);",
+            identifier(&operation.name),
+            call_arguments.join(", "),
+            status
+        );
+        match operation.config.response_model.is_empty() {
+            true => out.push_str(
+                "        let () = result;\n        Ok(status.into_response())\n    }\n\n",
+            ),
+            false => {
+                out.push_str("        Ok((status, axum::Json(result)).into_response())\n    }\n\n")
+            }
+        }
+    }
+    out.push_str("    /// Routes every operation to `service`\n    pub fn router<S: Api + Clone + Send + Sync + 'static>(service: S) -> axum::Router {\n        axum::Router::new()\n");
+    let mut paths: Vec<&str> = vec![];
+    for operation in operations {
+        if !paths.contains(&operation.path) {
+            paths.push(operation.path);
+        }
+    }
+    for path in paths {
+        let routes: Vec<String> = operations
+            .iter()
+            .filter(|o| o.path == path)
+            .map(|o| format!("{}({}::<S>)", o.verb, handler_name(o)))
+            .collect();
+        let _ = writeln!(
+            out,
+            "            .route({:?}, axum::routing::{})",
+            path,
+            routes.join(".")
+        );
+    }
+    out.push_str("            .with_state(service)\n    }\n}\n");
+}
+
+/// Rust module with the models, the `Api` trait, its reqwest client and axum router
+pub fn generate(project: &Project) -> String {
+    let operations = operations(project);
+    let mut out = format!(
+        "//! Generated by apish from {} {}, do not edit\n//!\n//! Needs serde (derive), serde_json, reqwest 0.12 (json) and axum 0.8\n\n#![allow(dead_code, deprecated, clippy::all)]\n\nuse serde::{{Deserialize, Serialize}};\n\n",
+        project.title, project.version
+    );
+    if let Some(models) = &project.models {
+        models_source(&mut out, models);
+    }
+    out.push_str(RUNTIME);
+    out.push('\n');
+    for operation in &operations {
+        params_source(&mut out, operation);
+    }
+    trait_source(&mut out, project, &operations);
+    client_source(&mut out, project, &operations);
+    server_source(&mut out, project, &operations);
+    out
+}
+
+#[test]
+fn test_generate_rust() {
+    use super::{assert_blocks, pets};
+
+    // Card and Bank have the same fields, only the discriminator tells them apart
+    let source = generate(&pets("union Loose = Card | Bank\n"));
+    assert_blocks(
+        &source,
+        &[
+            "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]\npub enum Kind {\n    #[serde(rename = \"dog\")]\n    Dog,\n    #[deprecated]\n    #[serde(rename = \"CAT\")]\n    Cat,\n}\n",
+            "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]\n#[serde(try_from = \"i64\", into = \"i64\")]\npub enum Size {\n    Small = 1,\n    Big = 2,\n}\n",
+            "#[derive(Debug, Clone, PartialEq, Serialize)]\n#[serde(untagged)]\npub enum Payment {\n    Card(Card),\n    Bank(Bank),\n}\n",
+            "impl<'de> Deserialize<'de> for Payment {\n    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {\n        use serde::de::Error;\n        let value = serde_json::Value::deserialize(deserializer)?;\n        match value.get(\"type\").and_then(serde_json::Value::as_str) {\n            Some(\"Card\") => serde_json::from_value(value).map(Self::Card).map_err(D::Error::custom),\n            Some(\"Bank\") => serde_json::from_value(value).map(Self::Bank).map_err(D::Error::custom),\n            Some(other) => Err(D::Error::custom(format!(\"Unknown Payment type `{}`\", other))),\n            None => Err(D::Error::missing_field(\"type\")),\n        }\n    }\n}\n",
+            "#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]\n#[serde(untagged)]\npub enum Loose {\n    Card(Card),\n    Bank(Bank),\n}\n",
+            "#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]\npub struct Pet {\n    pub id: i64,\n    /// Pet name\n    #[serde(alias = \"title\")]\n    pub name: String,\n    pub r#type: Kind,\n    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n    pub tags: Option<Vec<String>>,\n    pub owner: Option<Owner>,\n    pub parent: Option<Box<Pet>>,\n    #[serde(rename = \"nickName\")]\n    pub nick_name: Option<String>,\n    pub extra: std::collections::HashMap<String, i64>,\n    pub born: String,\n}\n",
+            "#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]\npub struct Settings {\n    #[serde(default = \"default_settings_theme\")]\n    pub theme: String,\n    #[serde(default = \"default_settings_retries\", skip_serializing_if = \"Option::is_none\")]\n    pub retries: Option<i64>,\n    #[serde(default = \"default_settings_kind\")]\n    pub kind: Option<Kind>,\n    #[serde(default = \"default_settings_size\")]\n    pub size: Option<Size>,\n}\n",
+            "fn default_settings_size() -> Option<Size> {\n    Some(Size::Small)\n}\n",
+            "/// Path params, query items and headers of `updatePet`\n#[derive(Debug, Clone, PartialEq, Default)]\npub struct UpdatePetParams {\n    pub id: f64,\n    pub r#type: Option<String>,\n    pub page_size: Option<f64>,\n    pub x_auth: String,\n}\n",
+            "/// Operations of the API, implemented by `client::Client` and served by `server::router`\npub trait Api {\n    /// Finds a pet\n    fn get_pet(&self, params: GetPetParams) -> impl std::future::Future<Output = Result<Pet, ApiError>> + Send;\n    /// Updates a pet\n    fn update_pet(&self, params: UpdatePetParams, body: Pet) -> impl std::future::Future<Output = Result<Pet, ApiError>> + Send;\n    /// Removes a pet\n    fn delete_pets_id(&self, params: DeletePetsIdParams) -> impl std::future::Future<Output = Result<(), ApiError>> + Send;\n    /// Health\n    fn get_health(&self) -> impl std::future::Future<Output = Result<(), ApiError>> + Send;\n}\n",
+            "        async fn update_pet(&self, params: UpdatePetParams, body: Pet) -> Result<Pet, ApiError> {\n            let url = format!(\"{}/pets/{}\", self.base_url, encode(&params.id.to_string()));\n            let mut request = self.http.request(reqwest::Method::PUT, url);\n            let mut query: Vec<(&str, String)> = vec![];\n            if let Some(value) = &params.r#type {\n                query.push((\"type\", value.to_string()));\n            }\n            if let Some(value) = &params.page_size {\n                query.push((\"page-size\", value.to_string()));\n            }\n            request = request.query(&query);\n            request = request.header(\"x-auth\", params.x_auth.to_string());\n            request = request.json(&body);\n            let response = request.send().await.map_err(failure)?;\n            read(response).await\n        }\n",
+            "    async fn handle_update_pet<S: Api + Clone + Send + Sync + 'static>(\n        axum::extract::State(service): axum::extract::State<S>,\n        axum::extract::Path(path): axum::extract::Path<std::collections::HashMap<String, String>>,\n        axum::extract::Query(query): axum::extract::Query<std::collections::HashMap<String, String>>,\n        headers: axum::http::HeaderMap,\n        axum::Json(body): axum::Json<Pet>,\n    ) -> Result<axum::response::Response, ApiError> {\n        use axum::response::IntoResponse;\n        let params = UpdatePetParams {\n            id: required(argument(path.get(\"id\").map(String::as_str), \"id\")?, \"id\")?,\n            r#type: argument(query.get(\"type\").map(String::as_str), \"type\")?,\n            page_size: argument(query.get(\"page-size\").map(String::as_str), \"page-size\")?,\n            x_auth: required(argument(header(&headers, \"x-auth\"), \"x-auth\")?, \"x-auth\")?,\n        };\n        let result = service.update_pet(params, body).await?;\n        let status = axum::http::StatusCode::from_u16(201).unwrap_or(axum::http::StatusCode::OK);\n        Ok((status, axum::Json(result)).into_response())\n    }\n",
+            "    /// Routes every operation to `service`\n    pub fn router<S: Api + Clone + Send + Sync + 'static>(service: S) -> axum::Router {\n        axum::Router::new()\n            .route(\"/pets/{id}\", axum::routing::get(handle_get_pet::<S>).put(handle_update_pet::<S>).delete(handle_delete_pets_id::<S>))\n            .route(\"/health\", axum::routing::get(handle_get_health::<S>))\n            .with_state(service)\n    }\n}\n",
+        ],
+    );
+}
+
+#[test]
+fn test_identifier() {
+    assert_eq!(identifier("favoriteFood"), "favorite_food");
+    assert_eq!(identifier("type"), "r#type");
+    for reserved in ["try", "box", "yield", "abstract", "macro", "gen"] {
+        assert_eq!(identifier(reserved), format!("r#{}", reserved));
+    }
+    assert_eq!(identifier("self"), "self_");
+    assert_eq!(identifier("2fa"), "_2fa");
+}
@@ -0,0 +1,454 @@
+//! TypeScript target: an interface per struct, literal unions for enums and unions, and a
+//! fetch based function per operation
+
+use std::fmt::Write;
+
+use crate::api_spec::get_mime_types;
+use crate::codegen::{
+    arguments, camel_case, default_literal, doc, operations, primitive, Literal, Operation,
+    Primitive, Types,
+};
+use crate::models::{Field, ProjectModel, TypeKind};
+use crate::project::Project;
+
+const RUNTIME: &str = "export interface ClientOptions {
+  baseUrl: string;
+  headers?: Record<string, string>;
+  fetch?: typeof fetch;
+}
+
+export class ApiError extends globalThis.Error {
+  status: number;
+  body: unknown;
+
+  constructor(status: number, body: unknown) {
+    super(`Request failed with status ${status}`);
+    this.status = status;
+    this.body = body;
+  }
+}
+
+type Values = Record<string, string | number | boolean | undefined>;
+
+async function request<T>(
+  options: ClientOptions,
+  method: string,
+  path: string,
+  query: Values,
+  headers: Values,
+  body?: unknown,
+  contentType?: string,
+): Promise<T> {
+  const search = new URLSearchParams();
+  for (const [key, value] of Object.entries(query)) {
+    if (value !== undefined) {
+      search.append(key, String(value));
+    }
+  }
+  const requestHeaders: Record<string, string> = { ...options.headers };
+  for (const [key, value] of Object.entries(headers)) {
+    if (value !== undefined) {
+      requestHeaders[key] = String(value);
+    }
+  }
+  const init: RequestInit = { method, headers: requestHeaders };
+  if (body !== undefined) {
+    requestHeaders[\"Content-Type\"] = contentType ?? \"application/json\";
+    init.body = requestHeaders[\"Content-Type\"] === \"application/json\"
+      ? JSON.stringify(body)
+      : (body as BodyInit);
+  }
+  const suffix = search.toString();
+  const url = options.baseUrl.replace(/\\/$/, \"\") + path + (suffix ? `?${suffix}` : \"\");
+  const response = await (options.fetch ?? fetch)(url, init);
+  const text = await response.text();
+  let data: unknown = text;
+  if (text && (response.headers.get(\"Content-Type\") ?? \"\").includes(\"json\")) {
+    data = JSON.parse(text);
+  }
+  if (!response.ok) {
+    throw new ApiError(response.status, data);
+  }
+  return data as T;
+}
+";
+
+/// Reserved words of strict mode modules, they cannot name a function
+const KEYWORDS: [&str; 47] = [
+    "arguments",
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "eval",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "implements",
+    "import",
+    "in",
+    "instanceof",
+    "interface",
+    "let",
+    "new",
+    "null",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "static",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "var",
+    "void",
+    "while",
+    "with",
+];
+
+/// Values the runtime declares or calls, an operation function would shadow them
+const RUNTIME_NAMES: [&str; 2] = ["fetch", "request"];
+
+/// Function name of an operation, `_` is appended to reserved words and runtime names
+fn function_name(name: &str) -> String {
+    let name = match name.starts_with(|c: char| c.is_ascii_digit()) {
+        true => format!("_{}", name),
+        false => name.to_owned(),
+    };
+    match KEYWORDS.contains(&name.as_str()) || RUNTIME_NAMES.contains(&name.as_str()) {
+        true => format!("{}_", name),
+        false => name,
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+/// Property name, quoted when it is not an identifier
+fn key(name: &str) -> String {
+    match is_identifier(name) {
+        true => name.to_owned(),
+        false => format!("{:?}", name),
+    }
+}
+
+/// TypeScript names of the model types, models keep their declared name
+struct TypeScript;
+
+impl Types for TypeScript {
+    fn primitive(&self, primitive: Primitive) -> &'static str {
+        match primitive {
+            Primitive::String => "string",
+            Primitive::Bool => "boolean",
+            _ => "number",
+        }
+    }
+
+    fn model(&mut self, name: &str, _kind: TypeKind) -> String {
+        name.to_owned()
+    }
+
+    fn unknown(&mut self) -> String {
+        "unknown".to_owned()
+    }
+}
+
+fn field_type(models: &ProjectModel, field: &Field) -> String {
+    let mut name = TypeScript.type_name(Some(models), &field.data_type);
+    if field.is_array {
+        name = format!("{}[]", name);
+    }
+    if field.is_map {
+        let key_type = primitive(&field.map_key_type).map_or("string", |p| TypeScript.primitive(p));
+        name = format!("Record<{}, {}>", key_type, name);
+    }
+    if field.is_nullable {
+        name = format!("{} | null", name);
+    }
+    name
+}
+
+fn models_source(out: &mut String, models: &ProjectModel) {
+    let mut names: Vec<&String> = models.enums.keys().collect();
+    names.sort();
+    for name in names {
+        let definition = &models.enums[name];
+        let values: Vec<String> = match definition.value_type == "int" {
+            true => definition.values.to_vec(),
+            false => definition
+                .values
+                .iter()
+                .map(|v| format!("{:?}", v))
+                .collect(),
+        };
+        let values = match values.is_empty() {
+            true => "never".to_owned(),
+            false => values.join(" | "),
+        };
+        let _ = writeln!(out, "export type {} = {};\n", name, values);
+    }
+    let mut names: Vec<&String> = models.unions.keys().collect();
+    names.sort();
+    for name in names {
+        let union = &models.unions[name];
+        if union.discriminator.is_empty() {
+            let _ = writeln!(
+                out,
+                "export type {} = {};\n",
+                name,
+                union.variants.join(" | ")
+            );
+            continue;
+        }
+        // the discriminator holds the variant name, as in the OpenAPI default mapping
+        let _ = writeln!(out, "export type {} =", name);
+        for variant in &union.variants {
+            let _ = writeln!(
+                out,
+                "  | ({} & {{ {}: {:?} }})",
+                variant,
+                key(&union.discriminator),
+                variant
+            );
+        }
+        out.truncate(out.trim_end().len());
+        out.push_str(";\n\n");
+    }
+    let mut names: Vec<&String> = models.entities.keys().collect();
+    names.sort();
+    for name in names {
+        let entity = &models.entities[name];
+        let _ = write!(out, "export interface {}", name);
+        if !entity.bases.is_empty() {
+            let _ = write!(out, " extends {}", entity.bases.join(", "));
+        }
+        out.push_str(" {\n");
+        for field in entity.ordered_fields() {
+            if !field.inherited_from.is_empty() {
+                continue;
+            }
+            doc(
+                out,
+                "  ",
+                "/**",
+                &field.description.replace("*/", "* /"),
+                " */",
+            );
+            let optional = if field.is_optional { "?" } else { "" };
+            let _ = writeln!(
+                out,
+                "  {}{}: {};",
+                key(&field.identifier),
+                optional,
+                field_type(models, field)
+            );
+        }
+        out.push_str("}\n\n");
+        defaults_source(out, models, name, entity.ordered_fields());
+    }
+}
+
+/// `<struct>Defaults` constant with the `= value` defaults of a struct, to spread under
+/// received values
+fn defaults_source(out: &mut String, models: &ProjectModel, name: &str, fields: Vec<&Field>) {
+    let values: Vec<String> = fields
+        .into_iter()
+        .filter_map(|field| {
+            let value = match default_literal(models, field)? {
+                Literal::Text(text) => format!("{:?}", text),
+                Literal::Int(number) => number.to_string(),
+                Literal::Float(number) => number.to_string(),
+                Literal::Bool(value) => value.to_string(),
+                Literal::Member(member) => match models.enums[&field.data_type].value_type == "int"
+                {
+                    true => member.value.to_owned(),
+                    false => format!("{:?}", member.value),
+                },
+            };
+            Some(format!("  {}: {},", key(&field.identifier), value))
+        })
+        .collect();
+    if !values.is_empty() {
+        let _ = writeln!(
+            out,
+            "export const {}Defaults: Partial<{}> = {{\n{}\n}};\n",
+            camel_case(name),
+            name,
+            values.join("\n")
+        );
+    }
+}
+
+/// `{ name: type; ... }` of arguments and whether any of them is required
+fn arguments_type(arguments: &[(String, &str, bool)]) -> (String, bool) {
+    let fields: Vec<String> = arguments
+        .iter()
+        .map(|(name, data_type, required)| {
+            let optional = if *required { "" } else { "?" };
+            format!("{}{}: {}", key(name), optional, data_type)
+        })
+        .collect();
+    let required = arguments.iter().any(|(_, _, required)| *required);
+    (format!("{{ {} }}", fields.join("; ")), required)
+}
+
+fn operation_source(out: &mut String, project: &Project, operation: &Operation) {
+    let config = operation.config;
+    let models = project.models.as_ref();
+    let mut groups = vec![];
+    let arguments = arguments(operation, str::to_owned);
+    for (group, location) in [("path", "path"), ("query", "query"), ("headers", "header")] {
+        let items: Vec<(String, &str, bool)> = arguments
+            .iter()
+            .filter(|a| a.location == location)
+            .map(|a| {
+                (
+                    a.wire.to_owned(),
+                    TypeScript.primitive(a.primitive),
+                    a.required,
+                )
+            })
+            .collect();
+        if !items.is_empty() {
+            groups.push((group, arguments_type(&items)));
+        }
+    }
+    if !config.request_model.is_empty() {
+        groups.push((
+            "body",
+            (TypeScript.type_name(models, &config.request_model), true),
+        ));
+    }
+    let response = match config.response_model.is_empty() {
+        true => "unknown".to_owned(),
+        false => TypeScript.type_name(models, &config.response_model),
+    };
+
+    let mut path = operation.path.to_owned();
+    for (name, _) in &operation.path_params {
+        let value = format!("${{encodeURIComponent(String(args.path[{:?}]))}}", name);
+        path = path.replace(&format!("{{{}}}", name), &value);
+    }
+    let group = |name: &str| match groups.iter().any(|(g, _)| *g == name) {
+        true => format!("args.{} ?? {{}}", name),
+        false => "{}".to_owned(),
+    };
+    let body = match config.request_model.is_empty() {
+        true => "undefined".to_owned(),
+        false => "args.body".to_owned(),
+    };
+    let content_type = get_mime_types(&config.consumes)
+        .first()
+        .map_or("undefined".to_owned(), |mime| format!("{:?}", mime));
+
+    doc(
+        out,
+        "",
+        "/**",
+        &config.description.replace("*/", "* /"),
+        " */",
+    );
+    let _ = write!(
+        out,
+        "export async function {}(\n  options: ClientOptions,\n",
+        function_name(&operation.name)
+    );
+    if !groups.is_empty() {
+        let required = groups.iter().any(|(_, (_, required))| *required);
+        let _ = writeln!(out, "  args: {{");
+        for (name, (group_type, group_required)) in &groups {
+            let optional = if *group_required { "" } else { "?" };
+            let _ = writeln!(out, "    {}{}: {};", name, optional, group_type);
+        }
+        let _ = writeln!(out, "  }}{},", if required { "" } else { " = {}" });
+    }
+    let _ = writeln!(
+        out,
+        "): Promise<{response}> {{\n  return request<{response}>(\n    options,\n    {:?},\n    `{}`,\n    {},\n    {},\n    {},\n    {},\n  );\n}}\n",
+        operation.verb.to_uppercase(),
+        path,
+        group("query"),
+        group("headers"),
+        body,
+        content_type,
+        response = response,
+    );
+}
+
+/// TypeScript module with the models and a client function per operation
+pub fn generate(project: &Project) -> String {
+    let mut out = format!(
+        "// Generated by apish from {} {}, do not edit\n\n",
+        project.title, project.version
+    );
+    if let Some(models) = &project.models {
+        models_source(&mut out, models);
+    }
+    out.push_str(RUNTIME);
+    out.push('\n');
+    for operation in operations(project) {
+        operation_source(&mut out, project, &operation);
+    }
+    format!("{}\n", out.trim_end())
+}
+
+#[test]
+fn test_generate_typescript() {
+    use super::{assert_blocks, pets};
+
+    let source = generate(&pets("enum Empty {}\\n"));
+    assert_blocks(
+        &source,
+        &[
+            "export type Kind = \"dog\" | \"CAT\";\n",
+            "export type Empty = never;\n",
+            "export type Payment =\n  | (Card & { type: \"Card\" })\n  | (Bank & { type: \"Bank\" });\n",
+            "export interface Error {\n  code: number;\n}\n",
+            "export interface Pet extends Base {\n  /** Pet name */\n  name: string;\n  type: Kind;\n  tags?: string[];\n  owner: Owner | null;\n  parent: Pet | null;\n  nickName: string | null;\n  extra: Record<string, number>;\n  born: string;\n}\n",
+            "export const settingsDefaults: Partial<Settings> = {\n  theme: \"dark\",\n  retries: 3,\n  kind: \"dog\",\n  size: 1,\n};\n",
+            "export class ApiError extends globalThis.Error {\n  status: number;\n  body: unknown;\n",
+            "/** Updates a pet */\nexport async function updatePet(\n  options: ClientOptions,\n  args: {\n    path: { id: number };\n    query?: { type?: string; \"page-size\"?: number };\n    headers: { \"x-auth\": string };\n    body: Pet;\n  },\n): Promise<Pet> {\n  return request<Pet>(\n    options,\n    \"PUT\",\n    `/pets/${encodeURIComponent(String(args.path[\"id\"]))}`,\n    args.query ?? {},\n    args.headers ?? {},\n    args.body,\n    \"application/json\",\n  );\n}\n",
+            "/** Health */\nexport async function getHealth(\n  options: ClientOptions,\n): Promise<unknown> {\n  return request<unknown>(\n    options,\n    \"GET\",\n    `/health`,\n    {},\n    {},\n    undefined,\n    undefined,\n  );\n}\n",
+        ],
+    );
+}
+
+#[test]
+fn test_function_name() {
+    let api = "apis:\n  /pets:\n    get: \"List\"\n      operation: request\n    post: \"Create\"\n      operation: new\n    delete: \"Remove\"\n      operation: delete\n    put: \"Replace\"\n      operation: 2fa\n";
+    let project = Project::new_from_sources(api, None, None).unwrap();
+    let source = generate(&project);
+    for name in ["request_", "new_", "delete_", "_2fa"] {
+        let declaration = format!("export async function {}(\n", name);
+        assert!(
+            source.contains(&declaration),
+            "missing {}\n{}",
+            name,
+            source
+        );
+    }
+    assert!(!source.contains("function request("));
+}
@@ -0,0 +1,486 @@
+//! Changes between two versions of a `Project`, classified by the semver bump they need
+
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::models::{Field, ProjectModel};
+use crate::project::{APIConfiguration, Project, ProjectArgument};
+
+/// Semver bump a change needs, breaking changes need a major one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Bump {
+    None,
+    Patch,
+    Minor,
+    Major,
+}
+
+impl fmt::Display for Bump {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Bump::None => "none",
+            Bump::Patch => "patch",
+            Bump::Minor => "minor",
+            Bump::Major => "major",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Change {
+    pub bump: Bump,
+    /// endpoint (`GET /pets`), path, model (`Pet.name`) or enum the change applies to
+    pub location: String,
+    pub message: String,
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let bump = self.bump.to_string().to_uppercase();
+        write!(f, "{:<5} {}: {}", bump, self.location, self.message)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApiDiff {
+    pub old_version: String,
+    pub new_version: String,
+    /// highest bump of all changes
+    pub bump: Bump,
+    /// `old_version` bumped, `None` when it is not a `major.minor.patch` version
+    pub suggested_version: Option<String>,
+    /// whether `new_version` is at least the suggested one
+    pub version_ok: bool,
+    /// sorted by bump, breaking changes first
+    pub changes: Vec<Change>,
+}
+
+impl ApiDiff {
+    pub fn is_breaking(&self) -> bool {
+        self.bump == Bump::Major
+    }
+}
+
+impl fmt::Display for ApiDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for change in &self.changes {
+            writeln!(f, "{}", change)?;
+        }
+        if self.changes.is_empty() {
+            writeln!(f, "No changes")?;
+        }
+        match &self.suggested_version {
+            Some(suggested) => write!(
+                f,
+                "Suggested {} bump: {} -> {} (declared {})",
+                self.bump, self.old_version, suggested, self.new_version
+            ),
+            None => write!(f, "Suggested {} bump", self.bump),
+        }
+    }
+}
+
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let parts: Vec<&str> = version.trim().trim_start_matches('v').split('.').collect();
+    if parts.len() != 3 {
+        return None;
+    }
+    let numbers: Vec<u64> = parts.iter().filter_map(|p| p.parse().ok()).collect();
+    if numbers.len() != 3 {
+        return None;
+    }
+    Some((numbers[0], numbers[1], numbers[2]))
+}
+
+fn bump_version(version: (u64, u64, u64), bump: Bump) -> (u64, u64, u64) {
+    let (major, minor, patch) = version;
+    match bump {
+        Bump::None => version,
+        Bump::Patch => (major, minor, patch + 1),
+        Bump::Minor => (major, minor + 1, 0),
+        Bump::Major => (major + 1, 0, 0),
+    }
+}
+
+fn sorted_keys<V>(map: &HashMap<String, V>) -> Vec<&String> {
+    let mut keys: Vec<&String> = map.keys().collect();
+    keys.sort();
+    keys
+}
+
+struct Differ {
+    changes: Vec<Change>,
+}
+
+impl Differ {
+    fn add(&mut self, bump: Bump, location: &str, message: String) {
+        self.changes.push(Change {
+            bump,
+            location: location.to_owned(),
+            message,
+        });
+    }
+
+    fn diff_arguments(
+        &mut self,
+        kind: &str,
+        location: &str,
+        old: Vec<&ProjectArgument>,
+        new: Vec<&ProjectArgument>,
+    ) {
+        for argument in &new {
+            match old.iter().find(|a| a.name == argument.name) {
+                Some(previous) => {
+                    if argument.required && !previous.required {
+                        let message = format!("{} `{}` is now required", kind, argument.name);
+                        self.add(Bump::Major, location, message);
+                    } else if !argument.required && previous.required {
+                        let message = format!("{} `{}` is now optional", kind, argument.name);
+                        self.add(Bump::Minor, location, message);
+                    }
+                    if argument.data_type != previous.data_type {
+                        let message = format!(
+                            "{} `{}` changed from {} to {}",
+                            kind,
+                            argument.name,
+                            previous.data_type.as_str(),
+                            argument.data_type.as_str()
+                        );
+                        self.add(Bump::Major, location, message);
+                    }
+                }
+                None => {
+                    let (bump, qualifier) = if argument.required {
+                        (Bump::Major, "Required")
+                    } else {
+                        (Bump::Minor, "Optional")
+                    };
+                    let message = format!(
+                        "{} {} `{}` was added",
+                        qualifier,
+                        kind.to_lowercase(),
+                        argument.name
+                    );
+                    self.add(bump, location, message);
+                }
+            }
+        }
+        for argument in &old {
+            if !new.iter().any(|a| a.name == argument.name) {
+                let message = format!("{} `{}` was removed", kind, argument.name);
+                self.add(Bump::Minor, location, message);
+            }
+        }
+    }
+
+    fn diff_list(&mut self, kind: &str, location: &str, old: &[String], new: &[String]) {
+        for item in old {
+            if !new.contains(item) {
+                let message = format!("{} `{}` was removed", kind, item);
+                self.add(Bump::Major, location, message);
+            }
+        }
+        for item in new {
+            if !old.contains(item) {
+                let message = format!("{} `{}` was added", kind, item);
+                self.add(Bump::Minor, location, message);
+            }
+        }
+    }
+
+    fn diff_model_name(&mut self, kind: &str, location: &str, old: &str, new: &str) {
+        if old == new {
+            return;
+        }
+        let message = match (old.is_empty(), new.is_empty()) {
+            (true, _) => format!("{} model `{}` was added", kind, new),
+            (_, true) => format!("{} model `{}` was removed", kind, old),
+            _ => format!("{} model changed from `{}` to `{}`", kind, old, new),
+        };
+        self.add(Bump::Major, location, message);
+    }
+
+    fn diff_operation(
+        &mut self,
+        location: &str,
+        old: &APIConfiguration,
+        new: &APIConfiguration,
+        old_project: &Project,
+        new_project: &Project,
+    ) {
+        if old.operation != new.operation {
+            let message = format!(
+                "Operation id changed from `{}` to `{}`",
+                old.operation, new.operation
+            );
+            self.add(Bump::Major, location, message);
+        }
+        self.diff_arguments(
+            "Header",
+            location,
+            old_project.get_headers(&old.headers),
+            new_project.get_headers(&new.headers),
+        );
+        self.diff_arguments(
+            "Query param",
+            location,
+            old_project.get_query_strings(&old.query_string),
+            new_project.get_query_strings(&new.query_string),
+        );
+        self.diff_arguments(
+            "Path param",
+            location,
+            old_project.get_path_params(&old.path_params),
+            new_project.get_path_params(&new.path_params),
+        );
+        let old_codes: Vec<String> = old_project
+            .get_status_codes(&old.status_codes)
+            .into_iter()
+            .map(|c| c.code)
+            .collect();
+        let new_codes: Vec<String> = new_project
+            .get_status_codes(&new.status_codes)
+            .into_iter()
+            .map(|c| c.code)
+            .collect();
+        self.diff_list("Status code", location, &old_codes, &new_codes);
+        self.diff_list("Produced type", location, &old.produces, &new.produces);
+        self.diff_list("Consumed type", location, &old.consumes, &new.consumes);
+        self.diff_model_name("Request", location, &old.request_model, &new.request_model);
+        self.diff_model_name(
+            "Response",
+            location,
+            &old.response_model,
+            &new.response_model,
+        );
+        if old.description != new.description {
+            self.add(Bump::Patch, location, "Description changed".to_owned());
+        }
+    }
+
+    fn diff_endpoints(&mut self, old: &Project, new: &Project) {
+        for path in sorted_keys(&old.endpoints) {
+            if path.is_empty() {
+                continue;
+            }
+            let old_definition = &old.endpoints[path];
+            let new_definition = match new.endpoints.get(path) {
+                Some(definition) => definition,
+                None => {
+                    self.add(Bump::Major, path, "Endpoint was removed".to_owned());
+                    continue;
+                }
+            };
+            let new_operations = new_definition.operations();
+            for (verb, config) in old_definition.operations() {
+                let location = format!("{} {}", verb.to_uppercase(), path);
+                match new_operations.iter().find(|(v, _)| *v == verb) {
+                    Some((_, new_config)) => {
+                        self.diff_operation(&location, config, new_config, old, new)
+                    }
+                    None => self.add(Bump::Major, &location, "Operation was removed".to_owned()),
+                }
+            }
+            let old_operations = old_definition.operations();
+            for (verb, _) in new_operations {
+                if !old_operations.iter().any(|(v, _)| *v == verb) {
+                    let location = format!("{} {}", verb.to_uppercase(), path);
+                    self.add(Bump::Minor, &location, "Operation was added".to_owned());
+                }
+            }
+        }
+        for path in sorted_keys(&new.endpoints) {
+            if !path.is_empty() && !old.endpoints.contains_key(path) {
+                self.add(Bump::Minor, path, "Endpoint was added".to_owned());
+            }
+        }
+    }
+
+    fn diff_field(&mut self, location: &str, old: &Field, new: &Field) {
+        let shape = |f: &Field| {
+            let mut shape = f.data_type.to_owned();
+            if f.is_array {
+                shape = format!("[]{}", shape);
+            }
+            if f.is_map {
+                shape = format!("map[{}]{}", f.map_key_type, shape);
+            }
+            shape
+        };
+        if shape(old) != shape(new) {
+            let message = format!("Type changed from `{}` to `{}`", shape(old), shape(new));
+            self.add(Bump::Major, location, message);
+        }
+        if new.is_required && !old.is_required {
+            self.add(Bump::Major, location, "Field is now required".to_owned());
+        } else if !new.is_required && old.is_required {
+            self.add(
+                Bump::Major,
+                location,
+                "Field is no longer required".to_owned(),
+            );
+        }
+        if new.is_optional && !old.is_optional {
+            self.add(Bump::Major, location, "Field is now optional".to_owned());
+        } else if !new.is_optional && old.is_optional {
+            self.add(
+                Bump::Major,
+                location,
+                "Field is no longer optional".to_owned(),
+            );
+        }
+        if new.is_nullable && !old.is_nullable {
+            self.add(Bump::Major, location, "Field is now nullable".to_owned());
+        } else if !new.is_nullable && old.is_nullable {
+            self.add(
+                Bump::Major,
+                location,
+                "Field is no longer nullable".to_owned(),
+            );
+        }
+        if old.description != new.description {
+            self.add(Bump::Patch, location, "Description changed".to_owned());
+        }
+    }
+
+    fn diff_models(&mut self, old: &ProjectModel, new: &ProjectModel) {
+        for name in sorted_keys(&old.entities) {
+            let old_entity = &old.entities[name];
+            let new_entity = match new.entities.get(name) {
+                Some(entity) => entity,
+                None => {
+                    self.add(Bump::Major, name, "Struct was removed".to_owned());
+                    continue;
+                }
+            };
+            for field_name in sorted_keys(&old_entity.fields) {
+                let location = format!("{}.{}", name, field_name);
+                match new_entity.fields.get(field_name) {
+                    Some(field) => {
+                        self.diff_field(&location, &old_entity.fields[field_name], field)
+                    }
+                    None => self.add(Bump::Major, &location, "Field was removed".to_owned()),
+                }
+            }
+            for field_name in sorted_keys(&new_entity.fields) {
+                if old_entity.fields.contains_key(field_name) {
+                    continue;
+                }
+                let location = format!("{}.{}", name, field_name);
+                if new_entity.fields[field_name].is_required {
+                    self.add(
+                        Bump::Major,
+                        &location,
+                        "Required field was added".to_owned(),
+                    );
+                } else {
+                    self.add(
+                        Bump::Minor,
+                        &location,
+                        "Optional field was added".to_owned(),
+                    );
+                }
+            }
+        }
+        for name in sorted_keys(&new.entities) {
+            if !old.entities.contains_key(name) {
+                self.add(Bump::Minor, name, "Struct was added".to_owned());
+            }
+        }
+
+        for name in sorted_keys(&old.enums) {
+            match new.enums.get(name) {
+                Some(definition) => {
+                    self.diff_list("Value", name, &old.enums[name].values, &definition.values)
+                }
+                None => self.add(Bump::Major, name, "Enum was removed".to_owned()),
+            }
+        }
+        for name in sorted_keys(&new.enums) {
+            if !old.enums.contains_key(name) {
+                self.add(Bump::Minor, name, "Enum was added".to_owned());
+            }
+        }
+
+        for name in sorted_keys(&old.unions) {
+            match new.unions.get(name) {
+                Some(union) => {
+                    let old_variants = &old.unions[name].variants;
+                    self.diff_list("Variant", name, old_variants, &union.variants)
+                }
+                None => self.add(Bump::Major, name, "Union was removed".to_owned()),
+            }
+        }
+        for name in sorted_keys(&new.unions) {
+            if !old.unions.contains_key(name) {
+                self.add(Bump::Minor, name, "Union was added".to_owned());
+            }
+        }
+    }
+}
+
+/// Compares two versions of a project and its models
+pub fn diff(old: &Project, new: &Project) -> ApiDiff {
+    let mut differ = Differ { changes: vec![] };
+    differ.diff_endpoints(old, new);
+    match (&old.models, &new.models) {
+        (Some(old_models), Some(new_models)) => differ.diff_models(old_models, new_models),
+        (Some(_), None) => differ.add(Bump::Major, "models", "Models were removed".to_owned()),
+        (None, Some(_)) => differ.add(Bump::Minor, "models", "Models were added".to_owned()),
+        (None, None) => {}
+    }
+    let mut changes = differ.changes;
+    changes.sort_by_key(|c| std::cmp::Reverse(c.bump));
+    let bump = changes.iter().map(|c| c.bump).max().unwrap_or(Bump::None);
+
+    let suggested = parse_version(&old.version).map(|v| bump_version(v, bump));
+    let version_ok = match (suggested, parse_version(&new.version)) {
+        (Some(suggested), Some(declared)) => declared >= suggested,
+        _ => false,
+    };
+    ApiDiff {
+        old_version: old.version.to_owned(),
+        new_version: new.version.to_owned(),
+        bump,
+        suggested_version: suggested
+            .map(|(major, minor, patch)| format!("{}.{}.{}", major, minor, patch)),
+        version_ok,
+        changes,
+    }
+}
+
+#[test]
+fn test_diff() {
+    let old_api = "title: \"Pets\"\nversion: \"1.2.3\"\nheaders:\n  x-trace string: \"Trace\"\n\napis:\n  /pets:\n    get: \"Pets\"\n      headers: x-trace\n      status_codes: 200 404\n      response: Pet\n    delete: \"Removes\"\n";
+    let new_api = "title: \"Pets\"\nversion: \"1.3.0\"\nheaders:\n  x-trace string required: \"Trace\"\n\napis:\n  /pets:\n    get: \"All pets\"\n      headers: x-trace\n      status_codes: 200\n      response: Pet\n  /owners:\n    get: \"Owners\"\n";
+    let old_models = "enum Kind { dog, cat }\n\nstruct Pet {\n name string required\n age int\n color string\n nick? string\n tag string?\n}\n";
+    let new_models = "enum Kind { dog }\n\nstruct Pet {\n name string required\n age string\n owner? string\n color? string\n nick string\n tag string\n}\n";
+    let old = Project::new_from_sources(old_api, Some(old_models), None).unwrap();
+    let new = Project::new_from_sources(new_api, Some(new_models), None).unwrap();
+    let result = diff(&old, &new);
+
+    let changes: Vec<String> = result.changes.iter().map(|c| c.to_string()).collect();
+    assert_eq!(
+        changes,
+        vec![
+            "MAJOR GET /pets: Header `x-trace` is now required",
+            "MAJOR GET /pets: Status code `404` was removed",
+            "MAJOR DELETE /pets: Operation was removed",
+            "MAJOR Pet.age: Type changed from `int` to `string`",
+            "MAJOR Pet.color: Field is now optional",
+            "MAJOR Pet.nick: Field is no longer optional",
+            "MAJOR Pet.tag: Field is no longer nullable",
+            "MAJOR Kind: Value `cat` was removed",
+            "MINOR /owners: Endpoint was added",
+            "MINOR Pet.owner: Optional field was added",
+            "PATCH GET /pets: Description changed",
+        ]
+    );
+    assert!(result.is_breaking());
+    assert_eq!(result.suggested_version, Some("2.0.0".to_owned()));
+    assert!(!result.version_ok);
+}
@@ -1,17 +1,16 @@
 use std::collections::HashMap;
-use std::fs;
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Example {
-    description: Option<String>,
-    request: Option<Value>,
-    response: Option<Value>,
+    pub description: Option<String>,
+    pub request: Option<Value>,
+    pub response: Option<Value>,
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, Default)]
 pub struct Bag {
     pub examples: HashMap<String, Vec<Example>>,
 }
@@ -26,22 +25,12 @@ impl Clone for Example {
     }
 }
 
-fn get_file_content(file_name: &str) -> Option<String> {
-    match fs::read_to_string(file_name) {
-        Ok(content) => Some(content),
-        _ => None,
-    }
-}
-
 impl Bag {
-    pub fn new_from_file(file_name: &str) -> Bag {
-        let mut bag = Bag {
-            examples: HashMap::new(),
-        };
-        if let Some(content) = get_file_content(file_name) {
-            if let Ok(json_example) = serde_json::from_str(content.as_str()) {
-                bag.examples = json_example;
-            }
+    /// Examples keyed by name, invalid JSON results in an empty bag
+    pub fn new_from_str(content: &str) -> Bag {
+        let mut bag = Bag::default();
+        if let Ok(json_example) = serde_json::from_str(content) {
+            bag.examples = json_example;
         }
         bag
     }
@@ -0,0 +1,497 @@
+//! Canonical formatting for `.api` and `.model` sources
+
+use pest::iterators::Pair;
+use pest::Parser;
+
+use crate::models::{ModelsParser, Rule as ModelRule};
+use crate::project::{ApishParser, Rule};
+
+const INDENT: &str = "  ";
+
+/// Pads every column to its widest value so descriptions line up, empty columns are
+/// dropped and trailing spaces trimmed
+fn align(rows: &[Vec<String>]) -> Vec<String> {
+    let columns = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..columns)
+        .map(|i| {
+            rows.iter()
+                .map(|row| row.get(i).map_or(0, |c| c.chars().count()))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    rows.iter()
+        .map(|row| {
+            let mut line = String::new();
+            for (i, width) in widths.iter().enumerate() {
+                if *width == 0 {
+                    continue;
+                }
+                let cell = row.get(i).map_or("", |c| c.as_str());
+                if !line.is_empty() {
+                    line.push(' ');
+                }
+                line.push_str(cell);
+                line.push_str(&" ".repeat(width - cell.chars().count()));
+            }
+            line.trim_end().to_owned()
+        })
+        .collect()
+}
+
+fn push_aligned(lines: &mut Vec<String>, indent: &str, rows: &[Vec<String>]) {
+    for line in align(rows) {
+        lines.push(format!("{}{}", indent, line));
+    }
+}
+
+/// Joins the string literals of a rule, the grammar reads adjacent strings as one
+fn strings_of(pair: Pair<Rule>) -> String {
+    pair.into_inner()
+        .filter(|p| p.as_rule() == Rule::string)
+        .map(|p| p.as_str().to_owned())
+        .collect()
+}
+
+fn words_of(pair: Pair<Rule>) -> Vec<String> {
+    pair.into_inner()
+        .filter(|p| p.as_rule() == Rule::word_list)
+        .map(|p| p.as_str().trim().to_owned())
+        .collect()
+}
+
+fn option_text(pair: Pair<Rule>) -> String {
+    let inner = pair.into_inner().next().unwrap();
+    match inner.as_rule() {
+        Rule::modified_pair => inner
+            .into_inner()
+            .map(|p| p.as_str().to_owned())
+            .collect::<Vec<String>>()
+            .join(" "),
+        Rule::default_value => format!("({})", inner.into_inner().as_str()),
+        Rule::constraint => {
+            let mut parts = inner.into_inner();
+            let name = parts.next().unwrap().as_str();
+            match parts.next() {
+                Some(value) => format!("{}({})", name, value.as_str()),
+                None => name.to_owned(),
+            }
+        }
+        _ => inner.as_str().to_owned(),
+    }
+}
+
+/// `name type options:` and description of a reusable item
+fn item_row(pair: Pair<Rule>) -> Vec<String> {
+    let mut head = vec![];
+    let mut description = String::new();
+    for p in pair.into_inner() {
+        match p.as_rule() {
+            Rule::ident | Rule::data_type => head.push(p.as_str().to_owned()),
+            Rule::options => head.push(option_text(p)),
+            Rule::string => description.push_str(p.as_str()),
+            _ => {}
+        }
+    }
+    vec![format!("{}:", head.join(" ")), description]
+}
+
+fn status_code_row(pair: Pair<Rule>) -> Vec<String> {
+    let mut head = vec![];
+    let mut description = String::new();
+    for p in pair.into_inner() {
+        match p.as_rule() {
+            Rule::status_code_n | Rule::status_codes_options => {
+                head.push(p.as_str().trim().to_owned())
+            }
+            Rule::string => description.push_str(p.as_str()),
+            _ => {}
+        }
+    }
+    vec![format!("{}:", head.join(" ")), description]
+}
+
+fn group_row(pair: Pair<Rule>) -> Vec<String> {
+    let mut inner = pair.clone().into_inner();
+    let name = inner.next().unwrap().as_str().to_owned();
+    vec![format!("{}:", name), words_of(pair).join(" ")]
+}
+
+fn sort_rows(rows: &mut [Vec<String>], sort: bool) {
+    if sort {
+        rows.sort_by(|a, b| a[0].cmp(&b[0]));
+    }
+}
+
+fn format_api_params(pair: Pair<Rule>, lines: &mut Vec<String>) {
+    let indent = INDENT.repeat(3);
+    let param = pair.into_inner().next().unwrap();
+    match param.as_rule() {
+        Rule::api_use_cases => {
+            lines.push(format!("{}use_cases:", indent));
+            for use_case in param.into_inner() {
+                lines.push(format!("{}{}{}", indent, INDENT, strings_of(use_case)));
+            }
+        }
+        Rule::api_single_option => {
+            let keyword = param.clone().into_inner().next().unwrap().as_str();
+            let words = words_of(param);
+            lines.push(format!("{}{}: {}", indent, keyword, words.join(" ")));
+        }
+        Rule::api_status_codes => {
+            lines.push(format!(
+                "{}status_codes: {}",
+                indent,
+                words_of(param).join(" ")
+            ));
+        }
+        Rule::api_operation | Rule::api_request | Rule::api_response => {
+            let keyword = match param.as_rule() {
+                Rule::api_operation => "operation",
+                Rule::api_request => "request",
+                _ => "response",
+            };
+            let value = param.into_inner().next().unwrap().as_str();
+            lines.push(format!("{}{}: {}", indent, keyword, value));
+        }
+        _ => {}
+    }
+}
+
+/// Lines of a single path with its operations, `None` for the blank entries the grammar
+/// reads between paths
+fn format_api_path(pair: Pair<Rule>) -> Option<(String, Vec<String>)> {
+    let mut path = String::new();
+    let mut lines = vec![];
+    for p in pair.into_inner() {
+        match p.as_rule() {
+            Rule::path => {
+                let text = p.as_str().trim();
+                path = text[..text.len() - 1].to_owned();
+                lines.push(format!("{}{}:", INDENT, path));
+            }
+            Rule::api_op => {
+                let mut verb = "";
+                let mut description = String::new();
+                let mut params = vec![];
+                for op in p.into_inner() {
+                    match op.as_rule() {
+                        Rule::http_verb => verb = op.as_str(),
+                        Rule::string => description.push_str(op.as_str()),
+                        Rule::api_params => params.push(op),
+                        _ => {}
+                    }
+                }
+                lines.push(format!("{}{}: {}", INDENT.repeat(2), verb, description));
+                for param in params {
+                    format_api_params(param, &mut lines);
+                }
+            }
+            _ => {}
+        }
+    }
+    if lines.is_empty() {
+        return None;
+    }
+    Some((path, lines))
+}
+
+/// Re-emits an `.api` source with two spaces per level, aligned descriptions and single
+/// spaces between tokens. Comments are kept, `sort` orders the reusable items, groups and
+/// paths by name
+pub fn format_api(source: &str, sort: bool) -> Result<String, String> {
+    let file = match ApishParser::parse(Rule::api_file, source) {
+        Ok(mut pairs) => pairs.next().unwrap(),
+        Err(e) => return Err(format!("Cannot parse API definition {}", e)),
+    };
+    let mut lines: Vec<String> = vec![];
+    let mut after_comment = false;
+    for pair in file.into_inner() {
+        let rule = pair.as_rule();
+        match rule {
+            Rule::spec_header => {
+                for p in pair.into_inner() {
+                    let keyword = match p.as_rule() {
+                        Rule::spec_header_title => "title",
+                        _ => "version",
+                    };
+                    lines.push(format!("{}: {}", keyword, strings_of(p)));
+                }
+            }
+            Rule::comment => {
+                if !after_comment && !lines.is_empty() {
+                    lines.push(String::new());
+                }
+                lines.push(pair.as_str().trim_end().to_owned());
+            }
+            Rule::spec_items => {
+                let mut inner = pair.into_inner();
+                lines.push(inner.next().unwrap().as_str().to_owned());
+                let mut rows: Vec<Vec<String>> = match inner.next() {
+                    Some(list) => list.into_inner().map(item_row).collect(),
+                    None => vec![],
+                };
+                sort_rows(&mut rows, sort);
+                push_aligned(&mut lines, INDENT, &rows);
+            }
+            Rule::status_codes => {
+                lines.push("status_codes:".to_owned());
+                let mut rows: Vec<Vec<String>> = pair
+                    .into_inner()
+                    .filter(|p| p.as_rule() == Rule::status_code_desc)
+                    .map(status_code_row)
+                    .collect();
+                sort_rows(&mut rows, sort);
+                push_aligned(&mut lines, INDENT, &rows);
+            }
+            Rule::common_groups_def => {
+                let mut inner = pair.into_inner();
+                lines.push(format!("{}:", inner.next().unwrap().as_str()));
+                let mut rows: Vec<Vec<String>> = inner.map(group_row).collect();
+                sort_rows(&mut rows, sort);
+                push_aligned(&mut lines, INDENT, &rows);
+            }
+            Rule::apis => {
+                if !after_comment && !lines.is_empty() {
+                    lines.push(String::new());
+                }
+                lines.push("apis:".to_owned());
+                let mut paths: Vec<(String, Vec<String>)> =
+                    pair.into_inner().filter_map(format_api_path).collect();
+                if sort {
+                    paths.sort_by(|a, b| a.0.cmp(&b.0));
+                }
+                for (_, path_lines) in paths {
+                    lines.extend(path_lines);
+                }
+            }
+            _ => {}
+        }
+        after_comment = rule == Rule::comment;
+    }
+    Ok(format!("{}\n", lines.join("\n")))
+}
+
+fn model_strings(pair: Pair<ModelRule>, rule: ModelRule) -> String {
+    pair.into_inner()
+        .filter(|p| p.as_rule() == rule)
+        .map(|p| p.as_str().to_owned())
+        .collect()
+}
+
+fn format_enum(pair: Pair<ModelRule>) -> (String, Vec<String>) {
+    let mut name = String::new();
+    let mut value_type = String::new();
+    let mut rows = vec![];
+    let mut multi_line = false;
+    for p in pair.into_inner() {
+        match p.as_rule() {
+            ModelRule::enumName => name = p.as_str().to_owned(),
+            ModelRule::enumType => value_type = format!(" {}", p.as_str()),
+            ModelRule::enumDef => {
+                for item in p.into_inner() {
+                    let mut row = vec![String::new(); 4];
+                    for part in item.into_inner() {
+                        match part.as_rule() {
+                            ModelRule::enumItemName => row[0] = part.as_str().to_owned(),
+                            ModelRule::enumValue => row[1] = format!("= {}", part.as_str()),
+                            ModelRule::enumDeprecated => row[2] = "deprecated".to_owned(),
+                            ModelRule::enumDescription => row[3] = part.as_str().to_owned(),
+                            _ => {}
+                        }
+                    }
+                    multi_line = multi_line || !row[2].is_empty() || !row[3].is_empty();
+                    rows.push(row);
+                }
+            }
+            _ => {}
+        }
+    }
+    let header = format!("enum {}{}", name, value_type);
+    if rows.is_empty() {
+        return (name, vec![format!("{} {{}}", header)]);
+    }
+    if !multi_line {
+        let items: Vec<String> = rows.iter().map(|row| row[..2].join(" ")).collect();
+        let items: Vec<&str> = items.iter().map(|item| item.trim()).collect();
+        return (name, vec![format!("{} {{ {} }}", header, items.join(", "))]);
+    }
+    let mut lines = vec![format!("{} {{", header)];
+    push_aligned(&mut lines, INDENT, &rows);
+    lines.push("}".to_owned());
+    (name, lines)
+}
+
+fn field_row(pair: Pair<ModelRule>) -> Vec<String> {
+    // name, type, default value and options, description
+    let mut row = vec![String::new(); 4];
+    for p in pair.into_inner() {
+        match p.as_rule() {
+            ModelRule::fieldName => row[0] = p.as_str().to_owned(),
+            ModelRule::optionalIndicator => row[0].push('?'),
+            ModelRule::mapIndicator | ModelRule::arrayIndicator | ModelRule::fieldType => {
+                row[1].push_str(p.as_str())
+            }
+            ModelRule::nullableIndicator => row[1].push('?'),
+            ModelRule::defaultValue => {
+                row[2] = format!("= {}", p.into_inner().next().unwrap().as_str())
+            }
+            ModelRule::objOptionals => {
+                let optional = p.into_inner().next().unwrap();
+                let text = match optional.as_rule() {
+                    ModelRule::objTags => {
+                        let mut inner = optional.into_inner();
+                        let tag = inner.next().unwrap().as_str();
+                        let value = inner.next().map_or("", |v| v.as_str().trim());
+                        if value.is_empty() {
+                            format!("`{}:`", tag)
+                        } else {
+                            format!("`{}: {}`", tag, value)
+                        }
+                    }
+                    ModelRule::objConstraint => {
+                        let mut inner = optional.into_inner();
+                        let name = inner.next().unwrap();
+                        match inner.next() {
+                            Some(value) => format!("{}({})", name.as_str(), value.as_str()),
+                            None => name.as_str().to_owned(),
+                        }
+                    }
+                    _ => optional.as_str().trim().to_owned(),
+                };
+                if !row[2].is_empty() {
+                    row[2].push(' ');
+                }
+                row[2].push_str(&text);
+            }
+            ModelRule::objDescription => row[3].push_str(p.as_str()),
+            _ => {}
+        }
+    }
+    row
+}
+
+fn format_struct(pair: Pair<ModelRule>) -> (String, Vec<String>) {
+    let mut name = String::new();
+    let mut header = String::new();
+    let mut body: Vec<Result<Vec<String>, String>> = vec![];
+    for p in pair.into_inner() {
+        match p.as_rule() {
+            ModelRule::ident => {
+                name = p.as_str().to_owned();
+                header = format!("struct {}", name);
+            }
+            ModelRule::objExtends => {
+                let base = p.into_inner().next().unwrap().as_str();
+                header = format!("{} extends {}", header, base);
+            }
+            ModelRule::objDef => {
+                for line in p.into_inner() {
+                    match line.as_rule() {
+                        ModelRule::objEmbedLine => {
+                            let base = line.into_inner().next().unwrap().as_str();
+                            body.push(Err(format!("{}...{}", INDENT, base)));
+                        }
+                        _ => {
+                            let field = line.into_inner().next().unwrap();
+                            body.push(Ok(field_row(field)));
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    if body.is_empty() {
+        return (name, vec![format!("{} {{}}", header)]);
+    }
+    let rows: Vec<Vec<String>> = body.iter().filter_map(|l| l.clone().ok()).collect();
+    let mut aligned = align(&rows).into_iter();
+    let mut lines = vec![format!("{} {{", header)];
+    for line in body {
+        match line {
+            Ok(_) => lines.push(format!("{}{}", INDENT, aligned.next().unwrap())),
+            Err(embed) => lines.push(embed),
+        }
+    }
+    lines.push("}".to_owned());
+    (name, lines)
+}
+
+fn format_union(pair: Pair<ModelRule>) -> (String, Vec<String>) {
+    let mut name = String::new();
+    let mut variants = vec![];
+    let mut discriminator = String::new();
+    for p in pair.into_inner() {
+        match p.as_rule() {
+            ModelRule::unionName => name = p.as_str().to_owned(),
+            ModelRule::unionVariant => variants.push(p.as_str().to_owned()),
+            ModelRule::unionDiscriminator => {
+                discriminator = format!(" discriminator {}", model_strings(p, ModelRule::ident))
+            }
+            _ => {}
+        }
+    }
+    let line = format!("union {} = {}{}", name, variants.join(" | "), discriminator);
+    (name, vec![line])
+}
+
+/// Re-emits a `.model` source with one blank line between declarations, two spaces per
+/// level and aligned field columns. Enums stay on a single line unless a value is
+/// described or deprecated, `sort` orders the declarations by name
+pub fn format_models(source: &str, sort: bool) -> Result<String, String> {
+    let file = match ModelsParser::parse(ModelRule::definitions_file, source) {
+        Ok(mut pairs) => pairs.next().unwrap(),
+        Err(e) => return Err(format!("Cannot parse models {}", e)),
+    };
+    let mut declarations = vec![];
+    for definitions in file.into_inner() {
+        for p in definitions.into_inner() {
+            match p.as_rule() {
+                ModelRule::enu => declarations.push(format_enum(p)),
+                ModelRule::objType => declarations.push(format_struct(p)),
+                ModelRule::uni => declarations.push(format_union(p)),
+                _ => {}
+            }
+        }
+    }
+    if sort {
+        declarations.sort_by(|a, b| a.0.cmp(&b.0));
+    }
+    let blocks: Vec<String> = declarations
+        .iter()
+        .map(|(_, lines)| lines.join("\n"))
+        .collect();
+    Ok(format!("{}\n", blocks.join("\n\n")))
+}
+
+#[test]
+fn test_format_api() {
+    let source = "title: \"Pets\"\nversion: \"1.0\"\nheaders:\n  x-auth string  alias auth required: \"Token\"\n\tx-trace string (none) maxLength(8): \"Trace id\"\nstatus_codes:\n  424   retryable: \"Later\"\n  401: \"No token\"\n\n// paths\napis:\n  /pets:\n    get: \"Pets\"\n        headers: auth  x-trace\n        use_cases:\n          \"Lists\"\n  /pets/{id}:\n\tdelete: \"Removes\"\n\t  status_codes: 401   424\n";
+    let expected = "title: \"Pets\"\nversion: \"1.0\"\nheaders:\n  x-auth string alias auth required:  \"Token\"\n  x-trace string (none) maxLength(8): \"Trace id\"\nstatus_codes:\n  424 retryable: \"Later\"\n  401:           \"No token\"\n\n// paths\napis:\n  /pets:\n    get: \"Pets\"\n      headers: auth x-trace\n      use_cases:\n        \"Lists\"\n  /pets/{id}:\n    delete: \"Removes\"\n      status_codes: 401 424\n";
+    let formatted = format_api(source, false).unwrap();
+    assert_eq!(formatted, expected);
+    assert_eq!(format_api(&formatted, false).unwrap(), formatted);
+
+    let original = crate::project::Project::new_from_sources(source, None, None).unwrap();
+    let reparsed = crate::project::Project::new_from_sources(&formatted, None, None).unwrap();
+    assert_eq!(
+        serde_json::to_value(&original).unwrap(),
+        serde_json::to_value(&reparsed).unwrap()
+    );
+    assert!(format_api(source, true)
+        .unwrap()
+        .contains("  401:           \"No token\"\n  424 retryable: \"Later\"\n"));
+}
+
+#[test]
+fn test_format_models() {
+    let source = "struct Person extends Base {\n name string required `example:  fido` \"Name\"\n    age? int = 3 min(0)\n...Audit\n}\nenum Mood {happy,sad}\nenum Status {\nACTIVE = \"active\" \"On\"\n  OLD deprecated\n}\nunion Pet=Cat|Dog   discriminator kind\n";
+    let expected = "struct Person extends Base {\n  name string required `example: fido` \"Name\"\n  age? int    = 3 min(0)\n  ...Audit\n}\n\nenum Mood { happy, sad }\n\nenum Status {\n  ACTIVE = \"active\"            \"On\"\n  OLD               deprecated\n}\n\nunion Pet = Cat | Dog discriminator kind\n";
+    let formatted = format_models(source, false).unwrap();
+    assert_eq!(formatted, expected);
+    assert_eq!(format_models(&formatted, false).unwrap(), formatted);
+    assert!(format_models(source, true)
+        .unwrap()
+        .starts_with("enum Mood"));
+}
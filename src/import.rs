@@ -0,0 +1,1626 @@
+//! Imports OpenAPI 3 documents: paths become an `.api` source with the parameters hoisted
+//! into the shared sections, component schemas become `.model` definitions and the media
+//! type examples an examples JSON
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::{self, Write};
+
+use serde::de::{Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde_json::{Map, Value};
+
+use crate::codegen::{camel_case, pascal_case};
+use crate::format::{format_api, format_models};
+
+const VERBS: [&str; 5] = ["get", "post", "put", "delete", "patch"];
+
+/// Headers OpenAPI describes elsewhere (content negotiation and security)
+const IGNORED_HEADERS: [&str; 3] = ["accept", "content-type", "authorization"];
+
+/// Shared sections of the `.api` file and the `in` of their parameters
+const LOCATIONS: [(&str, &str); 3] = [
+    ("headers", "header"),
+    ("params", "path"),
+    ("query", "query"),
+];
+
+/// Sources produced from an OpenAPI document
+pub struct Imported {
+    pub api: String,
+    /// empty when the document has no schemas
+    pub models: String,
+    pub examples: Map<String, Value>,
+    /// parts of the document without an APIsh equivalent
+    pub warnings: Vec<String>,
+}
+
+fn is_ident(text: &str, extra: &str) -> bool {
+    !text.is_empty()
+        && text
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || extra.contains(c))
+}
+
+/// Replaces the characters an identifier cannot hold with `_`
+fn sanitize(text: &str, extra: &str) -> String {
+    text.chars()
+        .map(|c| match c.is_ascii_alphanumeric() || extra.contains(c) {
+            true => c,
+            false => '_',
+        })
+        .collect()
+}
+
+/// Single line string literal, the grammars keep escapes verbatim so quotes and
+/// backslashes are replaced
+fn quoted(text: &str) -> String {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    format!(
+        "\"{}\"",
+        words.join(" ").replace('"', "'").replace('\\', "/")
+    )
+}
+
+/// String literal of a wire value, `None` when the grammars cannot hold it unchanged
+fn wire_literal(text: &str) -> Option<String> {
+    match text.contains(['"', '\\']) || text.chars().any(char::is_control) {
+        true => None,
+        false => Some(format!("\"{}\"", text)),
+    }
+}
+
+fn number_literal(value: &Value) -> Option<String> {
+    let text = value.as_f64().map(|_| value.to_string())?;
+    let valid = text
+        .trim_start_matches('-')
+        .split('.')
+        .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()));
+    match valid {
+        true => Some(text),
+        false => None,
+    }
+}
+
+/// Short name used by `produces:` and `consumes:`
+fn mime_short_name(mime: &str) -> Option<&'static str> {
+    let mime = mime.split(';').next().unwrap_or_default().trim();
+    match mime {
+        "application/json" => Some("json"),
+        "application/xml" => Some("xml"),
+        "text/plain" => Some("text"),
+        "text/css" => Some("css"),
+        "text/html" => Some("html"),
+        "application/javascript" => Some("js"),
+        "multipart/form-data" => Some("multipart"),
+        "application/octet-stream" => Some("binary"),
+        "video/mp4" => Some("mp4"),
+        "image/jpeg" => Some("jpg"),
+        "image/png" => Some("png"),
+        "image/svg+xml" => Some("svg"),
+        _ => None,
+    }
+}
+
+/// Model type of a field: `map[string]`, `[]`, name and `?`
+struct TypeExpr {
+    data_type: String,
+    is_array: bool,
+    is_map: bool,
+    nullable: bool,
+}
+
+impl TypeExpr {
+    fn named(data_type: &str) -> TypeExpr {
+        TypeExpr {
+            data_type: data_type.to_owned(),
+            is_array: false,
+            is_map: false,
+            nullable: false,
+        }
+    }
+
+    fn source(&self) -> String {
+        format!(
+            "{}{}{}{}",
+            if self.is_map { "map[string]" } else { "" },
+            if self.is_array { "[]" } else { "" },
+            self.data_type,
+            if self.nullable { "?" } else { "" }
+        )
+    }
+
+    fn is_primitive(&self) -> bool {
+        matches!(
+            self.data_type.as_str(),
+            "string"
+                | "int"
+                | "long"
+                | "float"
+                | "double"
+                | "number"
+                | "bool"
+                | "date"
+                | "datetime"
+        )
+    }
+}
+
+/// Variants of `oneOf`/`anyOf` but `null`, and whether there was a `null` one
+fn variants(schema: &Value) -> (Vec<&Value>, bool) {
+    let list = schema
+        .get("oneOf")
+        .or_else(|| schema.get("anyOf"))
+        .and_then(Value::as_array);
+    let list = match list {
+        Some(list) => list,
+        None => return (vec![], false),
+    };
+    let is_null = |v: &&Value| v.get("type").and_then(Value::as_str) == Some("null");
+    let variants: Vec<&Value> = list.iter().filter(|v| !is_null(v)).collect();
+    let nullable = variants.len() < list.len();
+    (variants, nullable)
+}
+
+/// Reusable item of the `.api` file
+struct Item {
+    section: &'static str,
+    name: String,
+    data_type: &'static str,
+    /// `required`, default and constraints
+    options: String,
+    description: String,
+    /// set once every item is known
+    alias: String,
+}
+
+impl Item {
+    fn reference(&self) -> &str {
+        match self.alias.is_empty() {
+            true => &self.name,
+            false => &self.alias,
+        }
+    }
+}
+
+/// Keys of every object of a JSON text in document order, `Value` objects sort them
+enum Keys {
+    Object(Vec<(String, Keys)>),
+    Array(Vec<Keys>),
+    Scalar,
+}
+
+impl<'de> Deserialize<'de> for Keys {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(KeysVisitor)
+    }
+}
+
+struct KeysVisitor;
+
+impl<'de> Visitor<'de> for KeysVisitor {
+    type Value = Keys;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a JSON value")
+    }
+
+    fn visit_bool<E>(self, _: bool) -> Result<Keys, E> {
+        Ok(Keys::Scalar)
+    }
+
+    fn visit_i64<E>(self, _: i64) -> Result<Keys, E> {
+        Ok(Keys::Scalar)
+    }
+
+    fn visit_u64<E>(self, _: u64) -> Result<Keys, E> {
+        Ok(Keys::Scalar)
+    }
+
+    fn visit_f64<E>(self, _: f64) -> Result<Keys, E> {
+        Ok(Keys::Scalar)
+    }
+
+    fn visit_str<E>(self, _: &str) -> Result<Keys, E> {
+        Ok(Keys::Scalar)
+    }
+
+    fn visit_unit<E>(self) -> Result<Keys, E> {
+        Ok(Keys::Scalar)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Keys, A::Error> {
+        let mut items = vec![];
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(Keys::Array(items))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Keys, A::Error> {
+        let mut entries = vec![];
+        while let Some(key) = map.next_key()? {
+            entries.push((key, map.next_value()?));
+        }
+        Ok(Keys::Object(entries))
+    }
+}
+
+/// Document order of the keys of every object in `value`, by object address
+fn key_order<'a>(value: &'a Value, keys: &Keys, order: &mut HashMap<*const Value, Vec<&'a str>>) {
+    match (value, keys) {
+        (Value::Object(object), Keys::Object(entries)) => {
+            // the last of repeated keys wins, as in `Value`
+            let positions: HashMap<&str, usize> = entries
+                .iter()
+                .enumerate()
+                .map(|(index, (key, _))| (key.as_str(), index))
+                .collect();
+            for (index, (key, keys)) in entries.iter().enumerate() {
+                if let (Some(child), Some(&position)) =
+                    (object.get(key), positions.get(key.as_str()))
+                {
+                    if position == index {
+                        key_order(child, keys, order);
+                    }
+                }
+            }
+            let mut names: Vec<&str> = object.keys().map(String::as_str).collect();
+            names.sort_by_key(|name| positions.get(name).copied());
+            order.insert(value, names);
+        }
+        (Value::Array(items), Keys::Array(keys)) => {
+            for (item, keys) in items.iter().zip(keys) {
+                key_order(item, keys, order);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Operation lines waiting for the item references
+struct ImportedOperation {
+    path: String,
+    verb: &'static str,
+    description: String,
+    /// item indexes by section, in declaration order
+    items: BTreeMap<&'static str, Vec<usize>>,
+    lines: Vec<String>,
+}
+
+struct Importer<'a> {
+    document: &'a Value,
+    /// object keys in document order
+    order: HashMap<*const Value, Vec<&'a str>>,
+    /// model names of the referenced schemas
+    defined: BTreeMap<String, String>,
+    names: HashSet<String>,
+    /// references being resolved, to stop on recursive aliases
+    visiting: HashSet<String>,
+    models: Vec<String>,
+    items: Vec<Item>,
+    status_codes: BTreeMap<String, String>,
+    examples: Map<String, Value>,
+    warnings: Vec<String>,
+}
+
+impl<'a> Importer<'a> {
+    /// Entries of an object in document order
+    fn entries(&self, value: &'a Value) -> Vec<(&'a str, &'a Value)> {
+        let object = match value.as_object() {
+            Some(object) => object,
+            None => return vec![],
+        };
+        match self.order.get(&(value as *const Value)) {
+            Some(names) => names.iter().map(|name| (*name, &object[*name])).collect(),
+            None => object.iter().map(|(k, v)| (k.as_str(), v)).collect(),
+        }
+    }
+
+    fn resolve(&self, value: &'a Value) -> &'a Value {
+        let mut value = value;
+        for _ in 0..16 {
+            let target = value
+                .get("$ref")
+                .and_then(Value::as_str)
+                .and_then(|reference| reference.strip_prefix('#'))
+                .and_then(|pointer| self.document.pointer(pointer));
+            match target {
+                Some(target) => value = target,
+                None => break,
+            }
+        }
+        value
+    }
+
+    fn unique_name(&mut self, base: &str) -> String {
+        let base = match pascal_case(base).as_str() {
+            "" => "Model".to_owned(),
+            name => name.to_owned(),
+        };
+        let mut name = base.to_owned();
+        let mut suffix = 2;
+        while self.names.contains(&name) {
+            name = format!("{}{}", base, suffix);
+            suffix += 1;
+        }
+        self.names.insert(name.to_owned());
+        name
+    }
+
+    /// Whether a schema, through references and single `allOf` aliases, declares a struct
+    fn is_struct(&self, schema: &'a Value) -> bool {
+        let mut schema = self.resolve(schema);
+        for _ in 0..16 {
+            match schema.get("allOf").and_then(Value::as_array) {
+                Some(all) if all.len() == 1 && schema.get("properties").is_none() => {
+                    schema = self.resolve(&all[0])
+                }
+                _ => break,
+            }
+        }
+        schema.get("enum").is_none()
+            && variants(schema).0.len() < 2
+            && (schema.get("properties").is_some()
+                || schema
+                    .get("allOf")
+                    .and_then(Value::as_array)
+                    .is_some_and(|all| all.len() > 1))
+    }
+
+    /// `oneOf`/`anyOf` of object schemas, at least one of them a struct
+    fn is_union(&self, schema: &'a Value) -> bool {
+        let (variants, _) = variants(schema);
+        variants.len() > 1
+            && variants.iter().all(|v| {
+                v.get("$ref").is_some() || v.get("properties").is_some() || v.get("allOf").is_some()
+            })
+            && variants.iter().any(|v| self.is_struct(v))
+    }
+
+    fn is_model(&self, schema: &'a Value) -> bool {
+        schema.get("enum").is_some()
+            || schema.get("properties").is_some()
+            || schema
+                .get("allOf")
+                .and_then(Value::as_array)
+                .is_some_and(|all| all.len() > 1)
+            || self.is_union(schema)
+    }
+
+    /// Model name of a referenced schema, defining it the first time
+    fn model_for(&mut self, reference: &str, target: &'a Value) -> String {
+        if let Some(name) = self.defined.get(reference) {
+            return name.to_owned();
+        }
+        let component = reference.rsplit('/').next().unwrap_or_default();
+        let name = match is_ident(component, "_") && !self.names.contains(component) {
+            true => {
+                self.names.insert(component.to_owned());
+                component.to_owned()
+            }
+            false => self.unique_name(component),
+        };
+        self.defined.insert(reference.to_owned(), name.to_owned());
+        self.define(&name, target);
+        name
+    }
+
+    fn type_of(&mut self, schema: &'a Value, context: &str) -> TypeExpr {
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            let target = self.resolve(schema);
+            if std::ptr::eq(target, schema) {
+                self.warnings
+                    .push(format!("{}: cannot resolve {}", context, reference));
+                return TypeExpr::named("string");
+            }
+            if self.is_model(target) {
+                return TypeExpr::named(&self.model_for(reference, target));
+            }
+            if !self.visiting.insert(reference.to_owned()) {
+                self.warnings.push(format!(
+                    "{}: recursive {} imported as string",
+                    context, reference
+                ));
+                return TypeExpr::named("string");
+            }
+            let component = reference.rsplit('/').next().unwrap_or(context).to_owned();
+            let result = self.type_of(target, &component);
+            self.visiting.remove(reference);
+            return result;
+        }
+        let mut nullable = schema.get("nullable") == Some(&Value::Bool(true));
+        let (options, null_option) = variants(schema);
+        nullable |= null_option;
+        let all_of = schema.get("allOf").and_then(Value::as_array);
+        let single = match (options.len(), all_of) {
+            (1, _) => Some(options[0]),
+            (0, Some(all)) if all.len() == 1 && schema.get("properties").is_none() => Some(&all[0]),
+            _ => None,
+        };
+        if let Some(single) = single {
+            let mut result = self.type_of(single, context);
+            result.nullable |= nullable;
+            return result;
+        }
+        if self.is_model(schema) {
+            let name = self.unique_name(context);
+            self.define(&name, schema);
+            let mut result = TypeExpr::named(&name);
+            result.nullable = nullable;
+            return result;
+        }
+        if options.len() > 1 {
+            self.warnings.push(format!(
+                "{}: oneOf/anyOf of non object schemas imported as the first one",
+                context
+            ));
+            let mut result = self.type_of(options[0], context);
+            result.nullable |= nullable;
+            return result;
+        }
+        let type_field = match schema.get("type") {
+            Some(Value::Array(types)) => {
+                nullable |= types.iter().any(|t| t == "null");
+                types
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .find(|t| *t != "null")
+                    .unwrap_or_default()
+            }
+            Some(Value::String(type_field)) => type_field.as_str(),
+            _ => "",
+        };
+        let format = schema
+            .get("format")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let mut result = match type_field {
+            "array" => {
+                let items = schema.get("items").unwrap_or(&Value::Null);
+                let mut items = self.type_of(items, &format!("{} item", context));
+                if items.is_array || items.is_map {
+                    self.warnings.push(format!(
+                        "{}: nested collections imported as []string",
+                        context
+                    ));
+                    items = TypeExpr::named("string");
+                }
+                items.is_array = true;
+                items.nullable = false;
+                items
+            }
+            "object" => {
+                let values = match schema.get("additionalProperties") {
+                    Some(values) if values.is_object() => {
+                        self.type_of(values, &format!("{} value", context))
+                    }
+                    _ => TypeExpr::named("string"),
+                };
+                match values.is_map {
+                    true => {
+                        self.warnings.push(format!(
+                            "{}: nested maps imported as map[string]string",
+                            context
+                        ));
+                        let mut values = TypeExpr::named("string");
+                        values.is_map = true;
+                        values
+                    }
+                    false => TypeExpr {
+                        is_map: true,
+                        nullable: false,
+                        ..values
+                    },
+                }
+            }
+            "integer" if format == "int64" => TypeExpr::named("long"),
+            "integer" => TypeExpr::named("int"),
+            "number" if format == "float" || format == "double" => TypeExpr::named(format),
+            "number" => TypeExpr::named("number"),
+            "boolean" => TypeExpr::named("bool"),
+            "string" if format == "date" => TypeExpr::named("date"),
+            "string" if format == "date-time" => TypeExpr::named("datetime"),
+            _ => TypeExpr::named("string"),
+        };
+        result.nullable = nullable;
+        result
+    }
+
+    fn define(&mut self, name: &str, schema: &'a Value) {
+        let source = if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+            self.enum_source(name, schema, values)
+        } else if self.is_union(schema) {
+            self.union_source(name, schema)
+        } else {
+            self.struct_source(name, schema)
+        };
+        self.models.push(source);
+    }
+
+    fn enum_source(&mut self, name: &str, schema: &Value, values: &[Value]) -> String {
+        let values: Vec<&Value> = values.iter().filter(|v| !v.is_null()).collect();
+        let is_int = !values.is_empty() && values.iter().all(|v| v.is_i64());
+        let var_names = schema.get("x-enum-varnames").and_then(Value::as_array);
+        let descriptions = schema.get("x-enum-descriptions").and_then(Value::as_array);
+        let deprecated = schema.get("x-enum-deprecated").and_then(Value::as_array);
+        let mut out = match is_int {
+            true => format!("enum {} int {{\n", name),
+            false => format!("enum {} {{\n", name),
+        };
+        let mut members = HashSet::new();
+        for (index, value) in values.iter().enumerate() {
+            let raw = match value {
+                Value::String(text) => text.to_owned(),
+                Value::Number(number) => number.to_string(),
+                _ => {
+                    self.warnings
+                        .push(format!("{}: enum value {} skipped", name, value));
+                    continue;
+                }
+            };
+            let literal = wire_literal(&raw);
+            if !is_int && literal.is_none() {
+                self.warnings.push(format!(
+                    "{}: enum value {} with quotes, backslashes or control characters skipped",
+                    name, value
+                ));
+                continue;
+            }
+            let declared = var_names
+                .and_then(|names| names.get(index))
+                .and_then(Value::as_str)
+                .filter(|member| is_ident(member, "_"));
+            let base = match (declared, is_int) {
+                (Some(member), _) => member.to_owned(),
+                (None, true) => format!("VALUE_{}", raw.replace('-', "MINUS_")),
+                (None, false) if raw.is_empty() => "EMPTY".to_owned(),
+                (None, false) => sanitize(&raw, "_"),
+            };
+            let mut member = base.to_owned();
+            let mut suffix = 2;
+            while !members.insert(member.to_owned()) {
+                member = format!("{}_{}", base, suffix);
+                suffix += 1;
+            }
+            let mut line = format!("  {}", member);
+            if is_int {
+                let _ = write!(line, " = {}", raw);
+            } else if let Some(literal) = literal.filter(|_| member != raw) {
+                let _ = write!(line, " = {}", literal);
+            }
+            if deprecated.is_some_and(|list| list.contains(value)) {
+                line.push_str(" deprecated");
+            }
+            let description = descriptions
+                .and_then(|list| list.get(index))
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            if !description.is_empty() {
+                let _ = write!(line, " {}", quoted(description));
+            }
+            let _ = writeln!(out, "{}", line);
+        }
+        out.push('}');
+        out
+    }
+
+    /// Whether `schema` or one of its `allOf` parts lists `property` as required
+    fn requires(&self, schema: &'a Value, property: &str) -> bool {
+        let schema = self.resolve(schema);
+        let listed = schema
+            .get("required")
+            .and_then(Value::as_array)
+            .is_some_and(|list| list.iter().any(|p| p.as_str() == Some(property)));
+        listed
+            || schema
+                .get("allOf")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .any(|part| self.requires(part, property))
+    }
+
+    fn union_source(&mut self, name: &str, schema: &'a Value) -> String {
+        let (options, _) = variants(schema);
+        let discriminator = schema
+            .pointer("/discriminator/propertyName")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let mut required = true;
+        let mut names = vec![];
+        for (index, option) in options.into_iter().enumerate() {
+            if !self.is_struct(option) {
+                self.warnings
+                    .push(format!("{}: variant {} is not a struct", name, index + 1));
+                continue;
+            }
+            required = required && self.requires(option, discriminator);
+            let variant = self.type_of(option, &format!("{} option {}", name, index + 1));
+            match variant.is_array || variant.is_map || variant.is_primitive() {
+                true => {
+                    self.warnings
+                        .push(format!("{}: variant {} is not a struct", name, index + 1))
+                }
+                false => names.push(variant.data_type),
+            }
+        }
+        let mut out = format!("union {} = {}", name, names.join(" | "));
+        if is_ident(discriminator, "_") {
+            match required {
+                true => {
+                    let _ = write!(out, " discriminator {}", discriminator);
+                }
+                false => self.warnings.push(format!(
+                    "{}: discriminator {} is not required in every variant, skipped",
+                    name, discriminator
+                )),
+            }
+        }
+        out
+    }
+
+    fn struct_source(&mut self, name: &str, schema: &'a Value) -> String {
+        let mut bases = vec![];
+        let mut parts = vec![schema];
+        for member in schema
+            .get("allOf")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            let target = self.resolve(member);
+            match member.get("$ref").is_some() && self.is_model(target) {
+                true => bases.push(self.type_of(member, name).data_type),
+                false => parts.push(target),
+            }
+        }
+        let mut required = HashSet::new();
+        for part in &parts {
+            for field in part
+                .get("required")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+            {
+                required.insert(field.as_str().unwrap_or_default());
+            }
+        }
+        let mut out = format!("struct {}", name);
+        if let Some(first) = bases.first() {
+            let _ = write!(out, " extends {}", first);
+        }
+        out.push_str(" {\n");
+        for base in bases.iter().skip(1) {
+            let _ = writeln!(out, "  ...{}", base);
+        }
+        for part in parts {
+            let properties = part.get("properties").unwrap_or(&Value::Null);
+            for (field, field_schema) in self.entries(properties) {
+                let line = self.field_line(name, field, field_schema, required.contains(field));
+                let _ = writeln!(out, "  {}", line);
+            }
+        }
+        out.push('}');
+        out
+    }
+
+    fn field_line(
+        &mut self,
+        entity: &str,
+        field: &str,
+        schema: &'a Value,
+        required: bool,
+    ) -> String {
+        let identifier = sanitize(field, "_");
+        if identifier != field {
+            self.warnings.push(format!(
+                "{}.{}: renamed to {}, model fields only hold letters, digits and _",
+                entity, field, identifier
+            ));
+        }
+        let field_type = self.type_of(schema, &format!("{} {}", entity, field));
+        let optional = if required { "" } else { "?" };
+        let mut line = format!("{}{} {}", identifier, optional, field_type.source());
+        match schema.get("default") {
+            Some(Value::String(text)) => match wire_literal(text) {
+                Some(literal) => {
+                    let _ = write!(line, " = {}", literal);
+                }
+                None => self.warnings.push(format!(
+                    "{}.{}: default with quotes, backslashes or control characters skipped",
+                    entity, field
+                )),
+            },
+            Some(Value::Bool(flag)) => {
+                let _ = write!(line, " = {}", flag);
+            }
+            Some(number) => {
+                if let Some(number) = number_literal(number) {
+                    let _ = write!(line, " = {}", number);
+                }
+            }
+            None => {}
+        }
+        if required {
+            line.push_str(" required");
+        }
+        let values = match field_type.is_array {
+            true => self.resolve(schema.get("items").unwrap_or(&Value::Null)),
+            false => schema,
+        };
+        line.push_str(&constraints(values, schema));
+        self.check_pattern(&format!("{}.{}", entity, field), values);
+        let example = schema
+            .get("example")
+            .or_else(|| schema.pointer("/examples/0"));
+        let example = match example {
+            Some(Value::String(text)) => text.to_owned(),
+            Some(value @ (Value::Number(_) | Value::Bool(_))) => value.to_string(),
+            _ => String::new(),
+        };
+        if !example.is_empty() && !example.contains(['`', '\n']) {
+            let _ = write!(line, " `example: {}`", example);
+        }
+        if let Some(description) = schema.get("description").and_then(Value::as_str) {
+            let _ = write!(line, " {}", quoted(description));
+        }
+        line
+    }
+
+    fn check_pattern(&mut self, context: &str, schema: &Value) {
+        let pattern = schema.get("pattern").and_then(Value::as_str);
+        if pattern.is_some_and(|pattern| pattern.contains(['\\', '"'])) {
+            self.warnings.push(format!(
+                "{}: pattern with backslashes or quotes skipped",
+                context
+            ));
+        }
+    }
+
+    /// Index of the `.api` item for a parameter, `None` when it is skipped
+    fn parameter(&mut self, context: &str, parameter: &'a Value) -> Option<usize> {
+        let parameter = self.resolve(parameter);
+        let name = parameter
+            .get("name")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let location = parameter
+            .get("in")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let section = match LOCATIONS.iter().find(|(_, l)| *l == location) {
+            Some((section, _)) => *section,
+            None => {
+                self.warnings.push(format!(
+                    "{}: {} parameter {} skipped",
+                    context, location, name
+                ));
+                return None;
+            }
+        };
+        if section == "headers" && IGNORED_HEADERS.contains(&name.to_lowercase().as_str()) {
+            return None;
+        }
+        let item_name = sanitize(name, "_-");
+        if item_name != name {
+            self.warnings.push(format!(
+                "{}: parameter {} renamed to {}",
+                context, name, item_name
+            ));
+        }
+        let schema = self.resolve(parameter.get("schema").unwrap_or(&Value::Null));
+        let data_type = match schema.get("type").and_then(Value::as_str) {
+            Some("integer" | "number") => "number",
+            Some("boolean") => "bool",
+            Some(other @ ("array" | "object")) => {
+                self.warnings.push(format!(
+                    "{}: {} parameter {} imported as string",
+                    context, other, name
+                ));
+                "string"
+            }
+            _ => "string",
+        };
+        let mut options = String::new();
+        if parameter.get("required") == Some(&Value::Bool(true)) {
+            options.push_str(" required");
+        }
+        let default = match schema.get("default") {
+            Some(Value::String(text)) => text.to_owned(),
+            Some(value @ (Value::Number(_) | Value::Bool(_))) => value.to_string(),
+            _ => String::new(),
+        };
+        if is_ident(&default, "_-") {
+            let _ = write!(options, " ({})", default);
+        }
+        options.push_str(&constraints(schema, schema));
+        self.check_pattern(&format!("{}: parameter {}", context, name), schema);
+        let description = parameter
+            .get("description")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let item = Item {
+            section,
+            name: item_name,
+            data_type,
+            options,
+            description: quoted(description),
+            alias: String::new(),
+        };
+        let found = self.items.iter().position(|other| {
+            other.section == item.section
+                && other.name == item.name
+                && other.data_type == item.data_type
+                && other.options == item.options
+                && other.description == item.description
+        });
+        Some(found.unwrap_or_else(|| {
+            self.items.push(item);
+            self.items.len() - 1
+        }))
+    }
+
+    /// Examples of a media type, named after their key in `examples`
+    fn media_examples(&self, media: &'a Value) -> Vec<(String, String, Value)> {
+        if let Some(example) = media.get("example") {
+            return vec![(String::new(), String::new(), example.clone())];
+        }
+        let examples = media.get("examples").and_then(Value::as_object);
+        examples
+            .into_iter()
+            .flatten()
+            .filter_map(|(name, example)| {
+                let example = self.resolve(example);
+                let summary = example
+                    .get("summary")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default();
+                example
+                    .get("value")
+                    .map(|value| (name.to_owned(), summary.to_owned(), value.clone()))
+            })
+            .collect()
+    }
+
+    /// JSON media type of a content map, or the first one
+    fn main_media(content: &'a Map<String, Value>) -> Option<&'a Value> {
+        content
+            .iter()
+            .find(|(mime, _)| mime.contains("json"))
+            .or_else(|| content.iter().next())
+            .map(|(_, media)| media)
+    }
+
+    /// `produces:`/`consumes:` line of a content map
+    fn media_names(
+        &mut self,
+        context: &str,
+        content: &Map<String, Value>,
+        names: &mut Vec<String>,
+    ) {
+        for mime in content.keys() {
+            match mime_short_name(mime) {
+                Some(name) if !names.iter().any(|n| n == name) => names.push(name.to_owned()),
+                Some(_) => {}
+                None => self.warnings.push(format!(
+                    "{}: media type {} has no short name",
+                    context, mime
+                )),
+            }
+        }
+    }
+
+    /// Model named by a request or response schema
+    fn body_model(&mut self, context: &str, media: &'a Value) -> Option<String> {
+        let schema = media.get("schema")?;
+        let body = self.type_of(schema, context);
+        if body.is_array || body.is_map {
+            self.warnings.push(format!(
+                "{}: {} is not a model, declare a struct wrapping it",
+                context,
+                body.source()
+            ));
+            return None;
+        }
+        // primitive bodies need no model
+        match body.is_primitive() {
+            true => None,
+            false => Some(body.data_type),
+        }
+    }
+
+    fn operation(
+        &mut self,
+        path: &str,
+        verb: &'static str,
+        shared: &'a [Value],
+        operation: &'a Value,
+    ) -> ImportedOperation {
+        let context = format!("{} {}", verb.to_uppercase(), path);
+        let text = |key: &str| {
+            operation
+                .get(key)
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+        };
+        let description = match text("summary").is_empty() {
+            true => text("description"),
+            false => text("summary"),
+        };
+        let mut result = ImportedOperation {
+            path: path.to_owned(),
+            verb,
+            description: quoted(description),
+            items: BTreeMap::new(),
+            lines: vec![],
+        };
+        let name = match text("operationId").is_empty() {
+            true => camel_case(&format!("{} {}", verb, path)),
+            false => sanitize(text("operationId"), "_-"),
+        };
+        if !text("operationId").is_empty() {
+            result.lines.push(format!("operation: {}", name));
+        }
+
+        // operation parameters override the path ones with the same name and location
+        let own = operation
+            .get("parameters")
+            .and_then(Value::as_array)
+            .map_or(&[][..], Vec::as_slice);
+        let key = |p: &Value| {
+            let p = self.resolve(p);
+            (p.get("name").cloned(), p.get("in").cloned())
+        };
+        let own_keys: Vec<_> = own.iter().map(key).collect();
+        let parameters: Vec<&Value> = shared
+            .iter()
+            .filter(|p| !own_keys.contains(&key(p)))
+            .chain(own.iter())
+            .collect();
+        for parameter in parameters {
+            if let Some(index) = self.parameter(&context, parameter) {
+                let section = self.items[index].section;
+                result.items.entry(section).or_default().push(index);
+            }
+        }
+
+        let tags: Vec<String> = operation
+            .get("tags")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .map(|tag| sanitize(&tag.split_whitespace().collect::<Vec<_>>().join("-"), "_-"))
+            .collect();
+        if !tags.is_empty() {
+            result.lines.push(format!("tags: {}", tags.join(" ")));
+        }
+
+        let mut request = None;
+        let mut request_examples = vec![];
+        let request_body = operation.get("requestBody").map(|body| self.resolve(body));
+        if let Some(content) = request_body
+            .and_then(|b| b.get("content"))
+            .and_then(Value::as_object)
+        {
+            let mut consumes = vec![];
+            self.media_names(&context, content, &mut consumes);
+            if !consumes.is_empty() {
+                result
+                    .lines
+                    .push(format!("consumes: {}", consumes.join(" ")));
+            }
+            if let Some(media) = Self::main_media(content) {
+                request = self.body_model(&format!("{} request", name), media);
+                request_examples = self.media_examples(media);
+            }
+        }
+
+        let mut produces = vec![];
+        let mut codes = vec![];
+        let mut response = None;
+        let mut response_examples = vec![];
+        // the first 2xx response with content describes the result
+        let mut success = false;
+        let responses = operation.get("responses").and_then(Value::as_object);
+        for (code, definition) in responses.into_iter().flatten() {
+            if code.len() != 3 || !code.chars().all(|c| c.is_ascii_digit()) {
+                self.warnings
+                    .push(format!("{}: response {} skipped", context, code));
+                continue;
+            }
+            let definition = self.resolve(definition);
+            codes.push(code.to_owned());
+            let description = definition
+                .get("description")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            self.status_codes
+                .entry(code.to_owned())
+                .or_insert_with(|| quoted(description));
+            let content = definition.get("content").and_then(Value::as_object);
+            if let Some(content) = content {
+                self.media_names(&context, content, &mut produces);
+                if code.starts_with('2') && !success {
+                    success = true;
+                    if let Some(media) = Self::main_media(content) {
+                        response = self.body_model(&format!("{} response", name), media);
+                        response_examples = self.media_examples(media);
+                    }
+                }
+            }
+        }
+        if !produces.is_empty() {
+            result
+                .lines
+                .push(format!("produces: {}", produces.join(" ")));
+        }
+        if !codes.is_empty() {
+            result
+                .lines
+                .push(format!("status_codes: {}", codes.join(" ")));
+        }
+
+        let examples = pair_examples(request_examples, response_examples);
+        if !examples.is_empty() {
+            let mut key = name.to_owned();
+            let mut suffix = 2;
+            while self.examples.contains_key(&key) {
+                key = format!("{}{}", name, suffix);
+                suffix += 1;
+            }
+            result.lines.push(format!("example: {}", key));
+            self.examples.insert(key, Value::Array(examples));
+        }
+        if let Some(request) = request {
+            result.lines.push(format!("request: {}", request));
+        }
+        if let Some(response) = response {
+            result.lines.push(format!("response: {}", response));
+        }
+        result
+    }
+
+    /// Aliases items with names that are not camelCase or shared by several items
+    fn assign_aliases(&mut self) {
+        let mut used: HashSet<(&str, String)> = self
+            .items
+            .iter()
+            .map(|item| (item.section, item.name.to_owned()))
+            .collect();
+        for index in 0..self.items.len() {
+            let (section, name) = (self.items[index].section, self.items[index].name.to_owned());
+            let shared = self.items[..index]
+                .iter()
+                .any(|item| item.section == section && item.name == name);
+            let camel = camel_case(&name);
+            if !shared && (camel == name || camel.is_empty()) {
+                continue;
+            }
+            let base = if camel.is_empty() {
+                name.to_owned()
+            } else {
+                camel
+            };
+            let mut alias = base.to_owned();
+            let mut suffix = 2;
+            while used.contains(&(section, alias.to_owned())) {
+                alias = format!("{}{}", base, suffix);
+                suffix += 1;
+            }
+            used.insert((section, alias.to_owned()));
+            self.items[index].alias = alias;
+        }
+    }
+}
+
+/// Request and response examples with the same name go together
+fn pair_examples(
+    requests: Vec<(String, String, Value)>,
+    mut responses: Vec<(String, String, Value)>,
+) -> Vec<Value> {
+    let mut result = vec![];
+    let single = requests.len() == 1 && responses.len() == 1;
+    for (name, summary, request) in requests {
+        let matching = responses
+            .iter()
+            .position(|(response_name, _, _)| single || *response_name == name);
+        let mut example = Map::new();
+        let description = if summary.is_empty() { name } else { summary };
+        if !description.is_empty() {
+            example.insert("description".to_owned(), Value::from(description));
+        }
+        example.insert("request".to_owned(), request);
+        if let Some(index) = matching {
+            let (_, _, response) = responses.remove(index);
+            example.insert("response".to_owned(), response);
+        }
+        result.push(Value::Object(example));
+    }
+    for (name, summary, response) in responses {
+        let mut example = Map::new();
+        let description = if summary.is_empty() { name } else { summary };
+        if !description.is_empty() {
+            example.insert("description".to_owned(), Value::from(description));
+        }
+        example.insert("response".to_owned(), response);
+        result.push(Value::Object(example));
+    }
+    result
+}
+
+/// DSL constraints of a schema, `collection` holds the array keywords
+fn constraints(values: &Value, collection: &Value) -> String {
+    let mut out = String::new();
+    for (keyword, name) in [("minimum", "min"), ("maximum", "max")] {
+        if let Some(number) = values.get(keyword).and_then(number_literal) {
+            let _ = write!(out, " {}({})", name, number);
+        }
+    }
+    for keyword in ["minLength", "maxLength"] {
+        if let Some(length) = values.get(keyword).and_then(Value::as_u64) {
+            let _ = write!(out, " {}({})", keyword, length);
+        }
+    }
+    // strings keep escapes verbatim, patterns with them cannot be written back
+    if let Some(pattern) = values.get("pattern").and_then(Value::as_str) {
+        if !pattern.contains(['\\', '"']) {
+            let _ = write!(out, " pattern(\"{}\")", pattern);
+        }
+    }
+    for keyword in ["minItems", "maxItems"] {
+        if let Some(length) = collection.get(keyword).and_then(Value::as_u64) {
+            let _ = write!(out, " {}({})", keyword, length);
+        }
+    }
+    if collection.get("uniqueItems") == Some(&Value::Bool(true)) {
+        out.push_str(" uniqueItems");
+    }
+    out
+}
+
+/// Greedily replaces item sets shared by several operations with groups, returns the
+/// groups with their member references
+fn detect_groups(section: &str, lists: &mut [Vec<String>]) -> Vec<(String, Vec<String>)> {
+    let mut groups: Vec<(String, Vec<String>)> = vec![];
+    loop {
+        let mut best: Option<(usize, Vec<String>)> = None;
+        for (i, first) in lists.iter().enumerate() {
+            for second in lists.iter().skip(i + 1) {
+                let shared: Vec<String> = first
+                    .iter()
+                    .filter(|r| !r.starts_with("g(") && second.contains(r))
+                    .cloned()
+                    .collect();
+                if shared.len() < 2 {
+                    continue;
+                }
+                let count = lists
+                    .iter()
+                    .filter(|list| shared.iter().all(|r| list.contains(r)))
+                    .count();
+                let score = shared.len() * count;
+                if best
+                    .as_ref()
+                    .is_none_or(|(best_score, _)| score > *best_score)
+                {
+                    best = Some((score, shared));
+                }
+            }
+        }
+        let members = match best {
+            Some((_, members)) => members,
+            None => break,
+        };
+        let base = match members.len() {
+            2 | 3 => camel_case(&members.join(" ")),
+            _ => camel_case(&format!("{} {}", members[0], section)),
+        };
+        let mut name = base.to_owned();
+        let mut suffix = 2;
+        while groups.iter().any(|(group, _)| *group == name) {
+            name = format!("{}{}", base, suffix);
+            suffix += 1;
+        }
+        let reference = format!("g({})", name);
+        for list in lists.iter_mut() {
+            if !members.iter().all(|r| list.contains(r)) {
+                continue;
+            }
+            let at = list.iter().position(|r| members.contains(r)).unwrap_or(0);
+            list.retain(|r| !members.contains(r));
+            list.insert(at.min(list.len()), reference.to_owned());
+        }
+        groups.push((name, members));
+    }
+    groups
+}
+
+/// `.api`, `.model` and examples equivalent to an OpenAPI 3 JSON document
+pub fn import(content: &str) -> Result<Imported, String> {
+    let document: Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    let keys: Keys = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    let document = &document;
+    let version = document
+        .get("openapi")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    if !version.starts_with("3.") {
+        return Err(format!(
+            "Only OpenAPI 3 documents can be imported, found `{}`",
+            version
+        ));
+    }
+    let mut order = HashMap::new();
+    key_order(document, &keys, &mut order);
+    let mut importer = Importer {
+        document,
+        order,
+        defined: BTreeMap::new(),
+        names: HashSet::new(),
+        visiting: HashSet::new(),
+        models: vec![],
+        items: vec![],
+        status_codes: BTreeMap::new(),
+        examples: Map::new(),
+        warnings: vec![],
+    };
+    let schemas = document
+        .pointer("/components/schemas")
+        .and_then(Value::as_object);
+    for (name, schema) in schemas.into_iter().flatten() {
+        if importer.is_model(schema) {
+            importer.model_for(&format!("#/components/schemas/{}", name), schema);
+        }
+    }
+    if document.pointer("/components/securitySchemes").is_some() {
+        importer
+            .warnings
+            .push("security schemes are not imported, declare their headers".to_owned());
+    }
+
+    let mut operations = vec![];
+    let paths = document.get("paths").and_then(Value::as_object);
+    for (path, definition) in paths.into_iter().flatten() {
+        let valid = path.split('/').all(|segment| {
+            segment.is_empty()
+                || is_ident(segment, "_-")
+                || (segment.starts_with('{')
+                    && segment.ends_with('}')
+                    && is_ident(&segment[1..segment.len() - 1], "_-"))
+        });
+        if !valid || !path.starts_with('/') {
+            importer.warnings.push(format!(
+                "{}: path has characters APIsh paths cannot hold",
+                path
+            ));
+            continue;
+        }
+        let shared = definition
+            .get("parameters")
+            .and_then(Value::as_array)
+            .map_or(&[][..], Vec::as_slice);
+        for (verb, operation) in definition.as_object().into_iter().flatten() {
+            match VERBS.iter().find(|v| *v == verb) {
+                Some(verb) => operations.push(importer.operation(path, verb, shared, operation)),
+                None if verb == "parameters" || verb == "summary" || verb == "description" => {}
+                None => importer.warnings.push(format!(
+                    "{} {}: operation skipped",
+                    verb.to_uppercase(),
+                    path
+                )),
+            }
+        }
+    }
+    importer.assign_aliases();
+
+    let mut api = String::new();
+    let info = |key: &str| {
+        document
+            .pointer(&format!("/info/{}", key))
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+    };
+    let _ = writeln!(
+        api,
+        "title: {}\nversion: {}",
+        quoted(info("title")),
+        quoted(info("version"))
+    );
+    for (section, _) in LOCATIONS {
+        let items: Vec<&Item> = importer
+            .items
+            .iter()
+            .filter(|i| i.section == section)
+            .collect();
+        if items.is_empty() {
+            continue;
+        }
+        let _ = writeln!(api, "{}:", section);
+        for item in items {
+            let alias = match item.alias.is_empty() {
+                true => String::new(),
+                false => format!(" alias {}", item.alias),
+            };
+            let _ = writeln!(
+                api,
+                "  {} {}{}{}: {}",
+                item.name, item.data_type, alias, item.options, item.description
+            );
+        }
+    }
+    if !importer.status_codes.is_empty() {
+        api.push_str("status_codes:\n");
+        for (code, description) in &importer.status_codes {
+            let _ = writeln!(api, "  {}: {}", code, description);
+        }
+    }
+    let mut references: Vec<BTreeMap<&str, Vec<String>>> = operations
+        .iter()
+        .map(|operation| {
+            operation
+                .items
+                .iter()
+                .map(|(section, indexes)| {
+                    let refs = indexes
+                        .iter()
+                        .map(|index| importer.items[*index].reference().to_owned())
+                        .collect();
+                    (*section, refs)
+                })
+                .collect()
+        })
+        .collect();
+    for (section, _) in LOCATIONS {
+        let mut lists: Vec<Vec<String>> = references
+            .iter_mut()
+            .map(|refs| refs.remove(section).unwrap_or_default())
+            .collect();
+        let groups = detect_groups(section, &mut lists);
+        if !groups.is_empty() {
+            let _ = writeln!(api, "{}_groups:", section);
+            for (name, members) in groups {
+                let _ = writeln!(api, "  {}: {}", name, members.join(" "));
+            }
+        }
+        for (refs, list) in references.iter_mut().zip(lists) {
+            if !list.is_empty() {
+                refs.insert(section, list);
+            }
+        }
+    }
+    api.push_str("\napis:\n");
+    let mut current = "";
+    for (operation, refs) in operations.iter().zip(&references) {
+        if operation.path != current {
+            let _ = writeln!(api, "  {}:", operation.path);
+            current = &operation.path;
+        }
+        let _ = writeln!(api, "    {}: {}", operation.verb, operation.description);
+        for (section, list) in refs {
+            let _ = writeln!(api, "      {}: {}", section, list.join(" "));
+        }
+        for line in &operation.lines {
+            let _ = writeln!(api, "      {}", line);
+        }
+    }
+
+    let api = format_api(&api, false)?;
+    let models = match importer.models.is_empty() {
+        true => String::new(),
+        false => format_models(&format!("{}\n", importer.models.join("\n\n")), false)?,
+    };
+    Ok(Imported {
+        api,
+        models,
+        examples: importer.examples,
+        warnings: importer.warnings,
+    })
+}
+
+#[test]
+fn test_import_openapi() {
+    let imported = import(
+        r##"{
+  "openapi": "3.0.3",
+  "info": { "title": "Pets", "version": "1.0.0" },
+  "paths": {
+    "/pets": {
+      "get": {
+        "operationId": "listPets",
+        "summary": "Lists pets",
+        "tags": ["pets"],
+        "parameters": [
+          { "name": "X-Request-Id", "in": "header", "required": true, "schema": { "type": "string" } },
+          { "name": "limit", "in": "query", "schema": { "type": "integer", "maximum": 100 } },
+          { "name": "offset", "in": "query", "schema": { "type": "integer" } }
+        ],
+        "responses": {
+          "200": {
+            "description": "The pets",
+            "content": { "application/json": { "schema": { "$ref": "#/components/schemas/PetPage" } } }
+          }
+        }
+      },
+      "post": {
+        "operationId": "createPet",
+        "parameters": [
+          { "name": "X-Request-Id", "in": "header", "required": true, "schema": { "type": "string" } }
+        ],
+        "requestBody": {
+          "content": {
+            "application/json": {
+              "schema": { "$ref": "#/components/schemas/Pet" },
+              "example": { "name": "fido", "kind": "dog" }
+            }
+          }
+        },
+        "responses": { "201": { "description": "Created" } }
+      }
+    },
+    "/owners": {
+      "get": {
+        "parameters": [
+          { "name": "limit", "in": "query", "schema": { "type": "integer", "maximum": 100 } },
+          { "name": "offset", "in": "query", "schema": { "type": "integer" } }
+        ],
+        "responses": { "200": { "description": "OK" } }
+      }
+    }
+  },
+  "components": {
+    "schemas": {
+      "Pet": {
+        "type": "object",
+        "required": ["name"],
+        "properties": {
+          "name": { "type": "string", "minLength": 1, "description": "Pet name", "example": "fido" },
+          "kind": { "$ref": "#/components/schemas/Kind" },
+          "tags": { "type": "array", "items": { "type": "string" } },
+          "owner": { "type": "object", "properties": { "name": { "type": "string" } }, "nullable": true }
+        }
+      },
+      "Kind": { "type": "string", "enum": ["dog", "in-house"] },
+      "PetPage": { "type": "object", "properties": { "items": { "type": "array", "items": { "$ref": "#/components/schemas/Pet" } } } }
+    }
+  }
+}"##,
+    )
+    .unwrap();
+    for expected in [
+        "headers:\n  X-Request-Id string alias xRequestId required: \"\"\n",
+        "query:\n  limit number max(100): \"\"\n  offset number:         \"\"\n",
+        "query_groups:\n  limitOffset: limit offset\n",
+        "  /owners:\n    get: \"\"\n      query: g(limitOffset)\n      status_codes: 200\n",
+        "    get: \"Lists pets\"\n      headers: xRequestId\n      query: g(limitOffset)\n      operation: listPets\n      tags: pets\n      produces: json\n      status_codes: 200\n      response: PetPage\n",
+        "    post: \"\"\n      headers: xRequestId\n      operation: createPet\n      consumes: json\n      status_codes: 201\n      example: createPet\n      request: Pet\n",
+    ] {
+        assert!(imported.api.contains(expected), "missing {}\n{}", expected, imported.api);
+    }
+    for expected in [
+        "enum Kind { dog, in_house = \"in-house\" }\n",
+        "struct PetOwner {\n  name? string\n}\n",
+        "struct Pet {\n  name   string    required minLength(1) `example: fido` \"Pet name\"\n  kind?  Kind\n  tags?  []string\n  owner? PetOwner?\n}\n",
+    ] {
+        assert!(imported.models.contains(expected), "missing {}\n{}", expected, imported.models);
+    }
+    assert_eq!(
+        imported.examples["createPet"],
+        serde_json::json!([{ "request": { "name": "fido", "kind": "dog" } }])
+    );
+    let project = crate::project::Project::new_from_sources(
+        &imported.api,
+        Some(&imported.models),
+        Some(&Value::Object(imported.examples).to_string()),
+    )
+    .unwrap();
+    assert_eq!(project.endpoints.len(), 2);
+}
+
+#[test]
+fn test_import_wire_values() {
+    let document = serde_json::json!({
+        "openapi": "3.0.3",
+        "info": { "title": "Quotes", "version": "1.0.0" },
+        "paths": {},
+        "components": { "schemas": {
+            "Mark": { "type": "string", "enum": ["plain", "say \"hi\"", "back\\slash"] },
+            "Note": { "type": "object", "properties": {
+                "text": { "type": "string", "default": "a \"b\"" },
+                "title": { "type": "string", "default": "two  spaces" }
+            } }
+        } }
+    });
+    let imported = import(&document.to_string()).unwrap();
+    assert!(
+        imported.models.contains("enum Mark { plain }\n"),
+        "{}",
+        imported.models
+    );
+    assert!(
+        imported.models.contains("text?  string\n"),
+        "{}",
+        imported.models
+    );
+    assert!(
+        imported
+            .models
+            .contains("title? string = \"two  spaces\"\n"),
+        "{}",
+        imported.models
+    );
+    assert_eq!(
+        imported.warnings,
+        [
+            "Mark: enum value \"say \\\"hi\\\"\" with quotes, backslashes or control characters skipped",
+            "Mark: enum value \"back\\\\slash\" with quotes, backslashes or control characters skipped",
+            "Note.text: default with quotes, backslashes or control characters skipped",
+        ]
+    );
+}
+
+#[test]
+fn test_import_compiles() {
+    let imported = import(
+        r##"{
+  "openapi": "3.0.3",
+  "info": { "title": "Shapes", "version": "1.0.0" },
+  "paths": {
+    "/holders": {
+      "get": {
+        "parameters": [
+          { "name": "tags", "in": "query", "schema": { "type": "array", "items": { "type": "string" } } }
+        ],
+        "responses": {
+          "200": { "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Holder" } } } }
+        }
+      }
+    }
+  },
+  "components": {
+    "schemas": {
+      "Holder": {
+        "type": "object",
+        "properties": {
+          "shape": { "$ref": "#/components/schemas/Shape" },
+          "mixed": { "$ref": "#/components/schemas/Mixed" }
+        }
+      },
+      "Shape": { "oneOf": [{ "$ref": "#/components/schemas/Pet" }, { "$ref": "#/components/schemas/Kind" }, { "$ref": "#/components/schemas/Owner" }] },
+      "Mixed": { "oneOf": [{ "$ref": "#/components/schemas/Kind" }, { "$ref": "#/components/schemas/Size" }] },
+      "Pet": { "type": "object", "properties": { "name": { "type": "string" } } },
+      "Owner": { "type": "object", "properties": { "name": { "type": "string" } } },
+      "Kind": { "type": "string", "enum": ["dog", "cat"] },
+      "Size": { "type": "integer", "enum": [1, 2] }
+    }
+  }
+}"##,
+    )
+    .unwrap();
+    assert!(
+        imported.models.contains("union Shape = Pet | Owner\n"),
+        "{}",
+        imported.models
+    );
+    assert!(
+        imported.models.contains("  mixed? Kind\n"),
+        "{}",
+        imported.models
+    );
+    assert!(
+        imported.api.contains("  tags string: \"\"\n"),
+        "{}",
+        imported.api
+    );
+    for expected in [
+        "Shape: variant 2 is not a struct",
+        "Mixed: oneOf/anyOf of non object schemas imported as the first one",
+        "GET /holders: array parameter tags imported as string",
+    ] {
+        assert!(
+            imported.warnings.iter().any(|w| w == expected),
+            "{:?}",
+            imported.warnings
+        );
+    }
+    crate::project::Project::new_from_sources(&imported.api, Some(&imported.models), None).unwrap();
+}
@@ -0,0 +1,283 @@
+//! JSON Schema (draft 2020-12) export of models: every struct, enum and union is a `$defs`
+//! entry and fields reference them with `$ref`
+
+use std::collections::BTreeSet;
+
+use serde_json::{json, Map, Value};
+
+use crate::models::{typed_value, Entity, Enum, Field, ProjectModel, Union};
+
+pub const DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+fn reference(name: &str) -> Value {
+    json!({ "$ref": format!("#/$defs/{}", name) })
+}
+
+fn type_schema(data_type: &str, models: &ProjectModel) -> Value {
+    let type_field = match data_type {
+        "int" | "integer" | "long" => "integer",
+        "number" | "float" | "double" => "number",
+        "bool" | "boolean" => "boolean",
+        _ => "string",
+    };
+    if models.entities.contains_key(data_type)
+        || models.enums.contains_key(data_type)
+        || models.unions.contains_key(data_type)
+    {
+        return reference(data_type);
+    }
+    let mut schema = json!({ "type": type_field });
+    match data_type {
+        "date" => schema["format"] = json!("date"),
+        "datetime" => schema["format"] = json!("date-time"),
+        _ => {}
+    }
+    schema
+}
+
+/// Adds the validation keywords of `constraints` to `schema`
+fn constrain(schema: &mut Value, constraints: Value) {
+    if let (Some(schema), Value::Object(constraints)) = (schema.as_object_mut(), constraints) {
+        schema.extend(constraints);
+    }
+}
+
+fn field_schema(field: &Field, models: &ProjectModel) -> Value {
+    let mut schema = type_schema(&field.data_type, models);
+    let mut constraints = serde_json::to_value(&field.constraints).unwrap_or_default();
+    if field.is_array {
+        let mut array = json!({ "type": "array" });
+        if let Some(constraints) = constraints.as_object_mut() {
+            for keyword in ["minItems", "maxItems", "uniqueItems"] {
+                if let Some(value) = constraints.remove(keyword) {
+                    array[keyword] = value;
+                }
+            }
+        }
+        constrain(&mut schema, constraints);
+        array["items"] = schema;
+        schema = array;
+    } else {
+        constrain(&mut schema, constraints);
+    }
+    if field.is_map {
+        schema = json!({ "type": "object", "additionalProperties": schema });
+    }
+    if field.is_nullable {
+        schema = match schema["type"].as_str() {
+            Some(type_field) => {
+                let mut nullable = schema.clone();
+                nullable["type"] = json!([type_field, "null"]);
+                nullable
+            }
+            None => json!({ "anyOf": [schema, { "type": "null" }] }),
+        };
+    }
+    if !field.description.is_empty() {
+        schema["description"] = json!(field.description);
+    }
+    if let Some(default) = models.default_value(field) {
+        schema["default"] = default;
+    }
+    if let Some(example) = field.example_value() {
+        schema["examples"] = json!([example]);
+    }
+    schema
+}
+
+fn entity_schema(entity: &Entity, models: &ProjectModel) -> Value {
+    let mut properties = Map::new();
+    let mut required = vec![];
+    for field in entity.ordered_fields() {
+        if !field.inherited_from.is_empty() {
+            // described by the base schema
+            continue;
+        }
+        if field.is_required {
+            required.push(field.identifier.to_owned());
+        }
+        properties.insert(field.identifier.to_owned(), field_schema(field, models));
+    }
+    let mut schema = json!({ "type": "object", "properties": properties });
+    if !required.is_empty() {
+        schema["required"] = json!(required);
+    }
+    if entity.bases.is_empty() {
+        return schema;
+    }
+    let mut all_of: Vec<Value> = entity.bases.iter().map(|base| reference(base)).collect();
+    all_of.push(schema);
+    json!({ "allOf": all_of })
+}
+
+fn enum_schema(definition: &Enum) -> Value {
+    let value_type = match definition.value_type.as_str() {
+        "int" => "int",
+        _ => "string",
+    };
+    let values: Vec<Value> = definition
+        .members
+        .iter()
+        .map(|m| typed_value(value_type, &m.value))
+        .collect();
+    let type_field = match value_type {
+        "int" => "integer",
+        _ => "string",
+    };
+    let mut schema = json!({ "type": type_field, "enum": values });
+    // member names, descriptions and deprecation only fit in per value subschemas
+    let annotated = definition
+        .members
+        .iter()
+        .any(|m| m.name != m.value || !m.description.is_empty() || m.deprecated);
+    if annotated {
+        let one_of: Vec<Value> = definition
+            .members
+            .iter()
+            .map(|m| {
+                let mut member = json!({
+                    "const": typed_value(value_type, &m.value),
+                    "title": m.name,
+                });
+                if !m.description.is_empty() {
+                    member["description"] = json!(m.description);
+                }
+                if m.deprecated {
+                    member["deprecated"] = json!(true);
+                }
+                member
+            })
+            .collect();
+        schema["oneOf"] = json!(one_of);
+    }
+    schema
+}
+
+fn union_schema(definition: &Union, models: &ProjectModel) -> Value {
+    let one_of: Vec<Value> = definition
+        .variants
+        .iter()
+        .map(|variant| type_schema(variant, models))
+        .collect();
+    json!({ "oneOf": one_of })
+}
+
+/// Schema of every struct, enum and union by name
+pub fn definitions(models: &ProjectModel) -> Map<String, Value> {
+    let mut definitions = Map::new();
+    for (name, entity) in &models.entities {
+        definitions.insert(name.to_owned(), entity_schema(entity, models));
+    }
+    for (name, definition) in &models.enums {
+        definitions.insert(name.to_owned(), enum_schema(definition));
+    }
+    for (name, definition) in &models.unions {
+        definitions.insert(name.to_owned(), union_schema(definition, models));
+    }
+    definitions
+}
+
+/// Single document with every model under `$defs`
+pub fn document(models: &ProjectModel) -> Value {
+    json!({ "$schema": DIALECT, "$defs": definitions(models) })
+}
+
+fn collect_references(schema: &Value, names: &mut BTreeSet<String>) {
+    match schema {
+        Value::Object(object) => {
+            for (key, value) in object {
+                match (key.as_str(), value.as_str()) {
+                    ("$ref", Some(target)) => {
+                        names.insert(target.trim_start_matches("#/$defs/").to_owned());
+                    }
+                    _ => collect_references(value, names),
+                }
+            }
+        }
+        Value::Array(items) => items
+            .iter()
+            .for_each(|item| collect_references(item, names)),
+        _ => {}
+    }
+}
+
+/// Document per struct, referencing it from the root and keeping the `$defs` it reaches
+pub fn entity_documents(models: &ProjectModel) -> Vec<(String, Value)> {
+    let definitions = definitions(models);
+    let mut names: Vec<&String> = models.entities.keys().collect();
+    names.sort();
+    names
+        .into_iter()
+        .map(|name| {
+            let mut reached = BTreeSet::new();
+            let mut pending = vec![name.to_owned()];
+            while let Some(next) = pending.pop() {
+                if !reached.insert(next.to_owned()) {
+                    continue;
+                }
+                let mut references = BTreeSet::new();
+                if let Some(schema) = definitions.get(&next) {
+                    collect_references(schema, &mut references);
+                }
+                pending.extend(references);
+            }
+            let defs: Map<String, Value> = definitions
+                .iter()
+                .filter(|(definition, _)| reached.contains(*definition))
+                .map(|(definition, schema)| (definition.to_owned(), schema.clone()))
+                .collect();
+            let document = json!({
+                "$schema": DIALECT,
+                "title": name,
+                "$ref": format!("#/$defs/{}", name),
+                "$defs": defs,
+            });
+            (name.to_owned(), document)
+        })
+        .collect()
+}
+
+#[test]
+fn test_json_schema() {
+    let models = crate::models::get_models(
+        "struct Base {\n id int required\n}\n\nstruct Pet extends Base {\n name string required minLength(1) \"Pet name\"\n tags? []string maxItems(3)\n mood Mood?\n}\n\nstruct Owner {\n pets []Pet\n mood Mood = SAD\n}\n\nenum Mood {\n HAPPY = \"happy\" \"Smiling\"\n SAD = \"sad\"\n}\n",
+    )
+    .unwrap();
+    let document = document(&models);
+    assert_eq!(document["$schema"], json!(DIALECT));
+    assert_eq!(
+        document["$defs"]["Pet"],
+        json!({
+            "allOf": [
+                { "$ref": "#/$defs/Base" },
+                {
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string", "minLength": 1, "description": "Pet name" },
+                        "tags": { "type": "array", "maxItems": 3, "items": { "type": "string" } },
+                        "mood": { "anyOf": [{ "$ref": "#/$defs/Mood" }, { "type": "null" }] },
+                    },
+                    "required": ["name"],
+                }
+            ]
+        })
+    );
+    assert_eq!(document["$defs"]["Mood"]["enum"], json!(["happy", "sad"]));
+    assert_eq!(
+        document["$defs"]["Mood"]["oneOf"][0],
+        json!({ "const": "happy", "title": "HAPPY", "description": "Smiling" })
+    );
+
+    // the default names a member, the schema holds its wire value
+    assert_eq!(
+        document["$defs"]["Owner"]["properties"]["mood"],
+        json!({ "$ref": "#/$defs/Mood", "default": "sad" })
+    );
+
+    let documents = entity_documents(&models);
+    let (name, owner) = &documents[1];
+    assert_eq!(name, "Owner");
+    assert_eq!(owner["$ref"], json!("#/$defs/Owner"));
+    let defs: Vec<&String> = owner["$defs"].as_object().unwrap().keys().collect();
+    assert_eq!(defs, vec!["Base", "Mood", "Owner", "Pet"]);
+}
@@ -1,89 +1,127 @@
-use wasm_bindgen::prelude::*;
-use crate::models::{Entity, Enum, ProjectModel};
-use std::collections::HashMap;
+//! APIsh compiler: parses `.api` files, models and examples into a `Project` and produces
+//! the api.json, api-spec.json and openapi.json documents
+//!
+//! ```no_run
+//! let sources = apish::Sources {
+//!     api: std::fs::read_to_string("example.api").unwrap(),
+//!     models: std::fs::read_to_string("models.model").ok(),
+//!     examples: None,
+//! };
+//! let compilation = apish::compile(&sources).unwrap();
+//! println!("{}", compilation.project.title);
+//! ```
+
 use serde::Serialize;
 
-mod models;
+pub mod api_spec;
+pub mod codegen;
+pub mod diff;
+pub mod examples;
+pub mod format;
+pub mod import;
+pub mod json_schema;
+pub mod lint;
+pub mod markdown;
+pub mod mock;
+pub mod models;
+pub mod open_api;
+pub mod project;
+pub mod site;
+pub mod verify;
+mod wasm;
+
+pub use api_spec::APISpec;
+pub use models::{Diagnostic, ProjectModel};
+pub use open_api::OpenAPI;
+pub use project::{APIConfiguration, APIDefinition, Project};
 
-#[derive(Serialize)]
-pub struct ModelsProject {
-    pub enums: Vec<Enum>,
-    pub entities: Vec<ModelEntity>,
+/// Sources of an API, models and examples are optional
+#[derive(Debug, Default)]
+pub struct Sources {
+    pub api: String,
+    pub models: Option<String>,
+    pub examples: Option<String>,
 }
 
-#[derive(Serialize)]
-pub struct ModelEntityField {
-    pub name: String,
-    pub data_type: String,
-    pub description: String,
-    /// use to represent arrays or maps
-    pub is_array: bool,
-    pub example: String,
-    pub markers: Vec<String>,
-    pub tags: HashMap<String, String>,
-    /// used only for enums
-    pub allowed_values: Vec<String>,
-    pub is_required: bool,
+/// Everything produced from `Sources`
+#[derive(Debug, Serialize)]
+pub struct Compilation {
+    /// api.json
+    #[serde(rename = "api")]
+    pub project: Project,
+    /// api-spec.json
+    pub api_spec: APISpec,
+    /// openapi.json
+    pub open_api: OpenAPI,
+    /// model warnings, the outputs are produced anyway
+    pub diagnostics: Vec<Diagnostic>,
 }
 
-#[derive(Serialize)]
-pub struct ModelEntity {
-    pub name: String,
-    pub fields: Vec<ModelEntityField>,
+/// Compiles the sources, fails on invalid API or models
+pub fn compile(sources: &Sources) -> Result<Compilation, String> {
+    let project = Project::new_from_sources(
+        &sources.api,
+        sources.models.as_deref(),
+        sources.examples.as_deref(),
+    )?;
+    Ok(compile_project(project))
 }
 
-fn to_summarized_entity(from: Entity) -> ModelEntity {
-    let mut new_fields = Vec::new();
-    for field in from.fields.into_iter() {
-        let mut is_required = false;
-        for v in &field.1.markers {
-            if v == "required" {
-                is_required = true;
-                break;
-            }
-        }
-        let mut example = field.1.example;
-        if example.starts_with("\"") && example.ends_with("\""){
-            example.remove(example.len() - 1);
-            example.remove(0);
-        }
-        let f = ModelEntityField {
-            name: field.0,
-            data_type: field.1.data_type,
-            description: field.1.description,
-            is_array: field.1.is_array,
-            example: "".to_string(),
-            markers: field.1.markers,
-            tags: field.1.tags,
-            allowed_values: field.1.allowed_values,
-            is_required
-        };
-        new_fields.push(f);
-    }
-    ModelEntity {
-        name: from.name,
-        fields: new_fields,
+/// Produces the outputs of an already built project, i.e. one loaded from api.json with
+/// `Project::new_from_json`
+pub fn compile_project(project: Project) -> Compilation {
+    let diagnostics = match &project.models {
+        Some(models) => models.diagnostics.clone(),
+        None => vec![],
+    };
+    Compilation {
+        api_spec: api_spec::API::new_project_spec(&project),
+        open_api: OpenAPI::new_from_project_spec(&project),
+        project,
+        diagnostics,
     }
 }
 
-fn convert(from: ProjectModel) -> ModelsProject {
-    let mut enums = Vec::new();
-    let mut entities = Vec::new();
-    for en in from.enums.into_iter() {
-        enums.push(en.1);
-    }
-    for entity_pair in from.entities.into_iter() {
-        entities.push(to_summarized_entity(entity_pair.1));
-    }
-    ModelsProject {
-        enums,
-        entities,
-    }
+#[test]
+fn test_compile_from_sources() {
+    let sources = Sources {
+        api: "title: \"Pets\"\nversion: \"1.0\"\n\napis:\n  /pets/{id}:\n    get: \"Pet by id\"\n      response: Pet\n".to_owned(),
+        models: Some("struct Pet {\n name string required\n owner Owner\n}\n".to_owned()),
+        examples: None,
+    };
+    let compilation = compile(&sources).unwrap();
+
+    assert_eq!(compilation.project.title, "Pets");
+    assert!(compilation.project.endpoints.contains_key("/pets/{id}"));
+    assert_eq!(compilation.diagnostics.len(), 1);
+    let json = serde_json::to_value(&compilation).unwrap();
+    assert!(json["open_api"]["components"]["schemas"]["Pet"].is_object());
+    assert!(json["api_spec"]["spec"]["/pets/{id}"]["get"]["response_object"].is_object());
+    let invalid = Sources {
+        api: "apis".to_owned(),
+        ..Sources::default()
+    };
+    assert!(compile(&invalid).is_err());
 }
 
-#[wasm_bindgen]
-pub fn parse_models(from: &str) -> String {
-    let res = models::get_models(from);
-    let project = convert(res);
-    serde_json::to_string(&project).unwrap()
-}
\ No newline at end of file
+#[test]
+fn test_api_json_round_trip() {
+    let sources = Sources {
+        api: "title: \"Pets\"\nversion: \"1.0\"\nheaders:\n  x-auth string alias auth required: \"Token\"\n\napis:\n  /pets:\n    post: \"New pet\"\n      headers: auth\n      request: Pet\n      example: pets\n".to_owned(),
+        models: Some("enum Kind int { DOG = 1, CAT }\n\nstruct Pet {\n name string required minLength(1)\n kind Kind `example: 2`\n tags? map[string][]string\n}\n".to_owned()),
+        examples: Some("{\"pets\": [{\"description\": \"A dog\", \"request\": {\"name\": \"fido\"}}]}".to_owned()),
+    };
+    let compilation = compile(&sources).unwrap();
+    let api_json = serde_json::to_string(&compilation.project).unwrap();
+
+    let reloaded = compile_project(Project::new_from_json(&api_json).unwrap());
+    assert_eq!(
+        serde_json::to_value(&reloaded.project).unwrap(),
+        serde_json::from_str::<serde_json::Value>(&api_json).unwrap()
+    );
+    assert_eq!(
+        serde_json::to_value(&reloaded.open_api).unwrap(),
+        serde_json::to_value(&compilation.open_api).unwrap()
+    );
+    assert!(Project::new_from_json("{}").is_err());
+}
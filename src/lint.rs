@@ -0,0 +1,316 @@
+//! Style rules over a `Project`, each rule has a default severity that a JSON config can
+//! override:
+//!
+//! ```json
+//! { "rules": { "kebab-case-path": "error", "description": "off" } }
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::project::{APIConfiguration, Project, ProjectArgument};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+    Off,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+            Severity::Off => "off",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Rule names, their default severity and what they check
+pub const RULES: [(&str, Severity, &str); 8] = [
+    (
+        "operation-id",
+        Severity::Error,
+        "every operation sets `operation:`",
+    ),
+    (
+        "operation-id-unique",
+        Severity::Error,
+        "operation ids are not repeated",
+    ),
+    (
+        "description",
+        Severity::Warning,
+        "operations, headers, params, query items and status codes are described",
+    ),
+    (
+        "post-request",
+        Severity::Warning,
+        "POST operations declare `consumes:` and `request:`",
+    ),
+    (
+        "client-error",
+        Severity::Warning,
+        "every operation declares at least one 4xx status code",
+    ),
+    (
+        "kebab-case-path",
+        Severity::Warning,
+        "path segments are kebab-case",
+    ),
+    (
+        "lowercase-header",
+        Severity::Warning,
+        "header names are lowercase",
+    ),
+    (
+        "retryable-error",
+        Severity::Error,
+        "`retryable` is only set on 4xx and 5xx status codes",
+    ),
+];
+
+/// Severity overrides by rule name, rules not listed keep their default
+#[derive(Debug, Default, Deserialize)]
+pub struct LintConfig {
+    #[serde(default)]
+    pub rules: HashMap<String, Severity>,
+}
+
+impl LintConfig {
+    pub fn new_from_json(content: &str) -> Result<LintConfig, String> {
+        let config: LintConfig =
+            serde_json::from_str(content).map_err(|e| format!("Invalid lint config {}", e))?;
+        for rule in config.rules.keys() {
+            if !RULES.iter().any(|(name, _, _)| name == rule) {
+                return Err(format!("Unknown lint rule `{}`", rule));
+            }
+        }
+        Ok(config)
+    }
+
+    pub fn severity(&self, rule: &str) -> Severity {
+        if let Some(severity) = self.rules.get(rule) {
+            return *severity;
+        }
+        RULES
+            .iter()
+            .find(|(name, _, _)| *name == rule)
+            .map_or(Severity::Off, |(_, severity, _)| *severity)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Finding {
+    pub rule: String,
+    pub severity: Severity,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}:{} {} {}: {}",
+            self.line, self.column, self.severity, self.rule, self.message
+        )
+    }
+}
+
+struct Linter<'a> {
+    config: &'a LintConfig,
+    findings: Vec<Finding>,
+}
+
+impl Linter<'_> {
+    fn report(&mut self, rule: &str, position: (usize, usize), message: String) {
+        let severity = self.config.severity(rule);
+        if severity == Severity::Off {
+            return;
+        }
+        self.findings.push(Finding {
+            rule: rule.to_owned(),
+            severity,
+            line: position.0,
+            column: position.1,
+            message,
+        });
+    }
+
+    fn check_arguments(&mut self, kind: &str, arguments: &[ProjectArgument]) {
+        for argument in arguments {
+            let position = (argument.line, argument.column);
+            if argument.description.is_empty() {
+                let message = format!("{} `{}` has no description", kind, argument.name);
+                self.report("description", position, message);
+            }
+            if kind == "Header" && argument.name != argument.name.to_lowercase() {
+                let message = format!("Header `{}` is not lowercase", argument.name);
+                self.report("lowercase-header", position, message);
+            }
+        }
+    }
+
+    fn check_operation(
+        &mut self,
+        verb: &str,
+        name: &str,
+        config: &APIConfiguration,
+        project: &Project,
+    ) {
+        let position = (config.line, config.column);
+        if config.operation.is_empty() {
+            self.report(
+                "operation-id",
+                position,
+                format!("{} has no operation id", name),
+            );
+        }
+        if config.description.is_empty() {
+            self.report(
+                "description",
+                position,
+                format!("{} has no description", name),
+            );
+        }
+        if verb == "post" {
+            if config.consumes.is_empty() {
+                let message = format!("{} does not declare `consumes:`", name);
+                self.report("post-request", position, message);
+            }
+            if config.request_model.is_empty() {
+                let message = format!("{} does not declare `request:`", name);
+                self.report("post-request", position, message);
+            }
+        }
+        let codes = project.get_status_codes(&config.status_codes);
+        if !codes.iter().any(|code| code.code.starts_with('4')) {
+            let message = format!("{} declares no 4xx status code", name);
+            self.report("client-error", position, message);
+        }
+    }
+}
+
+fn is_kebab_case(segment: &str) -> bool {
+    !segment.is_empty()
+        && segment.split('-').all(|word| {
+            !word.is_empty()
+                && word
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+        })
+}
+
+/// Applies every enabled rule, findings are sorted by position
+pub fn lint(project: &Project, config: &LintConfig) -> Vec<Finding> {
+    let mut linter = Linter {
+        config,
+        findings: vec![],
+    };
+    linter.check_arguments("Header", &project.headers);
+    linter.check_arguments("Param", &project.params);
+    linter.check_arguments("Query item", &project.query);
+
+    for status_code in &project.status_codes {
+        let position = (status_code.line, status_code.column);
+        if status_code.description.is_empty() {
+            let message = format!("Status code `{}` has no description", status_code.code);
+            linter.report("description", position, message);
+        }
+        if status_code.is_retryable
+            && !(status_code.code.starts_with('4') || status_code.code.starts_with('5'))
+        {
+            let message = format!(
+                "Status code `{}` is retryable but it is not an error",
+                status_code.code
+            );
+            linter.report("retryable-error", position, message);
+        }
+    }
+
+    let mut paths: Vec<&String> = project.endpoints.keys().filter(|p| !p.is_empty()).collect();
+    paths.sort_by_key(|path| project.endpoints[*path].position);
+    let mut operation_ids: HashMap<&str, String> = HashMap::new();
+    for path in paths {
+        let definition = &project.endpoints[path];
+        for segment in path.split('/') {
+            if segment.is_empty() || segment.starts_with('{') || is_kebab_case(segment) {
+                continue;
+            }
+            let message = format!("Path segment `{}` of `{}` is not kebab-case", segment, path);
+            let position = (definition.line, definition.column);
+            linter.report("kebab-case-path", position, message);
+        }
+        for (verb, config) in definition.operations() {
+            let name = format!("`{} {}`", verb.to_uppercase(), path);
+            linter.check_operation(verb, &name, config, project);
+            if config.operation.is_empty() {
+                continue;
+            }
+            match operation_ids.get(config.operation.as_str()) {
+                Some(first) => {
+                    let message = format!(
+                        "Operation id `{}` of {} is already used by {}",
+                        config.operation, name, first
+                    );
+                    let position = (config.line, config.column);
+                    linter.report("operation-id-unique", position, message);
+                }
+                None => {
+                    operation_ids.insert(&config.operation, name);
+                }
+            }
+        }
+    }
+    linter
+        .findings
+        .sort_by(|a, b| (a.line, a.column, &a.rule).cmp(&(b.line, b.column, &b.rule)));
+    linter.findings
+}
+
+#[test]
+fn test_lint_rules() {
+    let source = "title: \"Pets\"\nheaders:\n  X-Auth string: \"Token\"\nstatus_codes:\n  200 retryable: \"Ok\"\n  404: \"Missing\"\n\napis:\n  /petStore/{id}:\n    get: \"Pet\"\n      operation: getPet\n      status_codes: 200\n    post: \"New pet\"\n      operation: getPet\n      status_codes: 404\n    delete: \"\"\n      status_codes: 404\n";
+    let project = Project::new_from_sources(source, None, None).unwrap();
+    let findings = lint(&project, &LintConfig::default());
+    let reported: Vec<String> = findings
+        .iter()
+        .map(|f| format!("{}:{} {}", f.line, f.column, f.rule))
+        .collect();
+    assert_eq!(
+        reported,
+        vec![
+            "3:3 lowercase-header",
+            "5:3 retryable-error",
+            "9:3 kebab-case-path",
+            "10:5 client-error",
+            "13:5 operation-id-unique",
+            "13:5 post-request",
+            "13:5 post-request",
+            "16:5 description",
+            "16:5 operation-id",
+        ]
+    );
+    assert_eq!(
+        findings[0].to_string(),
+        "3:3 warning lowercase-header: Header `X-Auth` is not lowercase"
+    );
+
+    let config = LintConfig::new_from_json(
+        "{\"rules\": {\"post-request\": \"off\", \"client-error\": \"error\"}}",
+    )
+    .unwrap();
+    let findings = lint(&project, &config);
+    assert!(!findings.iter().any(|f| f.rule == "post-request"));
+    assert_eq!(findings[3].severity, Severity::Error);
+    assert!(LintConfig::new_from_json("{\"rules\": {\"nope\": \"off\"}}").is_err());
+}
@@ -1,40 +1,35 @@
-mod examples;
-mod models;
-mod open_api;
-mod project;
+#![allow(non_local_definitions)]
 
 extern crate structopt;
 #[macro_use]
 extern crate structopt_derive;
 
-extern crate serde;
-extern crate serde_json;
-
-extern crate pest;
-#[macro_use]
-extern crate pest_derive;
-
-use std::collections::HashMap;
+use std::fs;
 use std::fs::File;
+use std::path::Path;
+use std::process;
 use std::sync::mpsc::channel;
 use std::time::Duration;
 
-use crate::project::ProjectArgument;
-use serde::Serialize;
 use structopt::StructOpt;
 
 use notify::{RecommendedWatcher, RecursiveMode, Watcher};
 
-use crate::models::{Entity, Enum, ProjectModel};
+use apish::{
+    codegen, compile, compile_project, diff, format, import, lint, markdown, mock, site, verify,
+    Compilation, Project, Sources,
+};
 use notify::DebouncedEvent::Write;
-use project::{APIConfiguration, APIDefinition, DataType, Project, StatusCode};
 
 #[derive(StructOpt)]
 struct Opt {
     #[structopt(short = "w", help = "Keeps watching source API file changes")]
     watch: bool,
-    #[structopt(short = "f", help = "Input file")]
-    input: String,
+    #[structopt(
+        short = "f",
+        help = "Input file, .api source or a previously generated api.json"
+    )]
+    input: Option<String>,
     #[structopt(
         short = "m",
         help = "Input models file",
@@ -61,227 +56,192 @@ struct Opt {
         default_value = "./api-spec.json"
     )]
     spec_output: String,
+    #[structopt(long = "markdown", help = "Markdown documentation output file")]
+    markdown: Option<String>,
+    #[structopt(subcommand)]
+    cmd: Option<Command>,
 }
 
-#[derive(Debug, Serialize)]
-struct Argument {
-    name: String,
-    description: String,
-    data_type: DataType,
-    required: bool,
-    default_value: String,
-}
-
-#[derive(Debug, Serialize)]
-struct APIEndpoint {
-    description: String,
-    operation: String,
-    use_cases: Vec<String>,
-    params: Vec<Argument>,
-    query_strings: Vec<Argument>,
-    headers: Vec<Argument>,
-    tags: Vec<String>,
-    status_codes: Vec<StatusCode>,
-    produces: Vec<String>,
-    consumes: Vec<String>,
-    example: Option<Vec<examples::Example>>,
-    request_object: Option<Entity>,
-    request_enum: Option<Enum>,
-    response_object: Option<Entity>,
-    response_enum: Option<Enum>,
-}
-
-#[derive(Debug, Serialize)]
-struct APISpec {
-    title: String,
-    version: String,
-    spec: HashMap<String, API>,
-    models: Option<ProjectModel>,
-}
-
-#[derive(Debug, Serialize)]
-struct API {
-    position: isize,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    get: Option<APIEndpoint>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    post: Option<APIEndpoint>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    put: Option<APIEndpoint>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    delete: Option<APIEndpoint>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    patch: Option<APIEndpoint>,
-}
-
-impl APIEndpoint {
-    fn argument_from_project(argument: &ProjectArgument) -> Argument {
-        Argument {
-            name: argument.name.clone(),
-            description: argument.description.clone(),
-            required: argument.required,
-            data_type: argument.data_type.clone(),
-            default_value: argument.default_value.clone(),
-        }
-    }
-
-    fn transform_arguments(arguments: Vec<&ProjectArgument>) -> Vec<Argument> {
-        let mut args = Vec::new();
-        for argument in arguments {
-            let arg = APIEndpoint::argument_from_project(argument);
-            args.push(arg);
-        }
-        args
-    }
-
-    fn get_arguments(section: &str, project: &Project, from_list: &[String]) -> Vec<Argument> {
-        let args = Vec::new();
-        match section {
-            "headers" => {
-                let headers = project.get_headers(from_list);
-                return APIEndpoint::transform_arguments(headers);
-            }
-            "query" => {
-                let query_strings = project.get_query_strings(from_list);
-                return APIEndpoint::transform_arguments(query_strings);
-            }
-            "params" => {
-                let params = project.get_path_params(from_list);
-                return APIEndpoint::transform_arguments(params);
-            }
-            _ => {
-                // invalid section name
-            }
-        }
-        args
-    }
-
-    fn new_from_api_configuration(
-        configuration: &Option<APIConfiguration>,
-        project: &Project,
-    ) -> Option<APIEndpoint> {
-        match configuration {
-            Some(config) => {
-                let mut request: Option<Entity> = None;
-                let mut request_enum: Option<Enum> = None;
-                let mut response: Option<Entity> = None;
-                let mut response_enum: Option<Enum> = None;
-                let mut ex: Option<Vec<examples::Example>> = None;
-                if let Some(example) = project.examples.get(config.example.as_str()) {
-                    ex = Some(example.clone());
-                }
-                match &project.models {
-                    Some(models) => {
-                        if let Some(m) = models.entities.get(config.request_model.as_str()) {
-                            request = Some(m.clone());
-                        }
-                        if let Some(m) = models.enums.get(config.request_model.as_str()) {
-                            request_enum = Some(m.clone());
-                        }
-                        if let Some(m) = models.entities.get(config.response_model.as_str()) {
-                            response = Some(m.clone());
-                        }
-                        if let Some(m) = models.enums.get(config.response_model.as_str()) {
-                            response_enum = Some(m.clone());
-                        }
-                    }
-                    _ => {
-                        // ignore
-                    }
-                }
-                let endpoint = APIEndpoint {
-                    description: config.description.to_owned(),
-                    operation: config.operation.to_owned(),
-                    use_cases: config.use_cases.clone(),
-                    params: APIEndpoint::get_arguments("params", &project, &config.path_params),
-                    query_strings: APIEndpoint::get_arguments(
-                        "query",
-                        &project,
-                        &config.query_string,
-                    ),
-                    headers: APIEndpoint::get_arguments("headers", &project, &config.headers),
-                    tags: config.tags.clone(),
-                    status_codes: project.get_status_codes(&config.status_codes),
-                    produces: get_mime_types(&config.produces),
-                    consumes: get_mime_types(&config.consumes),
-                    example: ex,
-                    request_object: request,
-                    request_enum,
-                    response_object: response,
-                    response_enum,
-                };
-                Some(endpoint)
-            }
-            None => None,
-        }
-    }
+#[derive(StructOpt)]
+enum Command {
+    #[structopt(name = "fmt", about = "Formats .api and .model files in place")]
+    Fmt {
+        #[structopt(long = "check", help = "Only reports files that are not formatted")]
+        check: bool,
+        #[structopt(long = "sort", help = "Sorts reusable items, paths and models by name")]
+        sort: bool,
+        #[structopt(help = "Files to format")]
+        files: Vec<String>,
+    },
+    #[structopt(name = "lint", about = "Checks the style of an .api file")]
+    Lint {
+        #[structopt(short = "c", long = "config", help = "JSON file with rule severities")]
+        config: Option<String>,
+        #[structopt(long = "json", help = "Prints the findings as JSON")]
+        json: bool,
+        #[structopt(help = "API file")]
+        file: String,
+    },
+    #[structopt(
+        name = "diff",
+        about = "Classifies the changes between two API versions"
+    )]
+    Diff {
+        #[structopt(long = "old-models", help = "Models file of the old version")]
+        old_models: Option<String>,
+        #[structopt(long = "new-models", help = "Models file of the new version")]
+        new_models: Option<String>,
+        #[structopt(long = "json", help = "Prints the changes as JSON")]
+        json: bool,
+        #[structopt(
+            long = "check",
+            help = "Fails when the new version is lower than the suggested one"
+        )]
+        check: bool,
+        #[structopt(help = "Old .api file or api.json")]
+        old: String,
+        #[structopt(help = "New .api file or api.json")]
+        new: String,
+    },
+    #[structopt(
+        name = "mock",
+        about = "Serves the API examples over a local HTTP port"
+    )]
+    Mock {
+        #[structopt(
+            short = "m",
+            help = "Input models file",
+            default_value = "./models.model"
+        )]
+        models_file: String,
+        #[structopt(
+            short = "e",
+            help = "Examples json file",
+            default_value = "./example.json"
+        )]
+        examples: String,
+        #[structopt(short = "p", long = "port", help = "Port", default_value = "8080")]
+        port: u16,
+        #[structopt(help = ".api file or api.json")]
+        file: String,
+    },
+    #[structopt(
+        name = "verify",
+        about = "Checks a running service against the API contract"
+    )]
+    Verify {
+        #[structopt(
+            short = "m",
+            help = "Input models file",
+            default_value = "./models.model"
+        )]
+        models_file: String,
+        #[structopt(
+            short = "e",
+            help = "Examples json file",
+            default_value = "./example.json"
+        )]
+        examples: String,
+        #[structopt(long = "base-url", help = "Service url, http://localhost:8080")]
+        base_url: String,
+        #[structopt(long = "junit", help = "Writes a JUnit XML report")]
+        junit: Option<String>,
+        #[structopt(long = "json", help = "Writes a JSON report")]
+        json: Option<String>,
+        #[structopt(
+            long = "mock-examples",
+            help = "Asks `apish mock` for each example with the x-apish-example header"
+        )]
+        mock_examples: bool,
+        #[structopt(help = ".api file or api.json")]
+        file: String,
+    },
+    #[structopt(
+        name = "generate",
+        about = "Generates models and a client for a target language"
+    )]
+    Generate {
+        #[structopt(
+            short = "t",
+            long = "target",
+            help = "Target language: typescript, rust, go, proto or graphql"
+        )]
+        target: String,
+        #[structopt(
+            short = "m",
+            help = "Input models file",
+            default_value = "./models.model"
+        )]
+        models_file: String,
+        #[structopt(long = "out", help = "Output file, prints to stdout when missing")]
+        out: Option<String>,
+        #[structopt(help = ".api file or api.json")]
+        file: String,
+    },
+    #[structopt(
+        name = "import",
+        about = "Converts an OpenAPI 3 JSON document into .api, .model and examples files"
+    )]
+    Import {
+        #[structopt(long = "out", help = "Output directory", default_value = ".")]
+        out: String,
+        #[structopt(
+            long = "name",
+            help = "Name of the generated files",
+            default_value = "api"
+        )]
+        name: String,
+        #[structopt(long = "force", help = "Overwrites existing files")]
+        force: bool,
+        #[structopt(help = "OpenAPI 3 document in JSON")]
+        file: String,
+    },
+    #[structopt(name = "docs", about = "Generates a static HTML documentation site")]
+    Docs {
+        #[structopt(
+            short = "m",
+            help = "Input models file",
+            default_value = "./models.model"
+        )]
+        models_file: String,
+        #[structopt(
+            short = "e",
+            help = "Examples json file",
+            default_value = "./example.json"
+        )]
+        examples: String,
+        #[structopt(long = "out", help = "Output directory", default_value = "./site")]
+        out: String,
+        #[structopt(help = ".api file or api.json")]
+        file: String,
+    },
 }
 
-impl API {
-    fn new_from_api_definition(def: &APIDefinition, project: &Project) -> API {
-        API {
-            position: def.position,
-            get: APIEndpoint::new_from_api_configuration(&def.get, project),
-            post: APIEndpoint::new_from_api_configuration(&def.post, project),
-            put: APIEndpoint::new_from_api_configuration(&def.put, project),
-            delete: APIEndpoint::new_from_api_configuration(&def.delete, project),
-            patch: APIEndpoint::new_from_api_configuration(&def.patch, project),
-        }
-    }
-
-    fn new_project_spec(project: &Project) -> APISpec {
-        let mut api = HashMap::new();
-        for endpoint in &project.endpoints {
-            let api_path = endpoint.0.to_owned();
-            let api_def = API::new_from_api_definition(&endpoint.1, &project);
-            api.insert(api_path, api_def);
-        }
-        let mut models: Option<ProjectModel> = None;
-        if let Some(x) = &project.models {
-            models = Some(x.clone());
-        }
-        APISpec {
-            title: project.title.to_owned(),
-            version: project.version.to_owned(),
-            spec: api,
-            models,
-        }
+/// Compiles an `.api` file with its models and examples, or reloads an api.json which
+/// already contains them
+fn compile_input(
+    source: &str,
+    models_source: &str,
+    examples_source: &str,
+) -> Result<Compilation, String> {
+    let api = fs::read_to_string(source).map_err(|e| e.to_string())?;
+    if source.ends_with(".json") {
+        let project = Project::new_from_json(&api)?;
+        return Ok(compile_project(project));
     }
-}
-
-pub fn get_mime_types(list: &[String]) -> Vec<String> {
-    let mut mime_types = Vec::new();
-    let mime_map: HashMap<String, String> = [
-        ("json".to_owned(), "application/json".to_owned()),
-        ("xml".to_owned(), "application/xml".to_owned()),
-        ("text".to_owned(), "text/plain".to_owned()),
-        ("css".to_owned(), "text/css".to_owned()),
-        ("html".to_owned(), "text/html".to_owned()),
-        ("javascript".to_owned(), "application/javascript".to_owned()),
-        ("js".to_owned(), "application/javascript".to_owned()),
-        ("multipart".to_owned(), "multipart/form-data".to_owned()),
-        ("binary".to_owned(), "application/octet-stream".to_owned()),
-        ("mp4".to_owned(), "video/mp4".to_owned()),
-        // image formats
-        ("jpg".to_owned(), "image/jpeg".to_owned()),
-        ("png".to_owned(), "image/png".to_owned()),
-        ("svg".to_owned(), "image/svg+xml".to_owned()),
-    ]
-    .iter()
-    .cloned()
-    .collect();
-    for item in list {
-        let trimmed = item.trim().to_owned();
-        match mime_map.get(&trimmed) {
-            Some(mime_type) => {
-                mime_types.push(mime_type.to_owned());
-            }
-            None => {
-                mime_types.push(trimmed.clone());
-            }
+    let models = match fs::read_to_string(models_source) {
+        Ok(content) => Some(content),
+        Err(e) => {
+            println!("Models error: {}", e);
+            None
         }
-    }
-    mime_types
+    };
+    let sources = Sources {
+        api,
+        models,
+        examples: fs::read_to_string(examples_source).ok(),
+    };
+    compile(&sources)
 }
 
 fn produce_files(
@@ -291,32 +251,38 @@ fn produce_files(
     output: &str,
     spec_output: &str,
     open_api_output: &str,
+    markdown_output: &Option<String>,
 ) {
     let failure_icon = "🧟";
 
-    match Project::new_from_file(
-        source.to_string(),
-        models_source.to_string(),
-        examples_source.to_string(),
-    ) {
-        Ok(project) => {
+    match compile_input(source, models_source, examples_source) {
+        Ok(compilation) => {
+            for diagnostic in &compilation.diagnostics {
+                println!("⚠️ {}:{}", models_source, diagnostic);
+            }
             // producing api.json
             let file = File::create(output).unwrap();
-            serde_json::to_writer(file, &project).unwrap();
+            serde_json::to_writer(file, &compilation.project).unwrap();
 
             // producing api-spec.json (from project)
-            let api = API::new_project_spec(&project);
             let api_file = File::create(spec_output).unwrap();
-            serde_json::to_writer(api_file, &api).unwrap();
+            serde_json::to_writer(api_file, &compilation.api_spec).unwrap();
 
             // producing openapi.json
-            let open_api = open_api::OpenAPI::new_from_project_spec(&project);
             let open_api_file = File::create(open_api_output).unwrap();
-            serde_json::to_writer(open_api_file, &open_api).unwrap();
+            serde_json::to_writer(open_api_file, &compilation.open_api).unwrap();
             println!(
                 "✅ Generated {}, {}, and {}",
                 output, spec_output, open_api_output
             );
+
+            // producing the markdown docs
+            if let Some(markdown_output) = markdown_output {
+                match fs::write(markdown_output, markdown::render(&compilation.project)) {
+                    Ok(_) => println!("✅ Generated {}", markdown_output),
+                    Err(e) => println!("{} {}: {}", failure_icon, markdown_output, e),
+                }
+            }
         }
         Err(e) => {
             println!("{} {}", failure_icon, e);
@@ -331,6 +297,7 @@ fn watch(
     output: &str,
     spec_output: &str,
     open_api: &str,
+    markdown_output: &Option<String>,
 ) -> notify::Result<()> {
     let (tx, rx) = channel();
     let mut watcher: RecommendedWatcher = Watcher::new(tx, Duration::from_secs(1))?;
@@ -346,6 +313,7 @@ fn watch(
                         output,
                         spec_output,
                         open_api,
+                        markdown_output,
                     );
                 }
             }
@@ -354,30 +322,341 @@ fn watch(
     }
 }
 
+/// Formats every file, `.model` files as models and anything else as `.api`. Returns
+/// whether all of them were already formatted and valid
+fn format_files(files: &[String], check: bool, sort: bool) -> bool {
+    let mut clean = true;
+    for file in files {
+        let formatted = fs::read_to_string(file)
+            .map_err(|e| e.to_string())
+            .and_then(|content| {
+                let result = if file.ends_with(".model") {
+                    format::format_models(&content, sort)
+                } else {
+                    format::format_api(&content, sort)
+                };
+                result.map(|formatted| (content, formatted))
+            });
+        match formatted {
+            Ok((content, formatted)) => {
+                if content == formatted {
+                    continue;
+                }
+                clean = false;
+                if check {
+                    println!("❌ {} is not formatted", file);
+                } else {
+                    fs::write(file, formatted).unwrap();
+                    println!("✅ Formatted {}", file);
+                }
+            }
+            Err(e) => {
+                clean = false;
+                println!("🧟 {}: {}", file, e);
+            }
+        }
+    }
+    clean
+}
+
+/// Lints an `.api` file and prints its findings. Returns whether no error was found
+fn lint_file(file: &str, config_file: &Option<String>, json: bool) -> Result<bool, String> {
+    let config = match config_file {
+        Some(config_file) => {
+            let content = fs::read_to_string(config_file).map_err(|e| e.to_string())?;
+            lint::LintConfig::new_from_json(&content)?
+        }
+        None => lint::LintConfig::default(),
+    };
+    let source = fs::read_to_string(file).map_err(|e| e.to_string())?;
+    let project = Project::new_from_sources(&source, None, None)?;
+    let findings = lint::lint(&project, &config);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&findings).unwrap());
+    } else if findings.is_empty() {
+        println!("✅ No findings in {}", file);
+    } else {
+        for finding in &findings {
+            println!("{}:{}", file, finding);
+        }
+    }
+    Ok(!findings.iter().any(|f| f.severity == lint::Severity::Error))
+}
+
+/// Calls the service and writes the reports. Returns whether every check passed
+fn verify_service(
+    compilation: &Compilation,
+    base_url: &str,
+    junit: &Option<String>,
+    json: &Option<String>,
+    mock_examples: bool,
+) -> Result<bool, String> {
+    let report = verify::verify(&compilation.project, base_url, mock_examples)?;
+    println!("{}", report);
+    if let Some(junit) = junit {
+        fs::write(junit, report.to_junit()).map_err(|e| format!("{}: {}", junit, e))?;
+    }
+    if let Some(json) = json {
+        let content = serde_json::to_string_pretty(&report).unwrap();
+        fs::write(json, content).map_err(|e| format!("{}: {}", json, e))?;
+    }
+    Ok(report.failed() == 0)
+}
+
+/// Writes the sources imported from an OpenAPI document, returns the written paths.
+/// Existing files are only overwritten with `force`
+fn import_openapi(file: &str, out: &str, name: &str, force: bool) -> Result<Vec<String>, String> {
+    let content = fs::read_to_string(file).map_err(|e| format!("{}: {}", file, e))?;
+    let imported = import::import(&content).map_err(|e| format!("{}: {}", file, e))?;
+    for warning in &imported.warnings {
+        println!("⚠️ {}: {}", file, warning);
+    }
+    let out = Path::new(out);
+    fs::create_dir_all(out).map_err(|e| format!("{}: {}", out.display(), e))?;
+    let examples = serde_json::to_string_pretty(&imported.examples).unwrap_or_default();
+    let files: Vec<_> = vec![
+        (format!("{}.api", name), imported.api),
+        (format!("{}.model", name), imported.models),
+        (format!("{}.examples.json", name), examples),
+    ]
+    .into_iter()
+    .filter(|(_, content)| !content.is_empty() && content != "{}")
+    .map(|(file_name, content)| (out.join(file_name), content))
+    .collect();
+    let existing: Vec<String> = files
+        .iter()
+        .filter(|(path, _)| path.exists())
+        .map(|(path, _)| path.display().to_string())
+        .collect();
+    if !force && !existing.is_empty() {
+        return Err(format!(
+            "Refusing to overwrite {} without --force",
+            existing.join(", ")
+        ));
+    }
+    let mut written = vec![];
+    for (path, content) in files {
+        fs::write(&path, content).map_err(|e| format!("{}: {}", path.display(), e))?;
+        written.push(path.display().to_string());
+    }
+    Ok(written)
+}
+
+/// Writes the documentation site into `out`, creating the directory when needed
+fn write_site(compilation: &Compilation, out: &str) -> Result<(), String> {
+    let out = Path::new(out);
+    fs::create_dir_all(out).map_err(|e| format!("{}: {}", out.display(), e))?;
+    for (name, content) in site::render(&compilation.project) {
+        let path = out.join(name);
+        fs::write(&path, content).map_err(|e| format!("{}: {}", path.display(), e))?;
+    }
+    Ok(())
+}
+
+/// Loads an `.api` file with an optional models file, or an api.json
+fn load_project(source: &str, models_source: &Option<String>) -> Result<Project, String> {
+    let content = fs::read_to_string(source).map_err(|e| format!("{}: {}", source, e))?;
+    if source.ends_with(".json") {
+        return Project::new_from_json(&content);
+    }
+    let models = match models_source {
+        Some(models_source) => Some(
+            fs::read_to_string(models_source).map_err(|e| format!("{}: {}", models_source, e))?,
+        ),
+        None => None,
+    };
+    Project::new_from_sources(&content, models.as_deref(), None)
+}
+
 fn main() {
     let opt = Opt::from_args();
     let version = env!("CARGO_PKG_VERSION");
-    println!("APIsh 🙊 v{}\nReading API from {}", version, opt.input);
+    match &opt.cmd {
+        Some(Command::Fmt { check, sort, files }) => {
+            if !format_files(files, *check, *sort) {
+                process::exit(1);
+            }
+            return;
+        }
+        Some(Command::Lint { config, json, file }) => {
+            match lint_file(file, config, *json) {
+                Ok(true) => {}
+                Ok(false) => process::exit(1),
+                Err(e) => {
+                    println!("🧟 {}", e);
+                    process::exit(1);
+                }
+            }
+            return;
+        }
+        Some(Command::Diff {
+            old_models,
+            new_models,
+            json,
+            check,
+            old,
+            new,
+        }) => {
+            let projects = load_project(old, old_models)
+                .and_then(|old| load_project(new, new_models).map(|new| (old, new)));
+            match projects {
+                Ok((old, new)) => {
+                    let result = diff::diff(&old, &new);
+                    if *json {
+                        println!("{}", serde_json::to_string_pretty(&result).unwrap());
+                    } else {
+                        println!("{}", result);
+                    }
+                    if *check && !result.version_ok {
+                        process::exit(1);
+                    }
+                }
+                Err(e) => {
+                    println!("🧟 {}", e);
+                    process::exit(1);
+                }
+            }
+            return;
+        }
+        Some(Command::Mock {
+            models_file,
+            examples,
+            port,
+            file,
+        }) => {
+            let compilation = match compile_input(file, models_file, examples) {
+                Ok(compilation) => compilation,
+                Err(e) => {
+                    println!("🧟 {}", e);
+                    process::exit(1);
+                }
+            };
+            let address = format!("127.0.0.1:{}", port);
+            println!(
+                "APIsh 🙊 v{}\nMocking {} on http://{}",
+                version, file, address
+            );
+            if let Err(e) = mock::serve(compilation.project, &address) {
+                println!("🧟 {}", e);
+                process::exit(1);
+            }
+            return;
+        }
+        Some(Command::Verify {
+            models_file,
+            examples,
+            base_url,
+            junit,
+            json,
+            mock_examples,
+            file,
+        }) => {
+            let result = compile_input(file, models_file, examples).and_then(|compilation| {
+                verify_service(&compilation, base_url, junit, json, *mock_examples)
+            });
+            match result {
+                Ok(true) => println!("✅ {} matches {}", base_url, file),
+                Ok(false) => {
+                    println!("❌ {} does not match {}", base_url, file);
+                    process::exit(1);
+                }
+                Err(e) => {
+                    println!("🧟 {}", e);
+                    process::exit(1);
+                }
+            }
+            return;
+        }
+        Some(Command::Docs {
+            models_file,
+            examples,
+            out,
+            file,
+        }) => {
+            let result = compile_input(file, models_file, examples)
+                .and_then(|compilation| write_site(&compilation, out));
+            match result {
+                Ok(_) => println!("✅ Generated {}/index.html", out.trim_end_matches('/')),
+                Err(e) => {
+                    println!("🧟 {}", e);
+                    process::exit(1);
+                }
+            }
+            return;
+        }
+        Some(Command::Import {
+            out,
+            name,
+            force,
+            file,
+        }) => {
+            match import_openapi(file, out, name, *force) {
+                Ok(written) => println!("✅ Generated {}", written.join(", ")),
+                Err(e) => {
+                    println!("🧟 {}", e);
+                    process::exit(1);
+                }
+            }
+            return;
+        }
+        Some(Command::Generate {
+            target,
+            models_file,
+            out,
+            file,
+        }) => {
+            // models are optional, a target can generate the client alone
+            let models = Some(models_file.to_owned()).filter(|m| Path::new(m).exists());
+            let result =
+                load_project(file, &models).and_then(|project| codegen::generate(target, &project));
+            match (result, out) {
+                (Ok(source), Some(out)) => match fs::write(out, source) {
+                    Ok(_) => println!("✅ Generated {}", out),
+                    Err(e) => {
+                        println!("🧟 {}: {}", out, e);
+                        process::exit(1);
+                    }
+                },
+                (Ok(source), None) => print!("{}", source),
+                (Err(e), _) => {
+                    println!("🧟 {}", e);
+                    process::exit(1);
+                }
+            }
+            return;
+        }
+        None => {}
+    }
+    let input = match &opt.input {
+        Some(input) => input,
+        None => {
+            println!("Missing input file, use -f or a subcommand (see --help)");
+            process::exit(1);
+        }
+    };
+    println!("APIsh 🙊 v{}\nReading API from {}", version, input);
     if opt.watch {
-        println!("Listening for changes in {} file", opt.input);
+        println!("Listening for changes in {} file", input);
         if let Err(e) = watch(
-            opt.input.as_ref(),
+            input,
             opt.models_file.as_ref(),
             opt.examples.as_ref(),
             opt.output.as_ref(),
             opt.spec_output.as_ref(),
             opt.open_api.as_ref(),
+            &opt.markdown,
         ) {
             println!("Error listening: {:?}", e);
         }
     } else {
         produce_files(
-            opt.input.as_ref(),
+            input,
             opt.models_file.as_ref(),
             opt.examples.as_ref(),
             opt.output.as_ref(),
             opt.spec_output.as_ref(),
             opt.open_api.as_ref(),
+            &opt.markdown,
         );
     }
 }
@@ -0,0 +1,416 @@
+//! Markdown documentation of a `Project`: a table of contents grouped by tag, every
+//! operation with its arguments, status codes, models and examples, and a models reference
+
+use std::fmt::Write;
+
+use serde_json::Value;
+
+use crate::api_spec::get_mime_types;
+use crate::models::{Field, ProjectModel};
+use crate::project::{APIConfiguration, Project, ProjectArgument};
+
+/// Heading of operations without tags in the table of contents
+pub(crate) const UNTAGGED: &str = "Other";
+
+/// Lowercase id made of letters, digits and dashes
+pub fn anchor(text: &str) -> String {
+    let mut id = String::new();
+    for c in text.chars() {
+        if c.is_ascii_alphanumeric() {
+            id.push(c.to_ascii_lowercase());
+        } else if !id.is_empty() && !id.ends_with('-') {
+            id.push('-');
+        }
+    }
+    id.trim_end_matches('-').to_owned()
+}
+
+pub(crate) fn model_anchor(name: &str) -> String {
+    format!("model-{}", anchor(name))
+}
+
+/// Makes text safe for a table cell
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+pub(crate) fn yes(flag: bool) -> &'static str {
+    match flag {
+        true => "yes",
+        false => "",
+    }
+}
+
+/// Markup of the model tables shared by this document and the HTML site
+pub(crate) trait Markup {
+    fn code(text: &str) -> String;
+    /// Text of a table cell
+    fn text(text: &str) -> String;
+    /// Link to the models reference entry of `name`
+    fn model_link(name: &str) -> String;
+    fn table(out: &mut String, headers: &[&str], rows: &[Vec<String>]);
+}
+
+pub(crate) struct Markdown;
+
+impl Markup for Markdown {
+    fn code(text: &str) -> String {
+        format!("`{}`", text)
+    }
+
+    fn text(text: &str) -> String {
+        cell(text)
+    }
+
+    fn model_link(name: &str) -> String {
+        format!("[{}](#{})", name, model_anchor(name))
+    }
+
+    fn table(out: &mut String, headers: &[&str], rows: &[Vec<String>]) {
+        let dashes: Vec<String> = headers.iter().map(|h| "-".repeat(h.len() + 2)).collect();
+        let _ = writeln!(out, "| {} |\n|{}|", headers.join(" | "), dashes.join("|"));
+        for row in rows {
+            let _ = writeln!(out, "| {} |", row.join(" | "));
+        }
+        out.push('\n');
+    }
+}
+
+/// Tags in first use order, operations without tags go last under `Other`
+pub(crate) fn tags(project: &Project) -> Vec<String> {
+    let mut tags: Vec<String> = vec![];
+    let mut untagged = false;
+    for (_, _, config) in project.operations() {
+        untagged |= config.tags.is_empty();
+        for tag in &config.tags {
+            if !tags.contains(tag) {
+                tags.push(tag.to_owned());
+            }
+        }
+    }
+    if untagged {
+        tags.push(UNTAGGED.to_owned());
+    }
+    tags
+}
+
+pub(crate) fn has_tag(config: &APIConfiguration, tag: &str) -> bool {
+    config.tags.iter().any(|t| t == tag) || (tag == UNTAGGED && config.tags.is_empty())
+}
+
+fn is_model(models: Option<&ProjectModel>, data_type: &str) -> bool {
+    models.is_some_and(|models| {
+        models.entities.contains_key(data_type)
+            || models.enums.contains_key(data_type)
+            || models.unions.contains_key(data_type)
+    })
+}
+
+/// Model types link to their reference entry, other types are code
+pub(crate) fn type_name<M: Markup>(models: Option<&ProjectModel>, data_type: &str) -> String {
+    match is_model(models, data_type) {
+        true => M::model_link(data_type),
+        false => M::code(data_type),
+    }
+}
+
+pub(crate) fn field_type<M: Markup>(models: &ProjectModel, field: &Field) -> String {
+    let mut prefix = String::new();
+    if field.is_map {
+        prefix.push_str(&format!("map[{}]", field.map_key_type));
+    }
+    if field.is_array {
+        prefix.push_str("[]");
+    }
+    let mut name = match (is_model(Some(models), &field.data_type), prefix.is_empty()) {
+        (false, _) => M::code(&format!("{}{}", prefix, field.data_type)),
+        (true, true) => M::model_link(&field.data_type),
+        (true, false) => M::code(&prefix) + &M::model_link(&field.data_type),
+    };
+    if field.is_nullable {
+        name.push_str(" (nullable)");
+    }
+    name
+}
+
+fn arguments_table(out: &mut String, rows: &[(&str, &ProjectArgument)]) {
+    if rows.is_empty() {
+        return;
+    }
+    out.push_str("#### Parameters\n\n");
+    out.push_str("| Name | In | Type | Required | Default | Description |\n");
+    out.push_str("|------|----|------|----------|---------|-------------|\n");
+    for (location, argument) in rows {
+        let _ = writeln!(
+            out,
+            "| `{}` | {} | {} | {} | {} | {} |",
+            argument.name,
+            location,
+            argument.data_type.as_str(),
+            yes(argument.required || *location == "path"),
+            cell(&argument.default_value),
+            cell(&argument.description)
+        );
+    }
+    out.push('\n');
+}
+
+pub(crate) fn fields_table<M: Markup>(out: &mut String, models: &ProjectModel, name: &str) {
+    let entity = match models.entities.get(name) {
+        Some(entity) => entity,
+        None => return,
+    };
+    let rows: Vec<Vec<String>> = entity
+        .ordered_fields()
+        .into_iter()
+        .map(|field| {
+            vec![
+                M::code(&field.identifier),
+                field_type::<M>(models, field),
+                yes(field.is_required).to_owned(),
+                M::text(&field.default_value),
+                M::text(&field.description),
+            ]
+        })
+        .collect();
+    let headers = ["Field", "Type", "Required", "Default", "Description"];
+    M::table(out, &headers, &rows);
+}
+
+fn model_section(out: &mut String, project: &Project, title: &str, name: &str) {
+    if name.is_empty() {
+        return;
+    }
+    let _ = writeln!(
+        out,
+        "#### {} {}\n",
+        title,
+        type_name::<Markdown>(project.models.as_ref(), name)
+    );
+    if let Some(models) = &project.models {
+        fields_table::<Markdown>(out, models, name);
+    }
+}
+
+fn json_block(out: &mut String, value: &Value) {
+    match value {
+        Value::String(text) => {
+            let _ = writeln!(out, "```\n{}\n```\n", text);
+        }
+        _ => {
+            let json = serde_json::to_string_pretty(value).unwrap_or_default();
+            let _ = writeln!(out, "```json\n{}\n```\n", json);
+        }
+    }
+}
+
+fn operation_section(
+    out: &mut String,
+    project: &Project,
+    verb: &str,
+    path: &str,
+    config: &APIConfiguration,
+) {
+    let title = format!("{} {}", verb.to_uppercase(), path);
+    let _ = writeln!(out, "<a id=\"{}\"></a>\n", anchor(&title));
+    let _ = writeln!(out, "### {} `{}`\n", verb.to_uppercase(), path);
+    if !config.description.is_empty() {
+        let _ = writeln!(out, "{}\n", config.description);
+    }
+    let mut details = vec![];
+    if !config.operation.is_empty() {
+        details.push(format!("Operation: `{}`", config.operation));
+    }
+    if !config.tags.is_empty() {
+        details.push(format!("Tags: {}", config.tags.join(", ")));
+    }
+    let produces = get_mime_types(&config.produces);
+    if !produces.is_empty() {
+        details.push(format!("Produces: `{}`", produces.join("`, `")));
+    }
+    let consumes = get_mime_types(&config.consumes);
+    if !consumes.is_empty() {
+        details.push(format!("Consumes: `{}`", consumes.join("`, `")));
+    }
+    for detail in &details {
+        let _ = writeln!(out, "- {}", detail);
+    }
+    if !details.is_empty() {
+        out.push('\n');
+    }
+    if !config.use_cases.is_empty() {
+        out.push_str("Use cases:\n");
+        for use_case in &config.use_cases {
+            let _ = writeln!(out, "- {}", use_case);
+        }
+        out.push('\n');
+    }
+
+    let mut arguments: Vec<(&str, &ProjectArgument)> = vec![];
+    for param in project.get_path_params(&config.path_params) {
+        arguments.push(("path", param));
+    }
+    for query in project.get_query_strings(&config.query_string) {
+        arguments.push(("query", query));
+    }
+    for header in project.get_headers(&config.headers) {
+        arguments.push(("header", header));
+    }
+    arguments_table(out, &arguments);
+
+    let codes = project.get_status_codes(&config.status_codes);
+    if !codes.is_empty() {
+        out.push_str("#### Status codes\n\n");
+        out.push_str("| Code | Description | Retryable |\n");
+        out.push_str("|------|-------------|-----------|\n");
+        for code in &codes {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} |",
+                code.code,
+                cell(&code.description),
+                yes(code.is_retryable)
+            );
+        }
+        out.push('\n');
+    }
+    model_section(out, project, "Request", &config.request_model);
+    model_section(out, project, "Response", &config.response_model);
+
+    let examples = match project.examples.get(&config.example) {
+        Some(examples) if !examples.is_empty() => examples,
+        _ => return,
+    };
+    out.push_str("#### Examples\n\n");
+    for (index, example) in examples.iter().enumerate() {
+        let _ = write!(out, "**Example {}**", index + 1);
+        match &example.description {
+            Some(description) => {
+                let _ = writeln!(out, " {}\n", description);
+            }
+            None => out.push_str("\n\n"),
+        }
+        if let Some(request) = &example.request {
+            out.push_str("Request:\n\n");
+            json_block(out, request);
+        }
+        if let Some(response) = &example.response {
+            out.push_str("Response:\n\n");
+            json_block(out, response);
+        }
+    }
+}
+
+fn models_reference(out: &mut String, models: &ProjectModel) {
+    let mut names: Vec<&String> = models
+        .entities
+        .keys()
+        .chain(models.enums.keys())
+        .chain(models.unions.keys())
+        .collect();
+    if names.is_empty() {
+        return;
+    }
+    names.sort();
+    out.push_str("## Models\n\n");
+    for name in names {
+        let _ = writeln!(out, "<a id=\"{}\"></a>\n", model_anchor(name));
+        let _ = writeln!(out, "### {}\n", name);
+        if let Some(entity) = models.entities.get(name) {
+            if !entity.bases.is_empty() {
+                let bases: Vec<String> = entity
+                    .bases
+                    .iter()
+                    .map(|base| type_name::<Markdown>(Some(models), base))
+                    .collect();
+                let _ = writeln!(out, "Extends {}\n", bases.join(", "));
+            }
+            fields_table::<Markdown>(out, models, name);
+        }
+        if let Some(definition) = models.enums.get(name) {
+            out.push_str("| Value | Name | Description |\n");
+            out.push_str("|-------|------|-------------|\n");
+            for member in &definition.members {
+                let mut description = cell(&member.description);
+                if member.deprecated {
+                    description = format!("**Deprecated** {}", description).trim().to_owned();
+                }
+                let _ = writeln!(
+                    out,
+                    "| `{}` | {} | {} |",
+                    member.value, member.name, description
+                );
+            }
+            out.push('\n');
+        }
+        if let Some(union) = models.unions.get(name) {
+            let variants: Vec<String> = union
+                .variants
+                .iter()
+                .map(|variant| type_name::<Markdown>(Some(models), variant))
+                .collect();
+            let _ = write!(out, "One of {}", variants.join(", "));
+            if !union.discriminator.is_empty() {
+                let _ = write!(out, ", told apart by `{}`", union.discriminator);
+            }
+            out.push_str("\n\n");
+        }
+    }
+}
+
+/// Renders the whole project as a single markdown document
+pub fn render(project: &Project) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# {}\n", project.title);
+    if !project.version.is_empty() {
+        let _ = writeln!(out, "Version {}\n", project.version);
+    }
+    let operations = project.operations();
+    out.push_str("## Contents\n\n");
+    for tag in tags(project) {
+        let _ = writeln!(out, "- {}", tag);
+        for (verb, path, config) in &operations {
+            if !has_tag(config, &tag) {
+                continue;
+            }
+            let title = format!("{} {}", verb.to_uppercase(), path);
+            let _ = write!(out, "  - [{}](#{})", title, anchor(&title));
+            if !config.description.is_empty() {
+                let _ = write!(out, " {}", config.description);
+            }
+            out.push('\n');
+        }
+    }
+    if project.models.is_some() {
+        out.push_str("- [Models](#models)\n");
+    }
+    out.push_str("\n## Operations\n\n");
+    for (verb, path, config) in &operations {
+        operation_section(&mut out, project, verb, path, config);
+    }
+    if let Some(models) = &project.models {
+        models_reference(&mut out, models);
+    }
+    format!("{}\n", out.trim_end())
+}
+
+#[test]
+fn test_render_markdown() {
+    let api = "title: \"Pets\"\nversion: \"1.0.0\"\nheaders:\n  x-auth string required: \"Token | secret\"\nparams:\n  id number: \"Pet id\"\nstatus_codes:\n  424 retryable: \"Try later\"\n\napis:\n  /pets/{id}:\n    get: \"Finds a pet\"\n      operation: getPet\n      tags: pets\n      headers: x-auth\n      params: id\n      status_codes: 200 424\n      response: Pet\n      example: pet\n  /health:\n    get: \"Health\"\n";
+    let models = "struct Pet {\n name string required \"Pet name\"\n kind Kind\n tags? []string\n}\n\nenum Kind {\n DOG\n CAT \"Not a dog\"\n}\n";
+    let examples = "{\"pet\": [{\"description\": \"A dog\", \"response\": {\"name\": \"rex\"}}]}";
+    let project = Project::new_from_sources(api, Some(models), Some(examples)).unwrap();
+    let markdown = render(&project);
+    for expected in [
+        "- pets\n  - [GET /pets/{id}](#get-pets-id) Finds a pet\n- Other\n  - [GET /health](#get-health) Health\n- [Models](#models)\n",
+        "<a id=\"get-pets-id\"></a>\n\n### GET `/pets/{id}`\n\nFinds a pet\n\n- Operation: `getPet`\n- Tags: pets\n",
+        "| `id` | path | number | yes |  | Pet id |\n| `x-auth` | header | string | yes |  | Token \\| secret |\n",
+        "| 424 | Try later | yes |\n",
+        "#### Response [Pet](#model-pet)\n\n",
+        "| `name` | `string` | yes |  | Pet name |\n| `kind` | [Kind](#model-kind) |  |  |  |\n| `tags` | `[]string` |",
+        "**Example 1** A dog\n\nResponse:\n\n```json\n{\n  \"name\": \"rex\"\n}\n```\n",
+        "| `CAT` | CAT | Not a dog |\n",
+    ] {
+        assert!(markdown.contains(expected), "missing {}", expected);
+    }
+}
@@ -0,0 +1,364 @@
+//! Local HTTP server answering every endpoint of a `Project` with its examples, or with
+//! a sample of its `response:` model when there are none
+//!
+//! Requests choose the answer with two headers:
+//! - `x-apish-status`: a declared status code, or `retryable` for the first retryable one
+//! - `x-apish-example`: index of the example to return, the first one by default
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::thread;
+
+use serde_json::{json, Value};
+
+use crate::api_spec::get_mime_types;
+use crate::project::{status_description, APIConfiguration, APIDefinition, Project, StatusCode};
+
+pub const STATUS_HEADER: &str = "x-apish-status";
+pub const EXAMPLE_HEADER: &str = "x-apish-example";
+
+#[derive(Debug)]
+pub struct MockRequest {
+    pub method: String,
+    /// path without the query string
+    pub path: String,
+    pub query: HashMap<String, String>,
+    /// lowercase header names
+    pub headers: HashMap<String, String>,
+}
+
+#[derive(Debug)]
+pub struct MockResponse {
+    pub status: u16,
+    pub content_type: String,
+    pub body: String,
+}
+
+impl MockRequest {
+    /// Splits the request target into path and query string
+    pub fn new(method: &str, target: &str, headers: HashMap<String, String>) -> MockRequest {
+        let mut parts = target.splitn(2, '?');
+        let path = parts.next().unwrap_or("/").to_owned();
+        let mut query = HashMap::new();
+        if let Some(query_string) = parts.next() {
+            for pair in query_string.split('&').filter(|p| !p.is_empty()) {
+                let mut key_value = pair.splitn(2, '=');
+                let key = key_value.next().unwrap_or("").to_owned();
+                query.insert(key, key_value.next().unwrap_or("").to_owned());
+            }
+        }
+        MockRequest {
+            method: method.to_uppercase(),
+            path,
+            query,
+            headers: headers
+                .into_iter()
+                .map(|(k, v)| (k.to_lowercase(), v))
+                .collect(),
+        }
+    }
+}
+
+impl MockResponse {
+    fn json(status: u16, body: Value) -> MockResponse {
+        MockResponse {
+            status,
+            content_type: "application/json".to_owned(),
+            body: body.to_string(),
+        }
+    }
+
+    fn error(status: u16, message: String) -> MockResponse {
+        MockResponse::json(status, json!({ "error": message }))
+    }
+}
+
+/// Number of literal segments matched, `None` when the path does not match the template
+fn match_path(template: &str, path: &str) -> Option<usize> {
+    let template_segments: Vec<&str> = template.trim_end_matches('/').split('/').collect();
+    let path_segments: Vec<&str> = path.trim_end_matches('/').split('/').collect();
+    if template_segments.len() != path_segments.len() {
+        return None;
+    }
+    let mut literals = 0;
+    for (expected, actual) in template_segments.iter().zip(path_segments.iter()) {
+        if expected.starts_with('{') && expected.ends_with('}') {
+            if actual.is_empty() {
+                return None;
+            }
+        } else if expected == actual {
+            literals += 1;
+        } else {
+            return None;
+        }
+    }
+    Some(literals)
+}
+
+/// Endpoint matching the path, literal segments win over path params
+fn find_endpoint<'a>(project: &'a Project, path: &str) -> Option<(&'a String, &'a APIDefinition)> {
+    project
+        .endpoints
+        .iter()
+        .filter(|(template, _)| !template.is_empty())
+        .filter_map(|(template, definition)| {
+            match_path(template, path).map(|literals| (literals, template, definition))
+        })
+        .max_by(|a, b| a.0.cmp(&b.0).then_with(|| b.1.cmp(a.1)))
+        .map(|(_, template, definition)| (template, definition))
+}
+
+fn missing_arguments(
+    project: &Project,
+    config: &APIConfiguration,
+    request: &MockRequest,
+) -> Vec<String> {
+    let mut missing = vec![];
+    for header in project.get_headers(&config.headers) {
+        if header.required && !request.headers.contains_key(&header.name.to_lowercase()) {
+            missing.push(format!("header `{}`", header.name));
+        }
+    }
+    for query in project.get_query_strings(&config.query_string) {
+        if query.required && !request.query.contains_key(&query.name) {
+            missing.push(format!("query param `{}`", query.name));
+        }
+    }
+    missing
+}
+
+/// Status requested with `x-apish-status`, the first 2xx declared otherwise
+fn select_status<'a>(
+    codes: &'a [StatusCode],
+    request: &MockRequest,
+) -> Result<Option<&'a StatusCode>, String> {
+    match request.headers.get(STATUS_HEADER) {
+        Some(requested) if requested == "retryable" => {
+            match codes.iter().find(|c| c.is_retryable) {
+                Some(code) => Ok(Some(code)),
+                None => Err("No retryable status code is declared".to_owned()),
+            }
+        }
+        Some(requested) => match codes.iter().find(|c| &c.code == requested) {
+            Some(code) => Ok(Some(code)),
+            None => Err(format!("Status code `{}` is not declared", requested)),
+        },
+        None => Ok(codes.iter().find(|c| c.code.starts_with('2'))),
+    }
+}
+
+fn success_body(project: &Project, config: &APIConfiguration, request: &MockRequest) -> Value {
+    if let Some(examples) = project.examples.get(&config.example) {
+        let index = request
+            .headers
+            .get(EXAMPLE_HEADER)
+            .and_then(|i| i.parse::<usize>().ok())
+            .unwrap_or(0);
+        if let Some(response) = examples.get(index).and_then(|e| e.response.clone()) {
+            return response;
+        }
+    }
+    match &project.models {
+        Some(models) if !config.response_model.is_empty() => {
+            models.sample_value(&config.response_model)
+        }
+        _ => Value::Null,
+    }
+}
+
+/// Answers a request the way the API declares it
+pub fn respond(project: &Project, request: &MockRequest) -> MockResponse {
+    let (template, definition) = match find_endpoint(project, &request.path) {
+        Some(endpoint) => endpoint,
+        None => {
+            let message = format!("No endpoint matches `{}`", request.path);
+            return MockResponse::error(404, message);
+        }
+    };
+    let method = request.method.to_lowercase();
+    let config = match definition
+        .operations()
+        .into_iter()
+        .find(|(v, _)| *v == method)
+    {
+        Some((_, config)) => config,
+        None => {
+            let message = format!("`{}` does not declare {}", template, request.method);
+            return MockResponse::error(405, message);
+        }
+    };
+    let missing = missing_arguments(project, config, request);
+    if !missing.is_empty() {
+        let message = format!("Missing required {}", missing.join(", "));
+        return MockResponse::error(400, message);
+    }
+    let codes = project.get_status_codes(&config.status_codes);
+    let status = match select_status(&codes, request) {
+        Ok(status) => status,
+        Err(message) => return MockResponse::error(400, message),
+    };
+    let code = match status {
+        Some(status) => status.code.parse().unwrap_or(200),
+        None => 200,
+    };
+    if code >= 300 {
+        let status = status.unwrap();
+        let body = json!({
+            "code": code,
+            "description": status.description,
+            "retryable": status.is_retryable,
+        });
+        return MockResponse::json(code, body);
+    }
+    let mut response = MockResponse::json(code, success_body(project, config, request));
+    if let Some(mime_type) = get_mime_types(&config.produces).first() {
+        response.content_type = mime_type.to_owned();
+    }
+    if let Ok(Value::String(text)) = serde_json::from_str::<Value>(&response.body) {
+        if response.content_type != "application/json" {
+            response.body = text;
+        }
+    }
+    response
+}
+
+/// Largest request body the mock reads
+const MAX_BODY_LENGTH: usize = 1024 * 1024;
+
+fn content_length(headers: &HashMap<String, String>) -> usize {
+    headers
+        .get("content-length")
+        .and_then(|l| l.parse().ok())
+        .unwrap_or(0)
+}
+
+fn read_request(stream: &mut TcpStream) -> Option<MockRequest> {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).ok()?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next()?.to_owned();
+    let target = parts.next()?.to_owned();
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).ok()? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some(index) = line.find(':') {
+            let (name, value) = line.split_at(index);
+            headers.insert(name.trim().to_lowercase(), value[1..].trim().to_owned());
+        }
+    }
+    let length = content_length(&headers);
+    if length <= MAX_BODY_LENGTH {
+        // the body is read to drain the connection, larger ones are answered with 413
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).ok()?;
+    }
+    Some(MockRequest::new(&method, &target, headers))
+}
+
+fn handle(project: &Project, mut stream: TcpStream) {
+    let request = match read_request(&mut stream) {
+        Some(request) => request,
+        None => return,
+    };
+    let response = if content_length(&request.headers) > MAX_BODY_LENGTH {
+        let message = format!("Request bodies are limited to {} bytes", MAX_BODY_LENGTH);
+        MockResponse::error(413, message)
+    } else if request.method == "OPTIONS" {
+        MockResponse {
+            status: 204,
+            content_type: "text/plain".to_owned(),
+            body: String::new(),
+        }
+    } else {
+        respond(project, &request)
+    };
+    println!("{} {} -> {}", request.method, request.path, response.status);
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nAccess-Control-Allow-Origin: *\r\nAccess-Control-Allow-Headers: *\r\nAccess-Control-Allow-Methods: GET, POST, PUT, DELETE, PATCH, OPTIONS\r\nConnection: close\r\n\r\n",
+        response.status,
+        status_description(&response.status.to_string()).unwrap_or_default(),
+        response.content_type,
+        response.body.len()
+    );
+    let _ = stream.write_all(head.as_bytes());
+    let _ = stream.write_all(response.body.as_bytes());
+}
+
+/// Serves the project until the process stops, one thread per connection
+pub fn serve(project: Project, address: &str) -> std::io::Result<()> {
+    let listener = TcpListener::bind(address)?;
+    let project = Arc::new(project);
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let project = Arc::clone(&project);
+                thread::spawn(move || handle(&project, stream));
+            }
+            Err(e) => println!("{:?}", e),
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_mock_responses() {
+    let api = "title: \"Pets\"\nheaders:\n  x-auth string required: \"Token\"\nquery:\n  limit number: \"Page size\"\nstatus_codes:\n  424 retryable: \"Try later\"\n\napis:\n  /pets/{id}:\n    get: \"Pet\"\n      headers: x-auth\n      status_codes: 200 404 424\n      response: Pet\n  /pets/mine:\n    get: \"Own pets\"\n      example: mine\n";
+    let models = "struct Pet {\n name string `example: fido`\n age int\n owner? Owner\n}\n\nstruct Owner {\n pets []Pet\n}\n";
+    let examples = "{\"mine\": [{\"response\": [{\"name\": \"rex\"}]}]}";
+    let project = Project::new_from_sources(api, Some(models), Some(examples)).unwrap();
+    let headers = |pairs: &[(&str, &str)]| -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    };
+
+    let missing = respond(&project, &MockRequest::new("GET", "/pets/7", headers(&[])));
+    assert_eq!(missing.status, 400);
+    assert!(missing.body.contains("x-auth"));
+
+    let auth = headers(&[("X-Auth", "t")]);
+    let pet = respond(&project, &MockRequest::new("GET", "/pets/7?limit=1", auth));
+    assert_eq!(pet.status, 200);
+    let body: Value = serde_json::from_str(&pet.body).unwrap();
+    assert_eq!(
+        body,
+        json!({"name": "fido", "age": 0, "owner": {"pets": []}})
+    );
+    let models = project.models.as_ref().unwrap();
+    assert_eq!(models.validate_value("Pet", &body), Vec::<String>::new());
+
+    let retry = headers(&[("x-auth", "t"), (STATUS_HEADER, "retryable")]);
+    let failure = respond(&project, &MockRequest::new("GET", "/pets/7", retry));
+    assert_eq!(failure.status, 424);
+    assert!(failure.body.contains("\"retryable\":true"));
+
+    let mine = respond(
+        &project,
+        &MockRequest::new("GET", "/pets/mine", headers(&[])),
+    );
+    assert_eq!(mine.body, "[{\"name\":\"rex\"}]");
+    let post = respond(
+        &project,
+        &MockRequest::new("POST", "/pets/mine", headers(&[])),
+    );
+    assert_eq!(post.status, 405);
+    let unknown = respond(&project, &MockRequest::new("GET", "/owners", headers(&[])));
+    assert_eq!(unknown.status, 404);
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    client
+        .write_all(b"POST /pets/mine HTTP/1.1\r\nContent-Length: 2000000\r\n\r\n")
+        .unwrap();
+    handle(&project, listener.accept().unwrap().0);
+    let mut head = String::new();
+    BufReader::new(client).read_line(&mut head).unwrap();
+    assert_eq!(head, "HTTP/1.1 413 Payload Too Large\r\n");
+}
@@ -1,52 +1,155 @@
-use std::collections::HashMap;
-use std::hash::Hash;
+use std::collections::{HashMap, HashSet};
 
 use pest::iterators::Pair;
 use pest::Parser;
 
-use serde::Serialize;
 use pest_derive::Parser;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 #[derive(Parser)]
 #[grammar = "grammar_models.pest"]
-struct ModelsParser;
+pub(crate) struct ModelsParser;
 
-#[derive(Debug, Serialize)]
+/// Field types every model file understands without declaring them
+pub const PRIMITIVE_TYPES: [&str; 11] = [
+    "string", "int", "integer", "long", "float", "double", "number", "bool", "boolean", "date",
+    "datetime",
+];
+
+/// Types allowed inside `map[...]`, JSON object keys are strings or integers
+pub const MAP_KEY_TYPES: [&str; 3] = ["string", "int", "long"];
+
+/// What a field type resolves to once every declaration is known
+#[derive(Debug, Serialize, Deserialize)]
+pub enum TypeKind {
+    Primitive,
+    Enum,
+    Struct,
+    Union,
+    Unknown,
+}
+
+/// Problem found in a models file that does not prevent using it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Field {
     pub identifier: String,
     pub data_type: String,
     pub description: String,
-    /// use to represent arrays or maps
+    /// use to represent arrays, for maps it applies to the values
     pub is_array: bool,
+    /// declared as `map[keyType]valueType`, `data_type` holds the value type
+    pub is_map: bool,
+    /// key type of a map field, empty for any other field
+    pub map_key_type: String,
+    /// declared with `?` after the field name (`name? string`)
+    pub is_optional: bool,
+    /// declared with `?` after the field type (`name string?`)
+    pub is_nullable: bool,
+    /// set by the `required` marker, optional fields cannot be required
+    pub is_required: bool,
+    /// value declared after `=`, empty when the field has no default
+    pub default_value: String,
     pub example: String,
     pub markers: Vec<String>,
     pub tags: HashMap<String, String>,
     /// used only for enums
     pub allowed_values: Vec<String>,
+    /// set by the type resolution pass
+    pub type_kind: TypeKind,
+    pub constraints: Constraints,
+    /// struct declaring the field when it comes from a base, empty for own fields
+    pub inherited_from: String,
+    /// declaration order within the entity, inherited fields come first
+    pub position: usize,
+    /// position of the field name in the models file
+    #[serde(skip)]
+    pub line: usize,
+    #[serde(skip)]
+    pub column: usize,
 }
 
-#[derive(Debug, Serialize)]
+/// Validation keywords shared by model fields and API arguments, named as in JSON Schema
+#[derive(Debug, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Constraints {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minimum: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maximum: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_length: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_items: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_items: Option<u64>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub unique_items: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Entity {
     pub name: String,
+    /// structs this one `extends` or embeds with `...`, in declaration order
+    pub bases: Vec<String>,
+    /// own fields plus the fields of every base
     pub fields: HashMap<String, Field>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 struct Optionals {
     markers: Vec<String>,
     tags: HashMap<String, String>,
+    constraints: Constraints,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Enum {
     pub name: String,
+    /// wire values in declaration order
     pub values: Vec<String>,
+    /// `string` or `int`, declared after the enum name
+    pub value_type: String,
+    pub members: Vec<EnumMember>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EnumMember {
+    pub name: String,
+    /// declared with `=`, defaults to the name (string enums) or the next integer (int enums)
+    pub value: String,
+    pub description: String,
+    pub deprecated: bool,
+}
+
+/// One of several structs, `union Payment = Card | BankTransfer discriminator type`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Union {
+    pub name: String,
+    pub variants: Vec<String>,
+    /// field present in every variant telling them apart, empty when not declared
+    pub discriminator: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ProjectModel {
     pub entities: HashMap<String, Entity>,
     pub enums: HashMap<String, Enum>,
+    pub unions: HashMap<String, Union>,
+    /// unknown types and required reference cycles
+    #[serde(skip)]
+    pub diagnostics: Vec<Diagnostic>,
 }
 
 impl Clone for ProjectModel {
@@ -54,6 +157,270 @@ impl Clone for ProjectModel {
         ProjectModel {
             entities: self.entities.clone(),
             enums: self.enums.clone(),
+            unions: self.unions.clone(),
+            diagnostics: self.diagnostics.clone(),
+        }
+    }
+}
+
+impl Clone for Diagnostic {
+    fn clone(&self) -> Self {
+        Diagnostic {
+            line: self.line,
+            column: self.column,
+            message: self.message.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{} {}", self.line, self.column, self.message)
+    }
+}
+
+impl Clone for TypeKind {
+    fn clone(&self) -> Self {
+        match self {
+            TypeKind::Primitive => TypeKind::Primitive,
+            TypeKind::Enum => TypeKind::Enum,
+            TypeKind::Struct => TypeKind::Struct,
+            TypeKind::Union => TypeKind::Union,
+            TypeKind::Unknown => TypeKind::Unknown,
+        }
+    }
+}
+
+impl PartialEq for TypeKind {
+    fn eq(&self, other: &Self) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+}
+
+impl ProjectModel {
+    /// Classifies a field type against the primitives and the declared models
+    pub fn type_kind(&self, data_type: &str) -> TypeKind {
+        if PRIMITIVE_TYPES.contains(&data_type) {
+            TypeKind::Primitive
+        } else if self.enums.contains_key(data_type) {
+            TypeKind::Enum
+        } else if self.entities.contains_key(data_type) {
+            TypeKind::Struct
+        } else if self.unions.contains_key(data_type) {
+            TypeKind::Union
+        } else {
+            TypeKind::Unknown
+        }
+    }
+
+    /// The `= value` of a field as JSON typed after the field, enum defaults give the wire
+    /// value of the member they name. `None` when there is none or it names no member
+    pub fn default_value(&self, field: &Field) -> Option<Value> {
+        if field.default_value.is_empty() {
+            return None;
+        }
+        match self.enums.get(&field.data_type) {
+            Some(definition) => definition
+                .member(&field.default_value)
+                .map(|member| typed_value(&definition.value_type, &member.value)),
+            None => Some(typed_value(&field.data_type, &field.default_value)),
+        }
+    }
+
+    /// Sample JSON of a type built from the field examples and defaults, placeholders are
+    /// used for anything else. Recursive fields are left out when optional, and end in
+    /// `[]`, `{}` or `null` otherwise
+    pub fn sample_value(&self, data_type: &str) -> Value {
+        self.sample(data_type, &mut vec![])
+    }
+
+    /// Whether sampling the type would reach a struct being sampled
+    fn recursive(&self, data_type: &str, visiting: &[String]) -> bool {
+        if let Some(union) = self.unions.get(data_type) {
+            return union
+                .variants
+                .first()
+                .is_some_and(|variant| self.recursive(variant, visiting));
+        }
+        visiting.iter().any(|name| name == data_type)
+    }
+
+    fn sample(&self, data_type: &str, visiting: &mut Vec<String>) -> Value {
+        if let Some(definition) = self.enums.get(data_type) {
+            return match definition.values.first() {
+                Some(value) if definition.value_type == "int" => typed_value("int", value),
+                Some(value) => Value::from(value.to_owned()),
+                None => Value::Null,
+            };
+        }
+        if let Some(union) = self.unions.get(data_type) {
+            return match union.variants.first() {
+                Some(variant) => self.sample(variant, visiting),
+                None => Value::Null,
+            };
+        }
+        if let Some(entity) = self.entities.get(data_type) {
+            if visiting.iter().any(|name| name == data_type) {
+                return Value::Null;
+            }
+            visiting.push(data_type.to_owned());
+            let mut object = serde_json::Map::new();
+            for field in entity.ordered_fields() {
+                let value = match self.recursive(&field.data_type, visiting) {
+                    false => self.field_sample(field, visiting),
+                    true if field.is_optional => continue,
+                    true if field.is_map => Value::Object(serde_json::Map::new()),
+                    true if field.is_array => Value::Array(vec![]),
+                    true => Value::Null,
+                };
+                object.insert(field.identifier.to_owned(), value);
+            }
+            visiting.pop();
+            return Value::Object(object);
+        }
+        match data_type {
+            "int" | "integer" | "long" => Value::from(0),
+            "float" | "double" | "number" => Value::from(0.0),
+            "bool" | "boolean" => Value::from(true),
+            "date" => Value::from("2022-01-01"),
+            "datetime" => Value::from("2022-01-01T00:00:00Z"),
+            _ => Value::from("string"),
+        }
+    }
+
+    fn field_sample(&self, field: &Field, visiting: &mut Vec<String>) -> Value {
+        if let Some(example) = field.example_value() {
+            return example;
+        }
+        let item = if !field.default_value.is_empty()
+            && !field.is_array
+            && !field.is_map
+            && field.type_kind == TypeKind::Primitive
+        {
+            typed_value(&field.data_type, &field.default_value)
+        } else {
+            self.sample(&field.data_type, visiting)
+        };
+        if field.is_array {
+            let array = Value::Array(vec![item]);
+            if field.is_map {
+                return serde_json::json!({ "key": array });
+            }
+            return array;
+        }
+        if field.is_map {
+            return serde_json::json!({ "key": item });
+        }
+        item
+    }
+
+    /// Differences between a JSON value and a type, each one prefixed by its JSON path.
+    /// Unknown types accept any value
+    pub fn validate_value(&self, data_type: &str, value: &Value) -> Vec<String> {
+        let mut errors = vec![];
+        self.validate(data_type, value, "$", &mut errors);
+        errors
+    }
+
+    fn validate(&self, data_type: &str, value: &Value, path: &str, errors: &mut Vec<String>) {
+        if let Some(definition) = self.enums.get(data_type) {
+            let wire_value = match value {
+                Value::String(text) if definition.value_type != "int" => Some(text.to_owned()),
+                Value::Number(number) if definition.value_type == "int" => Some(number.to_string()),
+                _ => None,
+            };
+            if !wire_value.is_some_and(|v| definition.values.contains(&v)) {
+                errors.push(format!("{}: {} is not a {} value", path, value, data_type));
+            }
+            return;
+        }
+        if let Some(union) = self.unions.get(data_type) {
+            let matches = union.variants.iter().any(|variant| {
+                let mut variant_errors = vec![];
+                self.validate(variant, value, path, &mut variant_errors);
+                variant_errors.is_empty()
+            });
+            if !matches {
+                errors.push(format!(
+                    "{}: does not match any {} variant",
+                    path, data_type
+                ));
+            }
+            return;
+        }
+        if let Some(entity) = self.entities.get(data_type) {
+            let object = match value {
+                Value::Object(object) => object,
+                _ => {
+                    errors.push(format!("{}: expected a {} object", path, data_type));
+                    return;
+                }
+            };
+            for field in entity.ordered_fields() {
+                let field_path = format!("{}.{}", path, field.identifier);
+                match object.get(&field.identifier) {
+                    None if field.is_required => {
+                        errors.push(format!("{}: required field is missing", field_path))
+                    }
+                    None => {}
+                    Some(Value::Null) if field.is_nullable => {}
+                    Some(field_value) => {
+                        self.validate_field(field, field_value, &field_path, errors)
+                    }
+                }
+            }
+            return;
+        }
+        let valid = match data_type {
+            "int" | "integer" | "long" => value.is_i64() || value.is_u64(),
+            "float" | "double" | "number" => value.is_number(),
+            "bool" | "boolean" => value.is_boolean(),
+            "string" | "date" | "datetime" => value.is_string(),
+            _ => true,
+        };
+        if !valid {
+            errors.push(format!("{}: {} is not a {}", path, value, data_type));
+        }
+    }
+
+    fn validate_field(&self, field: &Field, value: &Value, path: &str, errors: &mut Vec<String>) {
+        let validate_item = |item: &Value, item_path: &str, errors: &mut Vec<String>| {
+            if !field.is_array {
+                self.validate(&field.data_type, item, item_path, errors);
+                return field.constraints.check_value(item, item_path, errors);
+            }
+            match item {
+                Value::Array(items) => {
+                    field.constraints.check_items(items, item_path, errors);
+                    for (index, item) in items.iter().enumerate() {
+                        let path = format!("{}[{}]", item_path, index);
+                        self.validate(&field.data_type, item, &path, errors);
+                        field.constraints.check_value(item, &path, errors);
+                    }
+                }
+                _ => errors.push(format!("{}: expected an array", item_path)),
+            }
+        };
+        if !field.is_map {
+            return validate_item(value, path, errors);
+        }
+        match value {
+            Value::Object(map) => {
+                for (key, item) in map {
+                    validate_item(item, &format!("{}.{}", path, key), errors);
+                }
+            }
+            _ => errors.push(format!("{}: expected a map", path)),
+        }
+    }
+}
+
+impl Clone for Union {
+    fn clone(&self) -> Self {
+        Union {
+            name: self.name.to_string(),
+            variants: self.variants.clone(),
+            discriminator: self.discriminator.to_string(),
         }
     }
 }
@@ -65,11 +432,157 @@ impl Clone for Field {
             data_type: self.data_type.to_string(),
             description: self.description.to_string(),
             is_array: self.is_array,
+            is_map: self.is_map,
+            map_key_type: self.map_key_type.to_string(),
+            is_optional: self.is_optional,
+            is_nullable: self.is_nullable,
+            is_required: self.is_required,
+            default_value: self.default_value.to_string(),
             example: self.example.to_string(),
             markers: self.markers.clone(),
             tags: self.tags.clone(),
             allowed_values: self.allowed_values.clone(),
+            type_kind: self.type_kind.clone(),
+            constraints: self.constraints.clone(),
+            inherited_from: self.inherited_from.to_string(),
+            position: self.position,
+            line: self.line,
+            column: self.column,
+        }
+    }
+}
+
+impl Clone for Constraints {
+    fn clone(&self) -> Self {
+        Constraints {
+            minimum: self.minimum,
+            maximum: self.maximum,
+            min_length: self.min_length,
+            max_length: self.max_length,
+            pattern: self.pattern.clone(),
+            min_items: self.min_items,
+            max_items: self.max_items,
+            unique_items: self.unique_items,
+        }
+    }
+}
+
+impl Constraints {
+    /// Sets a constraint by its DSL keyword (`min`, `maxLength`, ...)
+    pub fn set(&mut self, keyword: &str, value: &str) -> Result<(), String> {
+        fn parsed<T: std::str::FromStr>(keyword: &str, value: &str) -> Result<Option<T>, String> {
+            match value.parse() {
+                Ok(parsed) => Ok(Some(parsed)),
+                Err(_) => Err(format!("Invalid value `{}` for `{}`", value, keyword)),
+            }
+        }
+        match keyword {
+            "min" => self.minimum = parsed(keyword, value)?,
+            "max" => self.maximum = parsed(keyword, value)?,
+            "minLength" => self.min_length = parsed(keyword, value)?,
+            "maxLength" => self.max_length = parsed(keyword, value)?,
+            "pattern" => self.pattern = Some(value.to_owned()),
+            "minItems" => self.min_items = parsed(keyword, value)?,
+            "maxItems" => self.max_items = parsed(keyword, value)?,
+            "uniqueItems" => self.unique_items = true,
+            _ => return Err(format!("Unknown constraint `{}`", keyword)),
+        }
+        Ok(())
+    }
+
+    /// Bounds and lengths of a single value, `pattern` is not checked
+    fn check_value(&self, value: &Value, path: &str, errors: &mut Vec<String>) {
+        if let Some(number) = value.as_f64() {
+            if let Some(minimum) = self.minimum.filter(|minimum| number < *minimum) {
+                errors.push(format!("{}: {} is below min({})", path, value, minimum));
+            }
+            if let Some(maximum) = self.maximum.filter(|maximum| number > *maximum) {
+                errors.push(format!("{}: {} is above max({})", path, value, maximum));
+            }
+        }
+        if let Some(text) = value.as_str() {
+            let length = text.chars().count() as u64;
+            if let Some(min_length) = self.min_length.filter(|min_length| length < *min_length) {
+                errors.push(format!(
+                    "{}: {} is shorter than minLength({})",
+                    path, value, min_length
+                ));
+            }
+            if let Some(max_length) = self.max_length.filter(|max_length| length > *max_length) {
+                errors.push(format!(
+                    "{}: {} is longer than maxLength({})",
+                    path, value, max_length
+                ));
+            }
+        }
+    }
+
+    fn check_items(&self, items: &[Value], path: &str, errors: &mut Vec<String>) {
+        let count = items.len() as u64;
+        if let Some(min_items) = self.min_items.filter(|min_items| count < *min_items) {
+            errors.push(format!(
+                "{}: {} items, minItems({})",
+                path, count, min_items
+            ));
+        }
+        if let Some(max_items) = self.max_items.filter(|max_items| count > *max_items) {
+            errors.push(format!(
+                "{}: {} items, maxItems({})",
+                path, count, max_items
+            ));
+        }
+        let repeated = items
+            .iter()
+            .enumerate()
+            .any(|(index, item)| items[..index].contains(item));
+        if self.unique_items && repeated {
+            errors.push(format!("{}: items are not unique", path));
+        }
+    }
+}
+
+impl Entity {
+    /// Fields in declaration order
+    pub fn ordered_fields(&self) -> Vec<&Field> {
+        let mut fields: Vec<&Field> = self.fields.values().collect();
+        fields.sort_by_key(|field| field.position);
+        fields
+    }
+}
+
+/// Converts a literal from a models file into a JSON value of the given type,
+/// anything that is not a valid number or bool stays a string
+pub fn typed_value(data_type: &str, raw: &str) -> Value {
+    let parsed = match data_type {
+        "int" | "integer" | "long" => raw.parse::<i64>().ok().map(Value::from),
+        "float" | "double" | "number" => raw.parse::<f64>().ok().map(Value::from),
+        "bool" | "boolean" => raw.parse::<bool>().ok().map(Value::from),
+        _ => None,
+    };
+    parsed.unwrap_or_else(|| Value::from(normalize_parsed(raw)))
+}
+
+impl Field {
+    /// The `example` tag as JSON typed after the field. Arrays and maps accept JSON,
+    /// arrays also take comma separated items (`example: 1, 2`)
+    pub fn example_value(&self) -> Option<Value> {
+        if self.example.is_empty() {
+            return None;
+        }
+        if self.is_array || self.is_map || self.type_kind == TypeKind::Struct {
+            if let Ok(value) = serde_json::from_str::<Value>(&self.example) {
+                return Some(value);
+            }
+        }
+        if self.is_array && !self.is_map {
+            let items = self
+                .example
+                .split(',')
+                .map(|item| typed_value(&self.data_type, item.trim()))
+                .collect();
+            return Some(Value::Array(items));
         }
+        Some(typed_value(&self.data_type, &self.example))
     }
 }
 
@@ -77,69 +590,206 @@ impl Clone for Entity {
     fn clone(&self) -> Self {
         Entity {
             name: self.name.to_string(),
+            bases: self.bases.clone(),
             fields: self.fields.clone(),
         }
     }
 }
 
+impl Enum {
+    /// Member named by a default or example, by its name or its wire value
+    pub fn member(&self, value: &str) -> Option<&EnumMember> {
+        self.members
+            .iter()
+            .find(|m| m.name == value || m.value == value)
+    }
+}
+
 impl Clone for Enum {
     fn clone(&self) -> Self {
         Enum {
             name: self.name.to_string(),
             values: self.values.clone(),
+            value_type: self.value_type.to_string(),
+            members: self.members.clone(),
+        }
+    }
+}
+
+impl Clone for EnumMember {
+    fn clone(&self) -> Self {
+        EnumMember {
+            name: self.name.to_string(),
+            value: self.value.to_string(),
+            description: self.description.to_string(),
+            deprecated: self.deprecated,
         }
     }
 }
 
-fn get_enum_field(pair: Pair<Rule>) -> String {
+fn get_enum_field(pair: Pair<Rule>) -> EnumMember {
+    let mut member = EnumMember {
+        name: "".to_string(),
+        value: "".to_string(),
+        description: "".to_string(),
+        deprecated: false,
+    };
     for arg_pair in pair.into_inner() {
         match arg_pair.as_rule() {
-            Rule::enumInnerItem => {
-                return arg_pair.into_inner().next().unwrap().as_str().to_string();
+            Rule::enumItemName => {
+                member.name = arg_pair.as_str().to_string();
             }
-            Rule::ident => {
-                return arg_pair.as_str().to_string();
+            Rule::enumValue => {
+                member.value = normalize_parsed(arg_pair.as_str());
+            }
+            Rule::enumDeprecated => {
+                member.deprecated = true;
+            }
+            Rule::enumDescription => {
+                member.description = normalize_parsed(arg_pair.as_str());
             }
             _ => {
-                return "".to_string();
+                // nothing to do
             }
         }
     }
-    return "".to_string();
+    member
+}
+
+fn get_enum_definition(pair: Pair<Rule>) -> Vec<EnumMember> {
+    let mut members = Vec::new();
+    for arg_pair in pair.into_inner() {
+        if let Rule::enumItem = arg_pair.as_rule() {
+            members.push(get_enum_field(arg_pair));
+        }
+    }
+    members
+}
+
+/// Fills in implicit member values, int enums continue from the previous value
+fn assign_enum_values(definition: &mut Enum) -> Result<(), String> {
+    // `None` once the previous value is the largest integer
+    let mut next = Some(0i64);
+    let name = &definition.name;
+    for member in definition.members.iter_mut() {
+        if definition.value_type == "int" {
+            let value = match member.value.is_empty() {
+                true => next.ok_or_else(|| {
+                    format!(
+                        "Value of `{}.{}` is past the largest integer, set it with `=`",
+                        name, member.name
+                    )
+                })?,
+                false => member.value.parse::<i64>().map_err(|_| {
+                    format!(
+                        "Value `{}` of `{}.{}` is not an integer",
+                        member.value, name, member.name
+                    )
+                })?,
+            };
+            member.value = value.to_string();
+            next = value.checked_add(1);
+        } else if member.value.is_empty() {
+            member.value = member.name.to_owned();
+        }
+        if definition.values.contains(&member.value) {
+            return Err(format!(
+                "Duplicate value `{}` for `{}.{}`",
+                member.value, name, member.name
+            ));
+        }
+        definition.values.push(member.value.to_owned());
+    }
+    Ok(())
 }
 
-fn get_enum_definition(pair: Pair<Rule>) -> Vec<String> {
-    let mut values: Vec<String> = Vec::new();
+fn get_enum(pair: Pair<Rule>) -> Result<Enum, String> {
+    let mut definition = Enum {
+        name: "".to_string(),
+        values: vec![],
+        value_type: "string".to_string(),
+        members: vec![],
+    };
     for arg_pair in pair.into_inner() {
         match arg_pair.as_rule() {
-            Rule::enumItem => {
-                values.push(get_enum_field(arg_pair));
+            Rule::enumName => {
+                definition.name = arg_pair.as_str().to_string();
+            }
+            Rule::enumType => {
+                definition.value_type = arg_pair.as_str().to_string();
+            }
+            Rule::enumDef => {
+                definition.members = get_enum_definition(arg_pair);
             }
             _ => {
                 // nothing to do
             }
         }
     }
-    values
+    assign_enum_values(&mut definition)?;
+    Ok(definition)
 }
 
-fn get_enum(pair: Pair<Rule>) -> Enum {
+fn get_union(pair: Pair<Rule>) -> Union {
     let mut name: String = "".to_string();
-    let mut values = Vec::new();
+    let mut variants = Vec::new();
+    let mut discriminator = "".to_string();
     for arg_pair in pair.into_inner() {
         match arg_pair.as_rule() {
-            Rule::enumName => {
+            Rule::unionName => {
                 name = arg_pair.as_str().to_string();
             }
-            Rule::enumDef => {
-                values = get_enum_definition(arg_pair);
+            Rule::unionVariant => {
+                variants.push(arg_pair.as_str().to_string());
+            }
+            Rule::unionDiscriminator => {
+                if let Some(field) = arg_pair.into_inner().next() {
+                    discriminator = field.as_str().to_string();
+                }
             }
             _ => {
                 // nothing to do
             }
         }
     }
-    Enum { name, values }
+    Union {
+        name,
+        variants,
+        discriminator,
+    }
+}
+
+/// Variants must be known structs holding the discriminator field as required when declared
+fn check_union(union: &Union, entities: &HashMap<String, Entity>) -> Result<(), String> {
+    for variant in &union.variants {
+        match entities.get(variant) {
+            Some(entity) if !union.discriminator.is_empty() => {
+                match entity.fields.get(&union.discriminator) {
+                    Some(field) if !field.is_required => {
+                        return Err(format!(
+                            "Discriminator field `{}.{}` of union `{}` must be required",
+                            variant, union.discriminator, union.name
+                        ));
+                    }
+                    Some(_) => {}
+                    None => {
+                        return Err(format!(
+                            "Variant `{}` of union `{}` has no discriminator field `{}`",
+                            variant, union.name, union.discriminator
+                        ));
+                    }
+                }
+            }
+            Some(_) => {}
+            None => {
+                return Err(format!(
+                    "Unknown variant `{}` for union `{}`",
+                    variant, union.name
+                ));
+            }
+        }
+    }
+    Ok(())
 }
 
 fn get_identifiers(pair: Pair<Rule>) -> Vec<String> {
@@ -157,12 +807,32 @@ fn get_identifiers(pair: Pair<Rule>) -> Vec<String> {
     tokens
 }
 
-fn get_object_optionals(pair: Pair<Rule>) -> Optionals {
+fn get_object_optionals(pair: Pair<Rule>) -> Result<Optionals, String> {
     let mut markers = Vec::new();
     let mut tags = HashMap::new();
+    let mut constraints = Constraints::default();
     let mut last_key = "".to_string();
     for arg_pair in pair.into_inner() {
         match arg_pair.as_rule() {
+            Rule::objConstraint => {
+                let mut keyword = "";
+                for inner in arg_pair.into_inner() {
+                    match inner.as_rule() {
+                        Rule::constraintName => {
+                            keyword = inner.as_str();
+                        }
+                        Rule::constraintValue => {
+                            constraints.set(keyword, &normalize_parsed(inner.as_str()))?;
+                        }
+                        Rule::uniqueItems => {
+                            constraints.set("uniqueItems", "")?;
+                        }
+                        _ => {
+                            // skip
+                        }
+                    }
+                }
+            }
             Rule::objMarkers => {
                 markers = get_identifiers(arg_pair);
             }
@@ -172,8 +842,8 @@ fn get_object_optionals(pair: Pair<Rule>) -> Optionals {
                         Rule::fieldName => {
                             last_key = inner.as_str().to_string();
                         }
-                        Rule::ident => {
-                            let val = inner.as_str().to_string();
+                        Rule::tagValue => {
+                            let val = inner.as_str().trim().to_string();
                             tags.insert(last_key.to_owned(), val);
                         }
                         _ => {
@@ -187,7 +857,22 @@ fn get_object_optionals(pair: Pair<Rule>) -> Optionals {
             }
         }
     }
-    Optionals { markers, tags }
+    Ok(Optionals {
+        markers,
+        tags,
+        constraints,
+    })
+}
+
+fn merge_constraints(target: &mut Constraints, from: Constraints) {
+    target.minimum = from.minimum.or(target.minimum);
+    target.maximum = from.maximum.or(target.maximum);
+    target.min_length = from.min_length.or(target.min_length);
+    target.max_length = from.max_length.or(target.max_length);
+    target.pattern = from.pattern.or(target.pattern.take());
+    target.min_items = from.min_items.or(target.min_items);
+    target.max_items = from.max_items.or(target.max_items);
+    target.unique_items = from.unique_items || target.unique_items;
 }
 
 fn normalize_parsed(source: &str) -> String {
@@ -200,63 +885,115 @@ fn normalize_parsed(source: &str) -> String {
     normalized
 }
 
-fn get_object_field(pair: Pair<Rule>) -> Field {
+fn get_object_field(pair: Pair<Rule>) -> Result<Field, String> {
     let mut identifier: String = "".to_string();
     let mut data_type: String = "".to_string();
     let mut is_array = false;
+    let mut is_map = false;
+    let mut map_key_type = "".to_string();
+    let mut is_optional = false;
+    let mut is_nullable = false;
+    let mut default_value = "".to_string();
     let mut description = "".to_string();
     let mut markers = Vec::new();
     let mut tags = HashMap::new();
+    let mut constraints = Constraints::default();
+    let mut position = (0, 0);
     for arg_pair in pair.into_inner() {
         match arg_pair.as_rule() {
             Rule::objOptionals => {
-                let opts = get_object_optionals(arg_pair);
-                if opts.markers.len() > 0 {
+                let opts = get_object_optionals(arg_pair).map_err(|e| {
+                    format!("{} of field `{}` (line {})", e, identifier, position.0)
+                })?;
+                merge_constraints(&mut constraints, opts.constraints);
+                if !opts.markers.is_empty() {
                     markers = opts.markers;
                 }
-                if opts.tags.len() > 0 {
+                if !opts.tags.is_empty() {
                     for (k, v) in opts.tags {
                         tags.insert(k, v);
                     }
                 }
             }
             Rule::objDescription => {
-                description = normalize_parsed(&arg_pair.as_str());
+                description = normalize_parsed(arg_pair.as_str());
             }
             Rule::fieldType => {
                 data_type = arg_pair.as_str().to_owned();
             }
             Rule::fieldName => {
                 identifier = arg_pair.as_str().to_owned();
+                position = arg_pair.as_span().start_pos().line_col();
             }
             Rule::arrayIndicator => {
                 is_array = true;
             }
-            // Rule::
+            Rule::mapIndicator => {
+                is_map = true;
+                if let Some(key_type) = arg_pair.into_inner().next() {
+                    map_key_type = key_type.as_str().to_owned();
+                }
+            }
+            Rule::optionalIndicator => {
+                is_optional = true;
+            }
+            Rule::nullableIndicator => {
+                is_nullable = true;
+            }
+            Rule::defaultValue => {
+                if let Some(literal) = arg_pair.into_inner().next() {
+                    default_value = normalize_parsed(literal.as_str());
+                }
+            }
             _ => {
-
                 // only white space + new line here, ignore
             }
         }
     }
     let mut example = "".to_string();
-    if tags.contains_key("example") {
-        example = tags.get("example").unwrap().to_owned();
+    if let Some(value) = tags.get("example") {
+        example = value.to_owned();
+    }
+    // declared models are reported when resolving types
+    let is_primitive = PRIMITIVE_TYPES.contains(&map_key_type.as_str());
+    if is_map && is_primitive && !MAP_KEY_TYPES.contains(&map_key_type.as_str()) {
+        return Err(format!(
+            "Map key type `{}` of field `{}` must be string, int or long (line {})",
+            map_key_type, identifier, position.0
+        ));
+    }
+    let is_required = markers.iter().any(|m| m == "required");
+    if is_optional && is_required {
+        return Err(format!(
+            "Optional field `{}` cannot be required (line {})",
+            identifier, position.0
+        ));
     }
-    let default_field = Field {
+    Ok(Field {
         identifier,
         data_type,
         description,
         is_array,
+        is_map,
+        map_key_type,
+        is_optional,
+        is_nullable,
+        is_required,
+        default_value,
         example,
         markers,
         tags,
         allowed_values: vec![],
-    };
-    default_field
+        type_kind: TypeKind::Unknown,
+        constraints,
+        inherited_from: "".to_string(),
+        position: 0,
+        line: position.0,
+        column: position.1,
+    })
 }
 
-fn get_entity_field(pair: Pair<Rule>) -> Field {
+fn get_entity_field(pair: Pair<Rule>) -> Result<Field, String> {
     for arg_pair in pair.into_inner() {
         match arg_pair.as_rule() {
             Rule::objField => {
@@ -267,29 +1004,61 @@ fn get_entity_field(pair: Pair<Rule>) -> Field {
             }
         }
     }
-    Field {
+    Ok(Field {
         identifier: "".to_string(),
         data_type: "".to_string(),
         description: "".to_string(),
         is_array: false,
+        is_map: false,
+        map_key_type: "".to_string(),
+        is_optional: false,
+        is_nullable: false,
+        is_required: false,
+        default_value: "".to_string(),
         example: "".to_string(),
         markers: vec![],
         tags: Default::default(),
         allowed_values: vec![],
+        type_kind: TypeKind::Unknown,
+        constraints: Default::default(),
+        inherited_from: "".to_string(),
+        position: 0,
+        line: 0,
+        column: 0,
+    })
+}
+
+fn get_base_name(pair: Pair<Rule>) -> String {
+    for arg_pair in pair.into_inner() {
+        if let Rule::baseName = arg_pair.as_rule() {
+            return arg_pair.as_str().to_string();
+        }
     }
+    "".to_string()
 }
 
-fn get_entity(pair: Pair<Rule>) -> Entity {
+fn get_entity(pair: Pair<Rule>) -> Result<Entity, String> {
     let mut name: String = "".to_string();
+    let mut bases = Vec::new();
     let mut fields = HashMap::new();
+    let mut position = 0;
     for arg_pair in pair.into_inner() {
         match arg_pair.as_rule() {
             Rule::ident => {
                 name = arg_pair.as_str().to_string();
             }
+            Rule::objExtends => {
+                bases.push(get_base_name(arg_pair));
+            }
             Rule::objDef => {
                 for arg_pair_alt in arg_pair.into_inner() {
-                    let field = get_entity_field(arg_pair_alt);
+                    if let Rule::objEmbedLine = arg_pair_alt.as_rule() {
+                        bases.push(get_base_name(arg_pair_alt));
+                        continue;
+                    }
+                    let mut field = get_entity_field(arg_pair_alt)?;
+                    field.position = position;
+                    position += 1;
                     fields.insert(field.identifier.to_owned(), field);
                 }
             }
@@ -298,39 +1067,248 @@ fn get_entity(pair: Pair<Rule>) -> Entity {
             }
         }
     }
-    Entity { name, fields }
+    Ok(Entity {
+        name,
+        bases,
+        fields,
+    })
 }
 
-pub fn get_models(from_model: &str) -> ProjectModel {
-    let content = ModelsParser::parse(Rule::definitions, from_model)
-        .expect("cannot parse")
-        .next()
-        .unwrap();
+/// Collects the fields an entity gets from its bases in order, walking the whole chain
+fn get_inherited_fields(
+    name: &str,
+    entities: &HashMap<String, Entity>,
+    chain: &mut Vec<String>,
+) -> Result<Vec<Field>, String> {
+    if chain.iter().any(|visited| visited == name) {
+        chain.push(name.to_owned());
+        return Err(format!("Inheritance cycle: {}", chain.join(" -> ")));
+    }
+    let entity = match entities.get(name) {
+        Some(entity) => entity,
+        None => {
+            return Err(format!(
+                "Unknown base `{}` for struct `{}`",
+                name,
+                chain.last().unwrap_or(&"".to_string())
+            ));
+        }
+    };
+    chain.push(name.to_owned());
+    let mut fields = Vec::new();
+    for base in &entity.bases {
+        fields.append(&mut get_inherited_fields(base, entities, chain)?);
+    }
+    chain.pop();
+    for field in entity.ordered_fields() {
+        let mut inherited = field.clone();
+        if inherited.inherited_from.is_empty() {
+            inherited.inherited_from = name.to_owned();
+        }
+        fields.push(inherited);
+    }
+    Ok(fields)
+}
+
+/// Adds the fields of every base to each entity, own fields take precedence.
+/// Positions are renumbered so inherited fields come first.
+fn flatten_entities(entities: &mut HashMap<String, Entity>) -> Result<(), String> {
+    let mut resolved = HashMap::new();
+    for entity in entities.values() {
+        let mut inherited = Vec::new();
+        let mut chain = vec![entity.name.to_owned()];
+        for base in &entity.bases {
+            inherited.append(&mut get_inherited_fields(base, entities, &mut chain)?);
+        }
+        let own: Vec<Field> = entity.ordered_fields().into_iter().cloned().collect();
+        let mut fields: HashMap<String, Field> = HashMap::new();
+        let mut next = 0;
+        for mut field in inherited.into_iter().chain(own) {
+            match fields.get(&field.identifier) {
+                Some(previous) => field.position = previous.position,
+                None => {
+                    field.position = next;
+                    next += 1;
+                }
+            }
+            fields.insert(field.identifier.to_owned(), field);
+        }
+        resolved.insert(entity.name.to_owned(), fields);
+    }
+    for (name, fields) in resolved {
+        entities.get_mut(&name).unwrap().fields = fields;
+    }
+    Ok(())
+}
+
+/// Sets the kind of every field type, enum fields also get their allowed values
+fn resolve_types(model: &mut ProjectModel) {
+    let mut resolved = Vec::new();
+    for (entity_name, entity) in &model.entities {
+        for (identifier, field) in &entity.fields {
+            let kind = model.type_kind(&field.data_type);
+            if kind == TypeKind::Unknown && field.inherited_from.is_empty() {
+                model.diagnostics.push(Diagnostic {
+                    line: field.line,
+                    column: field.column,
+                    message: format!(
+                        "Unknown type `{}` for field `{}.{}`",
+                        field.data_type, entity_name, identifier
+                    ),
+                });
+            }
+            if field.is_map && field.inherited_from.is_empty() {
+                let problem = match model.type_kind(&field.map_key_type) {
+                    TypeKind::Primitive => "",
+                    TypeKind::Unknown => "is unknown",
+                    _ => "is not a primitive",
+                };
+                if !problem.is_empty() {
+                    model.diagnostics.push(Diagnostic {
+                        line: field.line,
+                        column: field.column,
+                        message: format!(
+                            "Map key type `{}` for field `{}.{}` {}",
+                            field.map_key_type, entity_name, identifier, problem
+                        ),
+                    });
+                }
+            }
+            let mut allowed_values = Vec::new();
+            if let Some(definition) = model.enums.get(&field.data_type) {
+                allowed_values = definition.values.clone();
+            }
+            resolved.push((
+                entity_name.to_owned(),
+                identifier.to_owned(),
+                kind,
+                allowed_values,
+            ));
+        }
+    }
+    for (entity_name, identifier, kind, allowed_values) in resolved {
+        let field = model
+            .entities
+            .get_mut(&entity_name)
+            .and_then(|entity| entity.fields.get_mut(&identifier))
+            .unwrap();
+        field.type_kind = kind;
+        field.allowed_values = allowed_values;
+    }
+}
+
+/// A struct can only be built when its required struct fields do not lead back to it.
+/// Cycles are reported from their alphabetically first struct so each one shows once.
+fn find_required_cycle(
+    name: &str,
+    model: &ProjectModel,
+    path: &mut Vec<(String, String)>,
+    cycles: &mut Vec<Diagnostic>,
+) {
+    let entity = match model.entities.get(name) {
+        Some(entity) => entity,
+        None => return,
+    };
+    let mut identifiers: Vec<&String> = entity.fields.keys().collect();
+    identifiers.sort();
+    for identifier in identifiers {
+        let field = &entity.fields[identifier];
+        let direct = field.is_required && !field.is_nullable && !field.is_array && !field.is_map;
+        if !direct || field.type_kind != TypeKind::Struct {
+            continue;
+        }
+        path.push((name.to_owned(), identifier.to_owned()));
+        let start = path[0].0.to_owned();
+        if field.data_type == start {
+            let steps: Vec<String> = path.iter().map(|(e, f)| format!("{}.{}", e, f)).collect();
+            let first = &model.entities[&start].fields[&path[0].1];
+            cycles.push(Diagnostic {
+                line: first.line,
+                column: first.column,
+                message: format!(
+                    "Required reference cycle: {} -> {}",
+                    steps.join(" -> "),
+                    start
+                ),
+            });
+        } else if field.data_type > start && !path.iter().any(|(e, _)| e == &field.data_type) {
+            find_required_cycle(&field.data_type, model, path, cycles);
+        }
+        path.pop();
+    }
+}
+
+fn check_required_cycles(model: &mut ProjectModel) {
+    let mut names: Vec<&String> = model.entities.keys().collect();
+    names.sort();
+    let mut cycles = Vec::new();
+    for name in names {
+        find_required_cycle(name, model, &mut vec![], &mut cycles);
+    }
+    model.diagnostics.append(&mut cycles);
+}
+
+pub fn get_models(from_model: &str) -> Result<ProjectModel, String> {
+    let content = match ModelsParser::parse(Rule::definitions, from_model) {
+        Ok(mut pairs) => pairs.next().unwrap(),
+        Err(e) => return Err(format!("Cannot parse models {}", e)),
+    };
 
     let mut enums = HashMap::new();
     let mut entities = HashMap::new();
+    let mut unions = HashMap::new();
+    // structs, enums and unions share one namespace
+    let mut names = HashSet::new();
     for record in content.into_inner() {
-        match record.as_rule() {
+        let name = match record.as_rule() {
             Rule::enu => {
-                let val = get_enum(record);
+                let val = get_enum(record)?;
+                let name = val.name.to_owned();
                 enums.insert(val.name.to_owned(), val);
+                name
+            }
+            Rule::uni => {
+                let val = get_union(record);
+                let name = val.name.to_owned();
+                unions.insert(val.name.to_owned(), val);
+                name
             }
             Rule::objType => {
-                let val = get_entity(record);
+                let val = get_entity(record)?;
+                let name = val.name.to_owned();
                 entities.insert(val.name.to_owned(), val);
+                name
             }
             _ => {
                 // println!("other");
+                continue;
             }
+        };
+        if !names.insert(name.to_owned()) {
+            return Err(format!("Duplicate model name `{}`", name));
         }
     }
-    ProjectModel { entities, enums }
+    flatten_entities(&mut entities)?;
+    for union in unions.values() {
+        check_union(union, &entities)?;
+    }
+    let mut model = ProjectModel {
+        entities,
+        enums,
+        unions,
+        diagnostics: vec![],
+    };
+    resolve_types(&mut model);
+    check_required_cycles(&mut model);
+    model.diagnostics.sort_by_key(|d| (d.line, d.column));
+    Ok(model)
 }
 
 #[test]
+#[allow(clippy::get_first)]
 fn test_parser() {
     let input = include_str!("models_def.model");
-    let result = get_models(input);
+    let result = get_models(input).unwrap();
     let mood = result.enums.get("Mood").unwrap();
 
     assert_eq!(mood.name, "Mood");
@@ -349,3 +1327,237 @@ fn test_parser() {
     let class_room = result.entities.get("ClassRoom").unwrap();
     assert_eq!(class_room.name, "ClassRoom");
 }
+
+#[test]
+fn test_optional_nullable_and_default_fields() {
+    let input = "struct Settings {\n nickname? string\n theme string? = \"dark\" required\n retries int = 3 required\n}\n";
+    let result = get_models(input).unwrap();
+    let settings = result.entities.get("Settings").unwrap();
+
+    let nickname = settings.fields.get("nickname").unwrap();
+    assert!(nickname.is_optional);
+    assert!(!nickname.is_nullable);
+    assert!(!nickname.is_required);
+
+    let theme = settings.fields.get("theme").unwrap();
+    assert!(theme.is_nullable);
+    assert!(theme.is_required);
+    assert_eq!(theme.default_value, "dark");
+
+    let retries = settings.fields.get("retries").unwrap();
+    assert_eq!(retries.default_value, "3");
+    assert_eq!(retries.markers, vec!["required"]);
+
+    let error = get_models("struct Settings {\n alias? string required\n}\n").unwrap_err();
+    assert_eq!(error, "Optional field `alias` cannot be required (line 2)");
+}
+
+#[test]
+fn test_map_fields() {
+    let input = "struct Directory {\n people map[string]Person\n tags map[string][]string\n}\n";
+    let result = get_models(input).unwrap();
+    let directory = result.entities.get("Directory").unwrap();
+
+    let people = directory.fields.get("people").unwrap();
+    assert!(people.is_map);
+    assert!(!people.is_array);
+    assert_eq!(people.map_key_type, "string");
+    assert_eq!(people.data_type, "Person");
+
+    let tags = directory.fields.get("tags").unwrap();
+    assert!(tags.is_map);
+    assert!(tags.is_array);
+    assert_eq!(tags.data_type, "string");
+
+    let error = get_models("struct Directory {\n a map[bool]int\n}\n").unwrap_err();
+    assert_eq!(
+        error,
+        "Map key type `bool` of field `a` must be string, int or long (line 2)"
+    );
+}
+
+#[test]
+fn test_struct_composition() {
+    let input = "struct Auditable {\n createdAt string\n updatedAt string\n}\n\nstruct Base {\n id string required\n ...Auditable\n}\n\nstruct Person extends Base {\n name string\n}\n";
+    let result = get_models(input).unwrap();
+    let person = result.entities.get("Person").unwrap();
+
+    assert_eq!(person.bases, vec!["Base"]);
+    assert_eq!(person.fields.len(), 4);
+    assert_eq!(person.fields.get("name").unwrap().inherited_from, "");
+    assert_eq!(person.fields.get("id").unwrap().inherited_from, "Base");
+    assert_eq!(
+        person.fields.get("createdAt").unwrap().inherited_from,
+        "Auditable"
+    );
+
+    let unknown = get_models("struct Person extends Missing {\n name string\n}\n");
+    assert_eq!(
+        unknown.unwrap_err(),
+        "Unknown base `Missing` for struct `Person`"
+    );
+
+    let cycle = get_models("struct A extends B {\n a string\n}\n\nstruct B {\n ...A\n}\n");
+    assert!(cycle.unwrap_err().starts_with("Inheritance cycle:"));
+}
+
+#[test]
+fn test_unions() {
+    let input = "union Payment = Card | BankTransfer discriminator type\n\nstruct Card {\n type string required\n number string\n}\n\nstruct BankTransfer {\n type string required\n iban string\n}\n";
+    let result = get_models(input).unwrap();
+    let payment = result.unions.get("Payment").unwrap();
+
+    assert_eq!(payment.variants, vec!["Card", "BankTransfer"]);
+    assert_eq!(payment.discriminator, "type");
+
+    let missing =
+        get_models("union Payment = Card discriminator kind\n\nstruct Card {\n number string\n}\n");
+    assert_eq!(
+        missing.unwrap_err(),
+        "Variant `Card` of union `Payment` has no discriminator field `kind`"
+    );
+
+    let optional =
+        get_models("union Payment = Card discriminator type\n\nstruct Card {\n type string\n}\n");
+    assert_eq!(
+        optional.unwrap_err(),
+        "Discriminator field `Card.type` of union `Payment` must be required"
+    );
+
+    let duplicate = get_models("struct Card {\n number string\n}\n\nenum Card { VISA }\n");
+    assert_eq!(duplicate.unwrap_err(), "Duplicate model name `Card`");
+}
+
+#[test]
+fn test_field_constraints() {
+    let input = "struct Address {\n zip string pattern(\"^[0-9]{5}$\") required\n age int min(0) max(150)\n lines []string minItems(1) maxItems(3) uniqueItems maxLength(80)\n}\n";
+    let result = get_models(input).unwrap();
+    let address = result.entities.get("Address").unwrap();
+
+    let zip = address.fields.get("zip").unwrap();
+    assert_eq!(zip.constraints.pattern, Some("^[0-9]{5}$".to_string()));
+    assert!(zip.is_required);
+
+    let age = address.fields.get("age").unwrap();
+    assert_eq!(age.constraints.minimum, Some(0.0));
+    assert_eq!(age.constraints.maximum, Some(150.0));
+
+    let lines = address.fields.get("lines").unwrap();
+    assert_eq!(lines.constraints.min_items, Some(1));
+    assert_eq!(lines.constraints.max_items, Some(3));
+    assert!(lines.constraints.unique_items);
+    assert_eq!(lines.constraints.max_length, Some(80));
+
+    let error = get_models("struct Address {\n zip string minLength(-1)\n}\n").unwrap_err();
+    assert_eq!(
+        error,
+        "Invalid value `-1` for `minLength` of field `zip` (line 2)"
+    );
+
+    let value = serde_json::json!({ "zip": "12345", "age": 200, "lines": ["a", "a", "a", "a"] });
+    assert_eq!(
+        result.validate_value("Address", &value),
+        vec![
+            "$.age: 200 is above max(150)",
+            "$.lines: 4 items, maxItems(3)",
+            "$.lines: items are not unique",
+        ]
+    );
+}
+
+#[test]
+fn test_rich_enums() {
+    let input = "enum Status {\n  ACTIVE = \"active\" \"Currently active\"\n  INACTIVE = \"inactive\" deprecated\n  PENDING\n}\n\nenum Priority int { LOW = 1, MEDIUM, HIGH = 10 }\n";
+    let result = get_models(input).unwrap();
+
+    let status = result.enums.get("Status").unwrap();
+    assert_eq!(status.value_type, "string");
+    assert_eq!(status.values, vec!["active", "inactive", "PENDING"]);
+    assert_eq!(status.members[0].name, "ACTIVE");
+    assert_eq!(status.members[0].description, "Currently active");
+    assert!(status.members[1].deprecated);
+
+    let priority = result.enums.get("Priority").unwrap();
+    assert_eq!(priority.value_type, "int");
+    assert_eq!(priority.values, vec!["1", "2", "10"]);
+
+    let invalid = get_models("enum Priority int { LOW = \"low\" }\n");
+    assert_eq!(
+        invalid.unwrap_err(),
+        "Value `low` of `Priority.LOW` is not an integer"
+    );
+
+    let duplicate = get_models("enum P int { A = 1, B, C = 2 }\n");
+    assert_eq!(duplicate.unwrap_err(), "Duplicate value `2` for `P.C`");
+    let duplicate = get_models("enum S { A = \"a\", a }\n");
+    assert_eq!(duplicate.unwrap_err(), "Duplicate value `a` for `S.a`");
+
+    let largest = get_models("enum P int { A = 9223372036854775807 }\n").unwrap();
+    assert_eq!(largest.enums["P"].values, vec!["9223372036854775807"]);
+    let overflow = get_models("enum P int { A = 9223372036854775807, B }\n");
+    assert_eq!(
+        overflow.unwrap_err(),
+        "Value of `P.B` is past the largest integer, set it with `=`"
+    );
+}
+
+#[test]
+fn test_type_resolution() {
+    let input = "enum Mood { happy, sad }\n\nstruct Person {\n age int\n mood Mood\n typo Mod\n}\n\nstruct A {\n b B required\n}\n\nstruct B {\n a A required\n}\n";
+    let result = get_models(input).unwrap();
+    let person = result.entities.get("Person").unwrap();
+
+    assert_eq!(
+        person.fields.get("age").unwrap().type_kind,
+        TypeKind::Primitive
+    );
+    let mood = person.fields.get("mood").unwrap();
+    assert_eq!(mood.type_kind, TypeKind::Enum);
+    assert_eq!(mood.allowed_values, vec!["happy", "sad"]);
+    assert_eq!(
+        person.fields.get("typo").unwrap().type_kind,
+        TypeKind::Unknown
+    );
+
+    let messages: Vec<String> = result.diagnostics.iter().map(|d| d.to_string()).collect();
+    assert_eq!(
+        messages,
+        vec![
+            "6:2 Unknown type `Mod` for field `Person.typo`",
+            "10:2 Required reference cycle: A.b -> B.a -> A",
+        ]
+    );
+
+    let input =
+        "enum Mood { happy, sad }\n\nstruct Person {\n moods map[Mood]int\n pets map[Foo]int\n}\n";
+    let result = get_models(input).unwrap();
+    let messages: Vec<String> = result.diagnostics.iter().map(|d| d.to_string()).collect();
+    assert_eq!(
+        messages,
+        vec![
+            "4:2 Map key type `Mood` for field `Person.moods` is not a primitive",
+            "5:2 Map key type `Foo` for field `Person.pets` is unknown",
+        ]
+    );
+}
+
+#[test]
+fn test_typed_examples_and_order() {
+    let input = "struct Base {\n id string\n}\n\nstruct Item extends Base {\n price float `example: 9.5`\n active bool `example: true`\n sizes []int `example: 1, 2`\n label string `example: \"big\"`\n}\n";
+    let result = get_models(input).unwrap();
+    let item = result.entities.get("Item").unwrap();
+
+    let names: Vec<&str> = item
+        .ordered_fields()
+        .iter()
+        .map(|f| f.identifier.as_str())
+        .collect();
+    assert_eq!(names, vec!["id", "price", "active", "sizes", "label"]);
+
+    let example = |name: &str| item.fields.get(name).unwrap().example_value().unwrap();
+    assert_eq!(example("price"), Value::from(9.5));
+    assert_eq!(example("active"), Value::from(true));
+    assert_eq!(example("sizes"), serde_json::json!([1, 2]));
+    assert_eq!(example("label"), Value::from("big"));
+    assert_eq!(item.fields.get("id").unwrap().example_value(), None);
+}
@@ -1,12 +1,10 @@
+#![allow(non_local_definitions)]
+
 use std::fs;
 use std::fs::File;
+use std::path::Path;
 
-mod models;
-
-
-extern crate pest;
-#[macro_use]
-extern crate pest_derive;
+use apish::{json_schema, models};
 
 extern crate structopt;
 #[macro_use]
@@ -19,18 +17,66 @@ struct Opt {
     input: String,
     #[structopt(short = "o", help = "Output file", default_value = "./models.json")]
     output: String,
+    #[structopt(
+        long = "format",
+        help = "json (parsed models) or json-schema (draft 2020-12)",
+        default_value = "json"
+    )]
+    format: String,
+    #[structopt(
+        long = "split",
+        help = "With json-schema, writes a <Struct>.schema.json per struct in the output directory"
+    )]
+    split: bool,
+}
+
+fn write_schemas(opt: &Opt, model_file: &models::ProjectModel) -> Result<(), String> {
+    if !opt.split {
+        let file = File::create(&opt.output).map_err(|e| e.to_string())?;
+        serde_json::to_writer_pretty(file, &json_schema::document(model_file))
+            .map_err(|e| e.to_string())?;
+        println!("Generated {} JSON Schema file", opt.output);
+        return Ok(());
+    }
+    fs::create_dir_all(&opt.output).map_err(|e| e.to_string())?;
+    for (name, document) in json_schema::entity_documents(model_file) {
+        let path = Path::new(&opt.output).join(format!("{}.schema.json", name));
+        let file = File::create(&path).map_err(|e| e.to_string())?;
+        serde_json::to_writer_pretty(file, &document).map_err(|e| e.to_string())?;
+        println!("Generated {} JSON Schema file", path.display());
+    }
+    Ok(())
 }
 
 fn main() {
     let opt = Opt::from_args();
+    if opt.format != "json" && opt.format != "json-schema" {
+        println!("Unknown format `{}`, use json or json-schema", opt.format);
+        return;
+    }
     let version = env!("CARGO_PKG_VERSION");
     println!("Models 🚀 v{}\nReading models from {}", version, opt.input);
-    if let Ok(content) = fs::read_to_string(opt.input) {
-        let model_file = models::get_models(&content);
-        let api_file = File::create(&opt.output).unwrap();
-        serde_json::to_writer(api_file, &model_file).unwrap();
-        println!("Generated {} models file", opt.output);
+    if let Ok(content) = fs::read_to_string(&opt.input) {
+        match models::get_models(&content) {
+            Ok(model_file) => {
+                for diagnostic in &model_file.diagnostics {
+                    println!("⚠️ {}:{}", opt.input, diagnostic);
+                }
+                if opt.format == "json-schema" {
+                    if let Err(e) = write_schemas(&opt, &model_file) {
+                        println!("{}", e);
+                    }
+                    return;
+                }
+                let api_file = File::create(&opt.output).unwrap();
+                serde_json::to_writer(api_file, &model_file).unwrap();
+                println!("Generated {} models file", opt.output);
+            }
+            Err(e) => {
+                println!("{}", e);
+            }
+        }
     } else {
         println!("Invalid input");
     }
-}
\ No newline at end of file
+}
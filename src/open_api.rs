@@ -1,6 +1,8 @@
-use crate::get_mime_types;
+use crate::api_spec::get_mime_types;
+use crate::models::{typed_value, Constraints, Entity, Enum, Field, ProjectModel, Union};
 use crate::project::{APIConfiguration, APIDefinition, Project, ProjectArgument, StatusCode};
 use serde::Serialize;
+use serde_json::Value;
 use std::collections::HashMap;
 
 #[derive(Debug, Serialize)]
@@ -8,6 +10,59 @@ pub struct OpenAPI {
     openapi: String,
     info: InfoSpec,
     paths: HashMap<String, PathSpec>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    components: Option<ComponentsSpec>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ComponentsSpec {
+    pub schemas: HashMap<String, ModelSchemaSpec>,
+}
+
+/// Schema object used for models, fields without value are not serialized
+#[derive(Debug, Serialize, Default)]
+pub struct ModelSchemaSpec {
+    #[serde(rename = "$ref", skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub type_field: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    #[serde(rename = "allOf", skip_serializing_if = "Vec::is_empty")]
+    pub all_of: Vec<ModelSchemaSpec>,
+    #[serde(rename = "oneOf", skip_serializing_if = "Vec::is_empty")]
+    pub one_of: Vec<ModelSchemaSpec>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub discriminator: Option<DiscriminatorSpec>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub items: Option<Box<ModelSchemaSpec>>,
+    #[serde(
+        rename = "additionalProperties",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub additional_properties: Option<Box<ModelSchemaSpec>>,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub properties: HashMap<String, ModelSchemaSpec>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub required: Vec<String>,
+    #[serde(rename = "enum", skip_serializing_if = "Vec::is_empty")]
+    pub enum_values: Vec<Value>,
+    #[serde(rename = "x-enum-varnames", skip_serializing_if = "Vec::is_empty")]
+    pub enum_var_names: Vec<String>,
+    #[serde(rename = "x-enum-descriptions", skip_serializing_if = "Vec::is_empty")]
+    pub enum_descriptions: Vec<String>,
+    #[serde(rename = "x-enum-deprecated", skip_serializing_if = "Vec::is_empty")]
+    pub enum_deprecated: Vec<Value>,
+    #[serde(skip_serializing_if = "is_not_false")]
+    pub nullable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub example: Option<Value>,
+    #[serde(flatten)]
+    pub constraints: Constraints,
 }
 
 #[derive(Debug, Serialize)]
@@ -50,6 +105,12 @@ pub struct APISchemaSpec {
     pub type_field: String,
 }
 
+#[derive(Debug, Serialize)]
+pub struct DiscriminatorSpec {
+    #[serde(rename = "propertyName")]
+    pub property_name: String,
+}
+
 #[derive(Debug, Serialize)]
 pub struct APIParamSpec {
     pub name: String,
@@ -65,6 +126,8 @@ pub struct APIParamSpec {
 pub struct APIParamSchemaSpec {
     #[serde(rename = "type")]
     pub type_field: String,
+    #[serde(flatten)]
+    pub constraints: Constraints,
 }
 
 #[derive(Debug, Serialize)]
@@ -87,6 +150,7 @@ fn args_to_params(list: Vec<&ProjectArgument>, group_name: &str) -> Vec<APIParam
             required: item.required,
             schema: APIParamSchemaSpec {
                 type_field: item.data_type.as_str().to_owned(),
+                constraints: item.constraints.clone(),
             },
         };
         params.push(param);
@@ -169,12 +233,213 @@ fn get_paths_from_project(project: &Project) -> HashMap<String, PathSpec> {
     for (endpoint, definition) in &project.endpoints {
         response.insert(
             endpoint.to_owned(),
-            api_spec_from_endpoint(definition, &project),
+            api_spec_from_endpoint(definition, project),
         );
     }
     response
 }
 
+/// Maps model primitive types to OpenAPI types
+fn primitive_type(data_type: &str) -> Option<&'static str> {
+    match data_type {
+        "string" | "date" | "datetime" => Some("string"),
+        "int" | "integer" | "long" => Some("integer"),
+        "number" | "float" | "double" => Some("number"),
+        "bool" | "boolean" => Some("boolean"),
+        _ => None,
+    }
+}
+
+fn type_schema(data_type: &str, models: &ProjectModel) -> ModelSchemaSpec {
+    if let Some(type_field) = primitive_type(data_type) {
+        let format = match data_type {
+            "long" => Some("int64".to_string()),
+            "float" => Some("float".to_string()),
+            "double" => Some("double".to_string()),
+            "date" => Some("date".to_string()),
+            "datetime" => Some("date-time".to_string()),
+            _ => None,
+        };
+        return ModelSchemaSpec {
+            type_field: Some(type_field.to_string()),
+            format,
+            ..Default::default()
+        };
+    }
+    if models.entities.contains_key(data_type)
+        || models.enums.contains_key(data_type)
+        || models.unions.contains_key(data_type)
+    {
+        return ModelSchemaSpec {
+            reference: Some(format!("#/components/schemas/{}", data_type)),
+            ..Default::default()
+        };
+    }
+    ModelSchemaSpec {
+        type_field: Some("string".to_string()),
+        ..Default::default()
+    }
+}
+
+/// Moves out the constraints applying to arrays, leaving the ones for their items
+fn take_array_constraints(constraints: &mut Constraints) -> Constraints {
+    Constraints {
+        min_items: constraints.min_items.take(),
+        max_items: constraints.max_items.take(),
+        unique_items: std::mem::take(&mut constraints.unique_items),
+        ..Default::default()
+    }
+}
+
+fn field_to_schema(field: &Field, models: &ProjectModel) -> ModelSchemaSpec {
+    let mut schema = type_schema(&field.data_type, models);
+    let mut constraints = field.constraints.clone();
+    if field.is_array {
+        let array_constraints = take_array_constraints(&mut constraints);
+        schema.constraints = constraints;
+        schema = ModelSchemaSpec {
+            type_field: Some("array".to_string()),
+            items: Some(Box::new(schema)),
+            constraints: array_constraints,
+            ..Default::default()
+        };
+    } else {
+        schema.constraints = constraints;
+    }
+    if field.is_map {
+        // JSON object keys are always strings, the key type is not represented
+        schema = ModelSchemaSpec {
+            type_field: Some("object".to_string()),
+            additional_properties: Some(Box::new(schema)),
+            ..Default::default()
+        };
+    }
+    let has_siblings = field.is_nullable
+        || !field.description.is_empty()
+        || !field.default_value.is_empty()
+        || !field.example.is_empty();
+    if schema.reference.is_some() && has_siblings {
+        // siblings of $ref are ignored by OpenAPI 3.0, wrapping keeps them
+        schema = ModelSchemaSpec {
+            all_of: vec![schema],
+            ..Default::default()
+        };
+    }
+    schema.description = field.description.to_owned();
+    schema.nullable = field.is_nullable;
+    schema.default = models.default_value(field);
+    schema.example = field.example_value();
+    schema
+}
+
+fn entity_to_schema(entity: &Entity, models: &ProjectModel) -> ModelSchemaSpec {
+    let mut properties = HashMap::new();
+    let mut required = Vec::new();
+    for (name, field) in &entity.fields {
+        if !field.inherited_from.is_empty() {
+            // described by the base schema
+            continue;
+        }
+        if field.is_required {
+            required.push(name.to_owned());
+        }
+        properties.insert(name.to_owned(), field_to_schema(field, models));
+    }
+    required.sort();
+    let schema = ModelSchemaSpec {
+        type_field: Some("object".to_string()),
+        properties,
+        required,
+        ..Default::default()
+    };
+    if entity.bases.is_empty() {
+        return schema;
+    }
+    let mut all_of: Vec<ModelSchemaSpec> = entity
+        .bases
+        .iter()
+        .map(|base| type_schema(base, models))
+        .collect();
+    all_of.push(schema);
+    ModelSchemaSpec {
+        all_of,
+        ..Default::default()
+    }
+}
+
+fn enum_to_schema(definition: &Enum) -> ModelSchemaSpec {
+    let mut schema = ModelSchemaSpec {
+        type_field: Some("string".to_string()),
+        ..Default::default()
+    };
+    let value_type = match definition.value_type.as_str() {
+        "int" => {
+            schema.type_field = Some("integer".to_string());
+            "int"
+        }
+        _ => "string",
+    };
+    for member in &definition.members {
+        schema
+            .enum_values
+            .push(typed_value(value_type, &member.value));
+        if member.deprecated {
+            schema
+                .enum_deprecated
+                .push(typed_value(value_type, &member.value));
+        }
+    }
+    // extensions are only worth emitting when they add information
+    if definition.members.iter().any(|m| m.name != m.value) {
+        schema.enum_var_names = definition.members.iter().map(|m| m.name.clone()).collect();
+    }
+    if definition.members.iter().any(|m| !m.description.is_empty()) {
+        schema.enum_descriptions = definition
+            .members
+            .iter()
+            .map(|m| m.description.clone())
+            .collect();
+    }
+    schema
+}
+
+fn union_to_schema(definition: &Union, models: &ProjectModel) -> ModelSchemaSpec {
+    let mut discriminator = None;
+    if !definition.discriminator.is_empty() {
+        discriminator = Some(DiscriminatorSpec {
+            property_name: definition.discriminator.to_owned(),
+        });
+    }
+    ModelSchemaSpec {
+        one_of: definition
+            .variants
+            .iter()
+            .map(|variant| type_schema(variant, models))
+            .collect(),
+        discriminator,
+        ..Default::default()
+    }
+}
+
+fn get_components_from_project(project: &Project) -> Option<ComponentsSpec> {
+    match &project.models {
+        Some(models) => {
+            let mut schemas = HashMap::new();
+            for (name, entity) in &models.entities {
+                schemas.insert(name.to_owned(), entity_to_schema(entity, models));
+            }
+            for (name, definition) in &models.enums {
+                schemas.insert(name.to_owned(), enum_to_schema(definition));
+            }
+            for (name, definition) in &models.unions {
+                schemas.insert(name.to_owned(), union_to_schema(definition, models));
+            }
+            Some(ComponentsSpec { schemas })
+        }
+        None => None,
+    }
+}
+
 impl OpenAPI {
     pub fn new_from_project_spec(project: &Project) -> OpenAPI {
         OpenAPI {
@@ -183,7 +448,35 @@ impl OpenAPI {
                 title: project.title.to_owned(),
                 version: project.version.to_owned(),
             },
-            paths: get_paths_from_project(&project),
+            paths: get_paths_from_project(project),
+            components: get_components_from_project(project),
         }
     }
 }
+
+#[test]
+fn test_model_field_schema() {
+    let models = crate::models::get_models(
+        "enum Mood { happy, sad }\n\nenum Kind { DOG = \"dog\" }\n\nenum Size int { SMALL = 1 }\n\nstruct Person {\n age int? = 3\n mood Mood = happy\n kind Kind = DOG\n size Size = SMALL\n}\n",
+    )
+    .unwrap();
+    let person = models.entities.get("Person").unwrap();
+
+    let age = field_to_schema(person.fields.get("age").unwrap(), &models);
+    assert_eq!(age.type_field, Some("integer".to_string()));
+    assert!(age.nullable);
+    assert_eq!(age.default, Some(Value::from(3)));
+
+    let mood = field_to_schema(person.fields.get("mood").unwrap(), &models);
+    assert_eq!(
+        mood.all_of[0].reference,
+        Some("#/components/schemas/Mood".to_string())
+    );
+    assert_eq!(mood.default, Some(Value::from("happy")));
+
+    // enum defaults name a member, the schema holds its wire value
+    let kind = field_to_schema(person.fields.get("kind").unwrap(), &models);
+    assert_eq!(kind.default, Some(Value::from("dog")));
+    let size = field_to_schema(person.fields.get("size").unwrap(), &models);
+    assert_eq!(size.default, Some(Value::from(1)));
+}
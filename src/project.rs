@@ -1,18 +1,18 @@
 use crate::examples;
 
 use std::collections::HashMap;
-use std::fs;
 
 use examples::Bag;
 
-use crate::models::{get_models, ProjectModel};
+use crate::models::{get_models, Constraints, ProjectModel};
 use pest::iterators::Pair;
 use pest::Parser;
-use serde::Serialize;
+use pest_derive::Parser;
+use serde::{Deserialize, Serialize};
 
 #[derive(Parser)]
 #[grammar = "grammar.pest"]
-struct ApishParser;
+pub(crate) struct ApishParser;
 
 // ToDo: Replace me
 #[derive(Debug)]
@@ -36,7 +36,7 @@ enum PairModifiers {
     Unknown,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct APIDefinition {
     pub position: isize,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -49,9 +49,14 @@ pub struct APIDefinition {
     pub delete: Option<APIConfiguration>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub patch: Option<APIConfiguration>,
+    /// position of the path in the `.api` file
+    #[serde(skip)]
+    pub line: usize,
+    #[serde(skip)]
+    pub column: usize,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub enum DataType {
     String,
     Number,
@@ -59,14 +64,19 @@ pub enum DataType {
     Unknown,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct StatusCode {
     pub code: String,
     pub description: String,
     pub is_retryable: bool,
+    /// position of the code in `status_codes`, zero for the well known ones
+    #[serde(skip)]
+    pub line: usize,
+    #[serde(skip)]
+    pub column: usize,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ProjectArgument {
     pub name: String,
     pub data_type: DataType,
@@ -74,15 +84,21 @@ pub struct ProjectArgument {
     pub required: bool,
     pub default_value: String,
     pub description: String,
+    pub constraints: Constraints,
+    /// position of the name in the `.api` file
+    #[serde(skip)]
+    pub line: usize,
+    #[serde(skip)]
+    pub column: usize,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 struct ArgumentGroup {
     id: String,
     items: Vec<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Project {
     pub title: String,
     pub version: String,
@@ -99,7 +115,7 @@ pub struct Project {
     pub models: Option<ProjectModel>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct APIConfiguration {
     pub description: String,
     pub operation: String,
@@ -114,6 +130,11 @@ pub struct APIConfiguration {
     pub example: String,
     pub request_model: String,
     pub response_model: String,
+    /// position of the HTTP verb in the `.api` file
+    #[serde(skip)]
+    pub line: usize,
+    #[serde(skip)]
+    pub column: usize,
 }
 
 impl Clone for DataType {
@@ -147,12 +168,32 @@ impl PartialEq for DataType {
     }
 }
 
+impl APIDefinition {
+    /// Declared operations with their lowercase HTTP verb, in get, post, put, delete, patch
+    /// order
+    pub fn operations(&self) -> Vec<(&'static str, &APIConfiguration)> {
+        let methods = [
+            ("get", &self.get),
+            ("post", &self.post),
+            ("put", &self.put),
+            ("delete", &self.delete),
+            ("patch", &self.patch),
+        ];
+        methods
+            .iter()
+            .filter_map(|(verb, config)| config.as_ref().map(|c| (*verb, c)))
+            .collect()
+    }
+}
+
 impl StatusCode {
     fn new(code: &str, description: &str) -> StatusCode {
         StatusCode {
             code: code.to_string(),
             description: description.to_string(),
             is_retryable: false,
+            line: 0,
+            column: 0,
         }
     }
 }
@@ -164,6 +205,7 @@ impl PartialEq for ProjectArgument {
             || other.default_value != self.default_value
             || other.data_type != self.data_type
             || other.alias != self.alias
+            || other.constraints != self.constraints
         {
             return false;
         }
@@ -193,33 +235,28 @@ impl Project {
         }
     }
 
-    pub fn new_from_file(
-        file_name: String,
-        models_file: String,
-        examples_file: String,
+    /// Builds a project from in-memory sources, the models and examples are optional
+    pub fn new_from_sources(
+        api_source: &str,
+        models_source: Option<&str>,
+        examples_source: Option<&str>,
     ) -> Result<Project, String> {
-        let api_definition = get_file_content(file_name);
-        if let Err(e) = api_definition {
-            return Err(e);
-        }
         let mut models: Option<ProjectModel> = None;
-        match get_file_content(models_file) {
-            Ok(file_content) => {
-                models = Some(get_models(file_content.as_ref()));
-            }
-            Err(e) => {
-                println!("Models error: {}", e);
-            }
+        if let Some(content) = models_source {
+            models = Some(get_models(content)?);
         }
-        let bag = Bag::new_from_file(examples_file.as_str());
-        match ApishParser::parse(Rule::api_file, &api_definition.unwrap().as_ref()) {
+        let bag = match examples_source {
+            Some(content) => Bag::new_from_str(content),
+            None => Bag::default(),
+        };
+        match ApishParser::parse(Rule::api_file, api_source) {
             Ok(mut pairs) => {
                 let n = pairs.next();
                 match n {
                     Some(pair) => {
                         let mut project = Project::new(bag.examples, models);
 
-                        parse_value(pair, 0, &mut project);
+                        parse_value(pair, 0, &mut project)?;
                         Ok(project)
                     }
                     None => Err("Cannot process file".to_string()),
@@ -229,31 +266,28 @@ impl Project {
         }
     }
 
+    /// Loads a project from the api.json `apish` writes, models diagnostics are not part
+    /// of it
+    pub fn new_from_json(content: &str) -> Result<Project, String> {
+        serde_json::from_str(content).map_err(|e| format!("Cannot parse api.json {}", e))
+    }
+
     fn get_header(&self, name: &str) -> Option<&ProjectArgument> {
-        for header in &self.headers {
-            if header.alias == name || header.name == name {
-                return Some(header);
-            }
-        }
-        None
+        self.headers
+            .iter()
+            .find(|header| header.alias == name || header.name == name)
     }
 
     fn get_query_string(&self, name: &str) -> Option<&ProjectArgument> {
-        for query in &self.query {
-            if query.alias == name || query.name == name {
-                return Some(query);
-            }
-        }
-        None
+        self.query
+            .iter()
+            .find(|query| query.alias == name || query.name == name)
     }
 
     fn get_path_param(&self, name: &str) -> Option<&ProjectArgument> {
-        for path_param in &self.params {
-            if path_param.alias == name || path_param.name == name {
-                return Some(path_param);
-            }
-        }
-        None
+        self.params
+            .iter()
+            .find(|path_param| path_param.alias == name || path_param.name == name)
     }
 
     fn get_status_code(&self, status_code: &str) -> Option<StatusCode> {
@@ -263,35 +297,27 @@ impl Project {
                     code: code.code.to_string(),
                     description: code.description.to_string(),
                     is_retryable: code.is_retryable,
+                    line: code.line,
+                    column: code.column,
                 };
                 return Some(project_status);
             }
         }
-        match status_code {
-            "200" => Some(StatusCode::new(status_code, "Ok")),
-            "201" => Some(StatusCode::new(status_code, "Created")),
-            "202" => Some(StatusCode::new(status_code, "Accepted")),
-            "204" => Some(StatusCode::new(status_code, "No Content")),
-            "400" => Some(StatusCode::new(status_code, "Bad Request")),
-            "401" => Some(StatusCode::new(status_code, "Unauthorized")),
-            "403" => Some(StatusCode::new(status_code, "Forbidden")),
-            "404" => Some(StatusCode::new(status_code, "Not Found")),
-            "405" => Some(StatusCode::new(status_code, "Method Not Allowed")),
-            "406" => Some(StatusCode::new(status_code, "Not Acceptable")),
-            "408" => Some(StatusCode::new(status_code, "Request Timeout")),
-            "413" => Some(StatusCode::new(status_code, "Payload Too Large")),
-            "415" => Some(StatusCode::new(status_code, "Unsupported Media Type")),
-            "417" => Some(StatusCode::new(status_code, "Expectation Failed")),
-            "418" => Some(StatusCode::new(status_code, "I'm a teapot")),
-            "424" => Some(StatusCode::new(status_code, "Failed Dependency")),
-            "429" => Some(StatusCode::new(status_code, "Too Many Requests")),
-            "500" => Some(StatusCode::new(status_code, "Internal Server Error")),
-            "501" => Some(StatusCode::new(status_code, "Not Implemented")),
-            "502" => Some(StatusCode::new(status_code, "Bad Gateway")),
-            "503" => Some(StatusCode::new(status_code, "Service Unavailable")),
-            "504" => Some(StatusCode::new(status_code, "Gateway Timeout")),
-            _ => None,
+        status_description(status_code).map(|description| StatusCode::new(status_code, description))
+    }
+
+    /// Declared operations in path order with their verb and path, each path lists them
+    /// as `APIDefinition::operations` does
+    pub fn operations(&self) -> Vec<(&'static str, &String, &APIConfiguration)> {
+        let mut paths: Vec<&String> = self.endpoints.keys().filter(|p| !p.is_empty()).collect();
+        paths.sort_by_key(|path| (self.endpoints[*path].position, path.to_owned()));
+        let mut operations = vec![];
+        for path in paths {
+            for (verb, config) in self.endpoints[path].operations() {
+                operations.push((verb, path, config));
+            }
         }
+        operations
     }
 
     /// Returns the expanded headers associated to a string of tokens
@@ -381,11 +407,44 @@ impl ProjectArgument {
             required,
             default_value: default_value.to_string(),
             description: "".to_string(),
+            constraints: Constraints::default(),
+            line: 0,
+            column: 0,
         }
     }
 }
 
-fn parse_argument(pair: Pair<Rule>) -> ProjectArgument {
+/// Description of the well known status codes, used when the API does not declare them
+pub fn status_description(code: &str) -> Option<&'static str> {
+    match code {
+        "200" => Some("Ok"),
+        "201" => Some("Created"),
+        "202" => Some("Accepted"),
+        "204" => Some("No Content"),
+        "400" => Some("Bad Request"),
+        "401" => Some("Unauthorized"),
+        "403" => Some("Forbidden"),
+        "404" => Some("Not Found"),
+        "405" => Some("Method Not Allowed"),
+        "406" => Some("Not Acceptable"),
+        "408" => Some("Request Timeout"),
+        "413" => Some("Payload Too Large"),
+        "415" => Some("Unsupported Media Type"),
+        "417" => Some("Expectation Failed"),
+        "418" => Some("I'm a teapot"),
+        "424" => Some("Failed Dependency"),
+        "429" => Some("Too Many Requests"),
+        "500" => Some("Internal Server Error"),
+        "501" => Some("Not Implemented"),
+        "502" => Some("Bad Gateway"),
+        "503" => Some("Service Unavailable"),
+        "504" => Some("Gateway Timeout"),
+        _ => None,
+    }
+}
+
+/// Item of a shared section, constraint values that do not fit their keyword are an error
+fn parse_argument(pair: Pair<Rule>) -> Result<ProjectArgument, String> {
     let mut arg = ProjectArgument {
         name: "".to_string(),
         description: "".to_string(),
@@ -393,11 +452,17 @@ fn parse_argument(pair: Pair<Rule>) -> ProjectArgument {
         alias: "".to_string(),
         required: false,
         default_value: "".to_string(),
+        constraints: Constraints::default(),
+        line: 0,
+        column: 0,
     };
     for arg_pair in pair.into_inner() {
         match arg_pair.as_rule() {
             Rule::ident => {
                 arg.name = arg_pair.as_str().to_owned();
+                let (line, column) = arg_pair.as_span().start_pos().line_col();
+                arg.line = line;
+                arg.column = column;
             }
             Rule::options => {
                 for opt in arg_pair.into_inner() {
@@ -407,10 +472,8 @@ fn parse_argument(pair: Pair<Rule>) -> ProjectArgument {
                             let mut value = String::new();
                             for k in opt.into_inner() {
                                 match k.as_rule() {
-                                    Rule::pair_modifiers => {
-                                        if k.as_str() == "alias" {
-                                            key = PairModifiers::Alias;
-                                        }
+                                    Rule::pair_modifiers if k.as_str() == "alias" => {
+                                        key = PairModifiers::Alias;
                                     }
                                     Rule::ident => {
                                         value = k.as_str().to_owned();
@@ -424,10 +487,8 @@ fn parse_argument(pair: Pair<Rule>) -> ProjectArgument {
                                 arg.alias = value;
                             }
                         }
-                        Rule::single_modifiers => {
-                            if opt.as_str() == "required" {
-                                arg.required = true;
-                            }
+                        Rule::single_modifiers if opt.as_str() == "required" => {
+                            arg.required = true;
                         }
                         Rule::default_value => {
                             for dv_inner in opt.into_inner() {
@@ -436,6 +497,28 @@ fn parse_argument(pair: Pair<Rule>) -> ProjectArgument {
                                 }
                             }
                         }
+                        Rule::constraint => {
+                            let mut keyword = "";
+                            for c_inner in opt.into_inner() {
+                                match c_inner.as_rule() {
+                                    Rule::constraint_name => {
+                                        keyword = c_inner.as_str();
+                                    }
+                                    Rule::constraint_value => {
+                                        let (line, _) = c_inner.as_span().start_pos().line_col();
+                                        arg.constraints
+                                            .set(keyword, &normalize_parsed(c_inner.as_str()))
+                                            .map_err(|e| format!("{} (line {})", e, line))?;
+                                    }
+                                    Rule::unique_items => {
+                                        arg.constraints.unique_items = true;
+                                    }
+                                    _ => {
+                                        // ignore
+                                    }
+                                }
+                            }
+                        }
                         _ => {
                             // println!("skipped option {}", opt);
                         }
@@ -464,7 +547,7 @@ fn parse_argument(pair: Pair<Rule>) -> ProjectArgument {
             }
         }
     }
-    arg
+    Ok(arg)
 }
 
 fn parse_group(pair: Pair<Rule>) -> ArgumentGroup {
@@ -492,20 +575,12 @@ fn parse_group(pair: Pair<Rule>) -> ArgumentGroup {
     arg_group
 }
 
-fn parse_project_arguments(pair: Pair<Rule>) -> Vec<ProjectArgument> {
+fn parse_project_arguments(pair: Pair<Rule>) -> Result<Vec<ProjectArgument>, String> {
     let mut args = Vec::new();
     for inner_pair in pair.into_inner() {
-        let arg = parse_argument(inner_pair);
-        args.push(arg);
-    }
-    args
-}
-
-fn get_file_content(file_name: String) -> Result<String, String> {
-    match fs::read_to_string(file_name) {
-        Ok(content) => Ok(content),
-        Err(e) => Err(format!("{}", e)),
+        args.push(parse_argument(inner_pair)?);
     }
+    Ok(args)
 }
 
 /// status_code rule parser
@@ -514,6 +589,8 @@ fn parse_status_code(pair: Pair<Rule>) -> StatusCode {
         code: "0".to_string(),
         description: "".to_string(),
         is_retryable: false,
+        line: 0,
+        column: 0,
     };
     for sub_rule in pair.into_inner() {
         match sub_rule.as_rule() {
@@ -522,11 +599,12 @@ fn parse_status_code(pair: Pair<Rule>) -> StatusCode {
             }
             Rule::status_code_n => {
                 status_code.code = sub_rule.as_str().to_owned();
+                let (line, column) = sub_rule.as_span().start_pos().line_col();
+                status_code.line = line;
+                status_code.column = column;
             }
-            Rule::status_codes_options => {
-                if let " retryable" = sub_rule.as_str() {
-                    status_code.is_retryable = true;
-                }
+            Rule::status_codes_options if sub_rule.as_str().trim() == "retryable" => {
+                status_code.is_retryable = true;
             }
             _ => {
                 // println!("ignoring sub rule {:?}", sub_rule);
@@ -551,11 +629,16 @@ fn parse_api_operation(pair: Pair<Rule>, project: &Project) -> (HttpMethod, APIC
         example: String::new(),
         request_model: String::new(),
         response_model: String::new(),
+        line: 0,
+        column: 0,
     };
     let mut current_method = HttpMethod::Unknown;
     for api_pair in pair.into_inner() {
         match api_pair.as_rule() {
             Rule::http_verb => {
+                let (line, column) = api_pair.as_span().start_pos().line_col();
+                definition.line = line;
+                definition.column = column;
                 match api_pair.as_str().to_ascii_lowercase().as_str() {
                     "get" => {
                         current_method = HttpMethod::Get;
@@ -692,35 +775,31 @@ fn parse_api_operation(pair: Pair<Rule>, project: &Project) -> (HttpMethod, APIC
                         Rule::api_status_codes => {
                             // parse group
                             for inner in param.into_inner() {
-                                match inner.as_rule() {
-                                    Rule::word_list => {
-                                        for status_component in inner.into_inner() {
-                                            match status_component.as_rule() {
-                                                Rule::ident => {
-                                                    let code =
-                                                        normalize_parsed(status_component.as_str());
-                                                    if !code.is_empty() {
-                                                        definition.status_codes.push(code);
-                                                    }
+                                if let Rule::word_list = inner.as_rule() {
+                                    for status_component in inner.into_inner() {
+                                        match status_component.as_rule() {
+                                            Rule::ident => {
+                                                let code =
+                                                    normalize_parsed(status_component.as_str());
+                                                if !code.is_empty() {
+                                                    definition.status_codes.push(code);
                                                 }
-                                                Rule::group_reference => {
-                                                    let name =
-                                                        status_component.into_inner().as_str();
-                                                    let values =
-                                                        project.spread_group("status_codes", name);
-                                                    for v in values {
-                                                        definition
-                                                            .status_codes
-                                                            .push(normalize_parsed(v.as_str()));
-                                                    }
-                                                }
-                                                _ => {
-                                                    //
+                                            }
+                                            Rule::group_reference => {
+                                                let name = status_component.into_inner().as_str();
+                                                let values =
+                                                    project.spread_group("status_codes", name);
+                                                for v in values {
+                                                    definition
+                                                        .status_codes
+                                                        .push(normalize_parsed(v.as_str()));
                                                 }
                                             }
+                                            _ => {
+                                                //
+                                            }
                                         }
                                     }
-                                    _ => {}
                                 }
                             }
                         }
@@ -752,6 +831,8 @@ fn parse_api(pair: Pair<Rule>, at_position: isize, project: &Project) -> APIWrap
             put: None,
             delete: None,
             patch: None,
+            line: 0,
+            column: 0,
         },
     };
     let mut current_endpoint = String::new();
@@ -759,6 +840,11 @@ fn parse_api(pair: Pair<Rule>, at_position: isize, project: &Project) -> APIWrap
         match api_sub_rule.as_rule() {
             Rule::path => {
                 for sub_path in api_sub_rule.into_inner() {
+                    if current_endpoint.is_empty() {
+                        let (line, column) = sub_path.as_span().start_pos().line_col();
+                        wrapper.definition.line = line;
+                        wrapper.definition.column = column;
+                    }
                     current_endpoint += sub_path.as_str();
                 }
                 wrapper.endpoint = current_endpoint.to_owned();
@@ -804,13 +890,17 @@ fn normalize_parsed(source: &str) -> String {
 }
 
 /// Parses the top level language keywords
-fn parse_value(pair: Pair<Rule>, current_position: isize, project: &mut Project) {
+fn parse_value(
+    pair: Pair<Rule>,
+    current_position: isize,
+    project: &mut Project,
+) -> Result<(), String> {
     let mut current_api_index = current_position;
     match pair.as_rule() {
         Rule::api_file => {
-            pair.into_inner().for_each(|local_pair| {
-                parse_value(local_pair, current_api_index, project);
-            });
+            for local_pair in pair.into_inner() {
+                parse_value(local_pair, current_api_index, project)?;
+            }
         }
         Rule::spec_header => {
             for p in pair.into_inner() {
@@ -835,7 +925,7 @@ fn parse_value(pair: Pair<Rule>, current_position: isize, project: &mut Project)
                 if n.is_empty() {
                     n = p.as_str().to_owned();
                 }
-                let args = parse_project_arguments(p);
+                let args = parse_project_arguments(p)?;
                 match n.as_str() {
                     "headers:" => {
                         project.headers = args;
@@ -862,7 +952,7 @@ fn parse_value(pair: Pair<Rule>, current_position: isize, project: &mut Project)
         Rule::apis => {
             for api in pair.into_inner() {
                 let wrapped_api = parse_api(api, current_api_index, project);
-                current_api_index+=1;
+                current_api_index += 1;
                 project
                     .endpoints
                     .insert(wrapped_api.endpoint, wrapped_api.definition);
@@ -901,9 +991,11 @@ fn parse_value(pair: Pair<Rule>, current_position: isize, project: &mut Project)
             // println!("Top level rule ignored: {:?}", pair);
         }
     }
+    Ok(())
 }
 
 #[test]
+#[allow(clippy::useless_vec, clippy::explicit_counter_loop)]
 fn test_item_parser() {
     let valid_expressions = vec![
         " x-my-auth string alias auth required (default_value): \"It does something\"\n",
@@ -920,9 +1012,26 @@ fn test_item_parser() {
     let mut current_case = 0;
     for expr in valid_expressions {
         let mut pair = ApishParser::parse(Rule::item, expr).unwrap();
-        let arg = parse_argument(pair.next().unwrap());
+        let arg = parse_argument(pair.next().unwrap()).unwrap();
         let expected = expected_args.get(current_case).unwrap();
         current_case += 1;
         assert_eq!(&arg, expected);
     }
 }
+
+#[test]
+fn test_item_constraints() {
+    let expr =
+        " zip string required minLength(5) maxLength(5) pattern(\"^[0-9]+$\"): \"Postal code\"\n";
+    let mut pair = ApishParser::parse(Rule::item, expr).unwrap();
+    let arg = parse_argument(pair.next().unwrap()).unwrap();
+    assert!(arg.required);
+    assert_eq!(arg.constraints.min_length, Some(5));
+    assert_eq!(arg.constraints.max_length, Some(5));
+    assert_eq!(arg.constraints.pattern, Some("^[0-9]+$".to_string()));
+    assert_eq!(arg.description, "Postal code");
+
+    let api = "title: \"Pets\"\nversion: \"1.0.0\"\nquery:\n  name string minLength(-1): \"Name\"\n\napis:\n  /pets:\n    get: \"Lists pets\"\n";
+    let error = Project::new_from_sources(api, None, None).unwrap_err();
+    assert_eq!(error, "Invalid value `-1` for `minLength` (line 4)");
+}
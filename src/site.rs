@@ -0,0 +1,530 @@
+//! Static HTML documentation site of a `Project`: an index, a page per tag, a models
+//! reference, and shared `apish.css` / `apish.js` files with the search index. Pages
+//! only link to each other so the site works from any static server or the file system
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+use serde_json::{json, Value};
+
+use crate::api_spec::get_mime_types;
+use crate::markdown::{
+    anchor, fields_table, has_tag, model_anchor, tags, type_name, yes, Markup, UNTAGGED,
+};
+use crate::models::ProjectModel;
+use crate::project::{APIConfiguration, Project, ProjectArgument};
+
+const MODELS_PAGE: &str = "models.html";
+
+const STYLE: &str = "body { margin: 0; font-family: -apple-system, Segoe UI, Helvetica, Arial, sans-serif; color: #222; display: flex; }
+nav { width: 280px; min-height: 100vh; padding: 16px; background: #f5f5f7; box-sizing: border-box; flex-shrink: 0; }
+nav ul { list-style: none; padding-left: 0; }
+nav ul ul { padding-left: 12px; font-size: 0.9em; }
+nav input { width: 100%; padding: 6px; box-sizing: border-box; }
+main { padding: 16px 32px; max-width: 960px; flex-grow: 1; }
+a { color: #0b62c4; text-decoration: none; }
+a:hover { text-decoration: underline; }
+table { border-collapse: collapse; margin: 8px 0 16px; }
+th, td { border: 1px solid #ddd; padding: 4px 8px; text-align: left; vertical-align: top; }
+th { background: #f5f5f7; }
+code, pre { font-family: Menlo, Consolas, monospace; font-size: 0.9em; }
+pre { background: #272822; color: #f8f8f2; padding: 12px; overflow-x: auto; }
+section.operation { border-top: 1px solid #ddd; padding-top: 8px; }
+.verb { display: inline-block; min-width: 56px; padding: 2px 6px; border-radius: 3px; color: white; text-align: center; background: #666; }
+.verb.get { background: #2f8132; } .verb.post { background: #186fb5; } .verb.put { background: #95507c; }
+.verb.delete { background: #cc3333; } .verb.patch { background: #b5691c; }
+.tabs button { border: 1px solid #ddd; background: white; padding: 4px 10px; cursor: pointer; }
+.tabs button.active { background: #0b62c4; color: white; }
+.deprecated { color: #cc3333; }
+";
+
+const SCRIPT: &str = "function apishSearch(query) {
+  var results = document.getElementById('search-results');
+  results.innerHTML = '';
+  query = query.toLowerCase().trim();
+  if (!query) { return; }
+  APISH_INDEX.filter(function (entry) {
+    return (entry.title + ' ' + entry.text).toLowerCase().indexOf(query) >= 0;
+  }).slice(0, 20).forEach(function (entry) {
+    var item = document.createElement('li');
+    var link = document.createElement('a');
+    link.href = entry.url;
+    link.textContent = entry.title;
+    item.appendChild(link);
+    results.appendChild(item);
+  });
+}
+
+function apishExample(id, index) {
+  document.querySelectorAll('[data-example=\"' + id + '\"]').forEach(function (pane) {
+    pane.hidden = pane.getAttribute('data-index') !== String(index);
+  });
+  document.querySelectorAll('[data-example-tab=\"' + id + '\"]').forEach(function (tab) {
+    tab.classList.toggle('active', tab.getAttribute('data-index') === String(index));
+  });
+}
+";
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Page of every tag, a number is appended when tags share the same file name
+fn tag_pages(project: &Project) -> HashMap<String, String> {
+    let mut pages = HashMap::new();
+    let mut taken = HashSet::new();
+    for tag in tags(project) {
+        let base = match anchor(&tag).as_str() {
+            "" => "tag".to_owned(),
+            id => format!("tag-{}", id),
+        };
+        let mut name = format!("{}.html", base);
+        let mut count = 1;
+        while !taken.insert(name.to_owned()) {
+            count += 1;
+            name = format!("{}-{}.html", base, count);
+        }
+        pages.insert(tag, name);
+    }
+    pages
+}
+
+struct Html;
+
+impl Markup for Html {
+    fn code(text: &str) -> String {
+        format!("<code>{}</code>", escape(text))
+    }
+
+    fn text(text: &str) -> String {
+        escape(text)
+    }
+
+    fn model_link(name: &str) -> String {
+        format!(
+            "<a href=\"{}#{}\">{}</a>",
+            MODELS_PAGE,
+            model_anchor(name),
+            escape(name)
+        )
+    }
+
+    fn table(out: &mut String, headers: &[&str], rows: &[Vec<String>]) {
+        let _ = writeln!(
+            out,
+            "<table>\n<tr><th>{}</th></tr>",
+            headers.join("</th><th>")
+        );
+        for row in rows {
+            let _ = writeln!(out, "<tr><td>{}</td></tr>", row.join("</td><td>"));
+        }
+        out.push_str("</table>\n");
+    }
+}
+
+fn json_block(value: &Value) -> String {
+    let text = match value {
+        Value::String(text) => text.to_owned(),
+        _ => serde_json::to_string_pretty(value).unwrap_or_default(),
+    };
+    format!("<pre>{}</pre>\n", escape(&text))
+}
+
+fn examples_viewer(out: &mut String, project: &Project, id: &str, config: &APIConfiguration) {
+    let examples = match project.examples.get(&config.example) {
+        Some(examples) if !examples.is_empty() => examples,
+        _ => return,
+    };
+    out.push_str("<h4>Examples</h4>\n<div class=\"tabs\">");
+    for (index, example) in examples.iter().enumerate() {
+        let label = example
+            .description
+            .clone()
+            .unwrap_or_else(|| format!("Example {}", index + 1));
+        let _ = write!(
+            out,
+            "<button data-example-tab=\"{id}\" data-index=\"{index}\" class=\"{active}\" onclick=\"apishExample('{id}', {index})\">{label}</button>",
+            id = id,
+            index = index,
+            active = if index == 0 { "active" } else { "" },
+            label = escape(&label)
+        );
+    }
+    out.push_str("</div>\n");
+    for (index, example) in examples.iter().enumerate() {
+        let _ = writeln!(
+            out,
+            "<div data-example=\"{}\" data-index=\"{}\"{}>",
+            id,
+            index,
+            if index == 0 { "" } else { " hidden" }
+        );
+        if let Some(request) = &example.request {
+            out.push_str("<p>Request</p>\n");
+            out.push_str(&json_block(request));
+        }
+        if let Some(response) = &example.response {
+            out.push_str("<p>Response</p>\n");
+            out.push_str(&json_block(response));
+        }
+        out.push_str("</div>\n");
+    }
+}
+
+fn operation_html(
+    out: &mut String,
+    project: &Project,
+    verb: &str,
+    path: &str,
+    config: &APIConfiguration,
+) {
+    let id = anchor(&format!("{} {}", verb, path));
+    let _ = writeln!(
+        out,
+        "<section class=\"operation\" id=\"{}\">\n<h3><span class=\"verb {}\">{}</span> <code>{}</code></h3>",
+        id,
+        verb,
+        verb.to_uppercase(),
+        escape(path)
+    );
+    if !config.description.is_empty() {
+        let _ = writeln!(out, "<p>{}</p>", escape(&config.description));
+    }
+    let mut details = vec![];
+    if !config.operation.is_empty() {
+        details.push(format!(
+            "Operation: <code>{}</code>",
+            escape(&config.operation)
+        ));
+    }
+    let produces = get_mime_types(&config.produces);
+    if !produces.is_empty() {
+        details.push(format!("Produces: <code>{}</code>", produces.join(", ")));
+    }
+    let consumes = get_mime_types(&config.consumes);
+    if !consumes.is_empty() {
+        details.push(format!("Consumes: <code>{}</code>", consumes.join(", ")));
+    }
+    if !details.is_empty() {
+        let _ = writeln!(out, "<p>{}</p>", details.join(" &middot; "));
+    }
+    if !config.use_cases.is_empty() {
+        out.push_str("<h4>Use cases</h4>\n<ul>\n");
+        for use_case in &config.use_cases {
+            let _ = writeln!(out, "<li>{}</li>", escape(use_case));
+        }
+        out.push_str("</ul>\n");
+    }
+
+    let mut arguments: Vec<(&str, &ProjectArgument)> = vec![];
+    for param in project.get_path_params(&config.path_params) {
+        arguments.push(("path", param));
+    }
+    for query in project.get_query_strings(&config.query_string) {
+        arguments.push(("query", query));
+    }
+    for header in project.get_headers(&config.headers) {
+        arguments.push(("header", header));
+    }
+    if !arguments.is_empty() {
+        out.push_str("<h4>Parameters</h4>\n<table>\n<tr><th>Name</th><th>In</th><th>Type</th><th>Required</th><th>Default</th><th>Description</th></tr>\n");
+        for (location, argument) in arguments {
+            let _ = writeln!(
+                out,
+                "<tr><td><code>{}</code></td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape(&argument.name),
+                location,
+                argument.data_type.as_str(),
+                yes(argument.required || location == "path"),
+                escape(&argument.default_value),
+                escape(&argument.description)
+            );
+        }
+        out.push_str("</table>\n");
+    }
+
+    let codes = project.get_status_codes(&config.status_codes);
+    if !codes.is_empty() {
+        out.push_str("<h4>Status codes</h4>\n<table>\n<tr><th>Code</th><th>Description</th><th>Retryable</th></tr>\n");
+        for code in &codes {
+            let _ = writeln!(
+                out,
+                "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape(&code.code),
+                escape(&code.description),
+                yes(code.is_retryable)
+            );
+        }
+        out.push_str("</table>\n");
+    }
+    for (title, model) in [
+        ("Request", &config.request_model),
+        ("Response", &config.response_model),
+    ] {
+        if model.is_empty() {
+            continue;
+        }
+        let _ = writeln!(
+            out,
+            "<h4>{} {}</h4>",
+            title,
+            type_name::<Html>(project.models.as_ref(), model)
+        );
+        if let Some(models) = &project.models {
+            fields_table::<Html>(out, models, model);
+        }
+    }
+    examples_viewer(out, project, &id, config);
+    out.push_str("</section>\n");
+}
+
+fn models_html(models: &ProjectModel) -> String {
+    let mut out = String::from("<h1>Models</h1>\n");
+    let mut names: Vec<&String> = models
+        .entities
+        .keys()
+        .chain(models.enums.keys())
+        .chain(models.unions.keys())
+        .collect();
+    names.sort();
+    for name in names {
+        let _ = writeln!(
+            out,
+            "<section id=\"{}\">\n<h2>{}</h2>",
+            model_anchor(name),
+            escape(name)
+        );
+        if let Some(entity) = models.entities.get(name) {
+            if !entity.bases.is_empty() {
+                let bases: Vec<String> = entity
+                    .bases
+                    .iter()
+                    .map(|base| type_name::<Html>(Some(models), base))
+                    .collect();
+                let _ = writeln!(out, "<p>Extends {}</p>", bases.join(", "));
+            }
+            fields_table::<Html>(&mut out, models, name);
+        }
+        if let Some(definition) = models.enums.get(name) {
+            out.push_str("<table>\n<tr><th>Value</th><th>Name</th><th>Description</th></tr>\n");
+            for member in &definition.members {
+                let deprecated = match member.deprecated {
+                    true => "<span class=\"deprecated\">Deprecated</span> ",
+                    false => "",
+                };
+                let _ = writeln!(
+                    out,
+                    "<tr><td><code>{}</code></td><td>{}</td><td>{}{}</td></tr>",
+                    escape(&member.value),
+                    escape(&member.name),
+                    deprecated,
+                    escape(&member.description)
+                );
+            }
+            out.push_str("</table>\n");
+        }
+        if let Some(union) = models.unions.get(name) {
+            let variants: Vec<String> = union
+                .variants
+                .iter()
+                .map(|variant| type_name::<Html>(Some(models), variant))
+                .collect();
+            let _ = write!(out, "<p>One of {}", variants.join(", "));
+            if !union.discriminator.is_empty() {
+                let _ = write!(
+                    out,
+                    ", told apart by <code>{}</code>",
+                    escape(&union.discriminator)
+                );
+            }
+            out.push_str("</p>\n");
+        }
+        out.push_str("</section>\n");
+    }
+    out
+}
+
+/// Entries the search box looks into, as `apish.js` content
+fn search_script(project: &Project, pages: &HashMap<String, String>) -> String {
+    let mut entries = vec![];
+    for (verb, path, config) in project.operations() {
+        let tag = &pages[config.tags.first().map_or(UNTAGGED, |t| t.as_str())];
+        entries.push(json!({
+            "title": format!("{} {}", verb.to_uppercase(), path),
+            "text": format!("{} {}", config.operation, config.description),
+            "url": format!("{}#{}", tag, anchor(&format!("{} {}", verb, path))),
+        }));
+    }
+    if let Some(models) = &project.models {
+        let mut names: Vec<&String> = models
+            .entities
+            .keys()
+            .chain(models.enums.keys())
+            .chain(models.unions.keys())
+            .collect();
+        names.sort();
+        for name in names {
+            let fields = models.entities.get(name).map_or(String::new(), |entity| {
+                let names: Vec<&str> = entity
+                    .ordered_fields()
+                    .iter()
+                    .map(|f| f.identifier.as_str())
+                    .collect();
+                names.join(" ")
+            });
+            entries.push(json!({
+                "title": name,
+                "text": fields,
+                "url": format!("{}#{}", MODELS_PAGE, model_anchor(name)),
+            }));
+        }
+    }
+    // `</` would end an inline script, escaped in case the file gets inlined
+    let index = Value::Array(entries).to_string().replace("</", "<\\/");
+    format!("var APISH_INDEX = {};\n\n{}", index, SCRIPT)
+}
+
+fn page(project: &Project, pages: &HashMap<String, String>, title: &str, body: &str) -> String {
+    let mut nav = String::new();
+    let _ = writeln!(
+        nav,
+        "<h2><a href=\"index.html\">{}</a></h2>",
+        escape(&project.title)
+    );
+    nav.push_str("<input type=\"search\" placeholder=\"Search\" oninput=\"apishSearch(this.value)\">\n<ul id=\"search-results\"></ul>\n<ul>\n");
+    let operations = project.operations();
+    for tag in tags(project) {
+        let _ = writeln!(
+            nav,
+            "<li><a href=\"{}\">{}</a>\n<ul>",
+            pages[&tag],
+            escape(&tag)
+        );
+        for (verb, path, config) in &operations {
+            if has_tag(config, &tag) {
+                let _ = writeln!(
+                    nav,
+                    "<li><a href=\"{}#{}\">{} {}</a></li>",
+                    pages[&tag],
+                    anchor(&format!("{} {}", verb, path)),
+                    verb.to_uppercase(),
+                    escape(path)
+                );
+            }
+        }
+        nav.push_str("</ul></li>\n");
+    }
+    if project.models.is_some() {
+        let _ = writeln!(nav, "<li><a href=\"{}\">Models</a></li>", MODELS_PAGE);
+    }
+    nav.push_str("</ul>\n");
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{} - {}</title>\n<link rel=\"stylesheet\" href=\"apish.css\">\n<script src=\"apish.js\"></script>\n</head>\n<body>\n<nav>\n{}</nav>\n<main>\n{}</main>\n</body>\n</html>\n",
+        escape(title),
+        escape(&project.title),
+        nav,
+        body
+    )
+}
+
+/// Files of the site with their content, paths are relative to the output directory
+pub fn render(project: &Project) -> Vec<(String, String)> {
+    let operations = project.operations();
+    let pages = tag_pages(project);
+    let mut files = vec![];
+
+    let mut index = format!("<h1>{}</h1>\n", escape(&project.title));
+    if !project.version.is_empty() {
+        let _ = writeln!(index, "<p>Version {}</p>", escape(&project.version));
+    }
+    for tag in tags(project) {
+        let mut body = format!("<h1>{}</h1>\n", escape(&tag));
+        let _ = writeln!(
+            index,
+            "<h2><a href=\"{}\">{}</a></h2>\n<table>",
+            pages[&tag],
+            escape(&tag)
+        );
+        for (verb, path, config) in &operations {
+            if !has_tag(config, &tag) {
+                continue;
+            }
+            let _ = writeln!(
+                index,
+                "<tr><td><span class=\"verb {}\">{}</span></td><td><a href=\"{}#{}\"><code>{}</code></a></td><td>{}</td></tr>",
+                verb,
+                verb.to_uppercase(),
+                pages[&tag],
+                anchor(&format!("{} {}", verb, path)),
+                escape(path),
+                escape(&config.description)
+            );
+            operation_html(&mut body, project, verb, path, config);
+        }
+        index.push_str("</table>\n");
+        files.push((pages[&tag].to_owned(), page(project, &pages, &tag, &body)));
+    }
+    if let Some(models) = &project.models {
+        let _ = writeln!(index, "<h2><a href=\"{}\">Models</a></h2>", MODELS_PAGE);
+        files.push((
+            MODELS_PAGE.to_owned(),
+            page(project, &pages, "Models", &models_html(models)),
+        ));
+    }
+    files.insert(
+        0,
+        (
+            "index.html".to_owned(),
+            page(project, &pages, "Index", &index),
+        ),
+    );
+    files.push(("apish.css".to_owned(), STYLE.to_owned()));
+    files.push(("apish.js".to_owned(), search_script(project, &pages)));
+    files
+}
+
+#[test]
+fn test_render_site() {
+    let api = "title: \"Pets\"\n\napis:\n  /pets/{id}:\n    get: \"Finds a pet\"\n      tags: pets\n      response: Pet\n      example: pet\n  /health:\n    get: \"Health <ok>\"\n";
+    let models = "struct Pet {\n name string\n kind Kind\n}\n\nenum Kind {\n DOG\n CAT\n}\n";
+    let examples = "{\"pet\": [{\"response\": {\"name\": \"rex\"}}, {\"description\": \"Cat\", \"response\": {\"name\": \"tom\"}}]}";
+    let project = Project::new_from_sources(api, Some(models), Some(examples)).unwrap();
+    let files = render(&project);
+    let names: Vec<&str> = files.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec![
+            "index.html",
+            "tag-pets.html",
+            "tag-other.html",
+            "models.html",
+            "apish.css",
+            "apish.js"
+        ]
+    );
+    let content = |name: &str| files.iter().find(|(n, _)| n == name).unwrap().1.clone();
+    let pets = content("tag-pets.html");
+    assert!(pets.contains("<section class=\"operation\" id=\"get-pets-id\">"));
+    assert!(pets.contains("<h4>Response <a href=\"models.html#model-pet\">Pet</a></h4>"));
+    assert!(pets.contains("onclick=\"apishExample('get-pets-id', 1)\">Cat</button>"));
+    assert!(pets.contains("<div data-example=\"get-pets-id\" data-index=\"1\" hidden>"));
+    assert!(content("tag-other.html").contains("Health &lt;ok&gt;"));
+    assert!(content("models.html").contains(
+        "<td><code>kind</code></td><td><a href=\"models.html#model-kind\">Kind</a></td>"
+    ));
+    let script = content("apish.js");
+    assert!(script.contains("\"url\":\"tag-pets.html#get-pets-id\""));
+    assert!(script.contains("\"url\":\"tag-other.html#get-health\""));
+    assert!(script.contains("\"url\":\"models.html#model-kind\""));
+    assert!(!files
+        .iter()
+        .any(|(_, c)| c.contains("http://") || c.contains("https://")));
+    let api = "title: \"Pets\"\n\napis:\n  /a:\n    get: \"A\"\n      tags: foo_bar\n  /b:\n    get: \"B\"\n      tags: foo-bar\n";
+    let project = Project::new_from_sources(api, None, None).unwrap();
+    let pages = tag_pages(&project);
+    assert_eq!(pages["foo_bar"], "tag-foo-bar.html");
+    assert_eq!(pages["foo-bar"], "tag-foo-bar-2.html");
+}
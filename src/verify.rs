@@ -0,0 +1,506 @@
+//! Contract tests: calls every operation of a `Project` on a running service and checks
+//! the answer against the declared status codes, `produces` and `response:` model
+//!
+//! Requests send every declared header, query item and path param with its default
+//! value, or a placeholder after its type, and the `request` of each example as body.
+//! Only `http://` base urls are supported
+
+use std::fmt;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::api_spec::get_mime_types;
+use crate::mock::EXAMPLE_HEADER;
+use crate::project::{APIConfiguration, DataType, Project, ProjectArgument};
+
+/// Request built for one operation and example
+#[derive(Debug)]
+pub struct Call<'a> {
+    pub name: String,
+    pub method: String,
+    /// path with params replaced and the query string
+    pub target: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+    pub config: &'a APIConfiguration,
+    pub expected: Option<&'a Value>,
+}
+
+#[derive(Debug)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub content_type: Option<String>,
+    pub body: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Case {
+    pub name: String,
+    pub method: String,
+    pub target: String,
+    pub status: Option<u16>,
+    /// seconds
+    pub time: f64,
+    pub failures: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub title: String,
+    pub base_url: String,
+    pub cases: Vec<Case>,
+}
+
+fn argument_value(argument: &ProjectArgument) -> String {
+    if !argument.default_value.is_empty() {
+        return argument.default_value.to_owned();
+    }
+    match argument.data_type {
+        DataType::Number => "1".to_owned(),
+        DataType::Boolean => "true".to_owned(),
+        _ => "sample".to_owned(),
+    }
+}
+
+fn encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+fn target(project: &Project, path: &str, config: &APIConfiguration) -> String {
+    let params = project.get_path_params(&config.path_params);
+    let segments: Vec<String> = path
+        .split('/')
+        .map(|segment| {
+            if !(segment.starts_with('{') && segment.ends_with('}')) {
+                return segment.to_owned();
+            }
+            let name = &segment[1..segment.len() - 1];
+            match params.iter().find(|p| p.name == name || p.alias == name) {
+                Some(param) => encode(&argument_value(param)),
+                None => "1".to_owned(),
+            }
+        })
+        .collect();
+    let query: Vec<String> = project
+        .get_query_strings(&config.query_string)
+        .iter()
+        .map(|item| format!("{}={}", encode(&item.name), encode(&argument_value(item))))
+        .collect();
+    if query.is_empty() {
+        return segments.join("/");
+    }
+    format!("{}?{}", segments.join("/"), query.join("&"))
+}
+
+/// One call per example of every operation, or a single call when it has none.
+/// `mock_examples` adds the header telling `apish mock` which example to answer with
+pub fn plan(project: &Project, mock_examples: bool) -> Vec<Call<'_>> {
+    let mut paths: Vec<&String> = project.endpoints.keys().filter(|p| !p.is_empty()).collect();
+    paths.sort_by_key(|path| project.endpoints[*path].position);
+    let mut calls = vec![];
+    for path in paths {
+        for (verb, config) in project.endpoints[path].operations() {
+            let method = verb.to_uppercase();
+            let mut name = format!("{} {}", method, path);
+            if !config.operation.is_empty() {
+                name = format!("{} ({})", name, config.operation);
+            }
+            let mut headers: Vec<(String, String)> = project
+                .get_headers(&config.headers)
+                .iter()
+                .map(|header| (header.name.to_owned(), argument_value(header)))
+                .collect();
+            let examples = project.examples.get(&config.example);
+            let count = examples.map_or(0, |examples| examples.len());
+            if let Some(mime_type) = get_mime_types(&config.consumes).first() {
+                headers.push(("Content-Type".to_owned(), mime_type.to_owned()));
+            }
+            for index in 0..count.max(1) {
+                let example = examples.and_then(|examples| examples.get(index));
+                let body = example
+                    .and_then(|e| e.request.as_ref())
+                    .map(|request| match request {
+                        Value::String(text) => text.to_owned(),
+                        _ => request.to_string(),
+                    });
+                let mut headers = headers.clone();
+                if mock_examples && count > 1 {
+                    headers.push((EXAMPLE_HEADER.to_owned(), index.to_string()));
+                }
+                calls.push(Call {
+                    name: match count > 1 {
+                        true => format!("{} #{}", name, index),
+                        false => name.to_owned(),
+                    },
+                    method: method.to_owned(),
+                    target: target(project, path, config),
+                    headers,
+                    body,
+                    config,
+                    expected: example.and_then(|e| e.response.as_ref()),
+                });
+            }
+        }
+    }
+    calls
+}
+
+/// Keys of `expected` missing from `actual` or holding another JSON type, values
+/// themselves may differ
+fn shape_mismatches(expected: &Value, actual: &Value, path: &str, mismatches: &mut Vec<String>) {
+    match (expected, actual) {
+        (Value::Null, _) => {}
+        (Value::Object(expected), Value::Object(actual)) => {
+            for (key, value) in expected {
+                let path = format!("{}.{}", path, key);
+                match actual.get(key) {
+                    Some(actual) => shape_mismatches(value, actual, &path, mismatches),
+                    None => mismatches.push(format!("{}: missing, present in the example", path)),
+                }
+            }
+        }
+        (Value::Array(expected), Value::Array(actual)) => {
+            if let (Some(expected), Some(actual)) = (expected.first(), actual.first()) {
+                shape_mismatches(expected, actual, &format!("{}[0]", path), mismatches);
+            }
+        }
+        (Value::Bool(_), Value::Bool(_))
+        | (Value::Number(_), Value::Number(_))
+        | (Value::String(_), Value::String(_)) => {}
+        _ => mismatches.push(format!(
+            "{}: {} does not match the example {}",
+            path, actual, expected
+        )),
+    }
+}
+
+/// Failures of a response to its call
+pub fn check(project: &Project, call: &Call, response: &HttpResponse) -> Vec<String> {
+    let mut failures = vec![];
+    let config = call.config;
+    let codes = project.get_status_codes(&config.status_codes);
+    let status = response.status.to_string();
+    if !codes.is_empty() && !codes.iter().any(|c| c.code == status) {
+        let declared: Vec<String> = codes.iter().map(|c| c.code.to_owned()).collect();
+        failures.push(format!(
+            "Status code {} is not declared ({})",
+            status,
+            declared.join(", ")
+        ));
+    }
+    let mime_types = get_mime_types(&config.produces);
+    let content_type = response
+        .content_type
+        .as_ref()
+        .and_then(|c| c.split(';').next())
+        .map(|c| c.trim().to_lowercase());
+    if !mime_types.is_empty() && !response.body.is_empty() {
+        match &content_type {
+            Some(content_type) if mime_types.contains(content_type) => {}
+            Some(content_type) => failures.push(format!(
+                "Content-Type {} is not produced ({})",
+                content_type,
+                mime_types.join(", ")
+            )),
+            None => failures.push("Content-Type is missing".to_owned()),
+        }
+    }
+    if !(200..300).contains(&response.status) {
+        return failures;
+    }
+    let model = match &project.models {
+        Some(models) if !config.response_model.is_empty() => Some(models),
+        _ => None,
+    };
+    if model.is_none() && call.expected.is_none() {
+        return failures;
+    }
+    let body: Value = match serde_json::from_str(&response.body) {
+        Ok(body) => body,
+        Err(e) => {
+            failures.push(format!("Body is not JSON {}", e));
+            return failures;
+        }
+    };
+    if let Some(models) = model {
+        failures.extend(models.validate_value(&config.response_model, &body));
+    }
+    if let Some(expected) = call.expected {
+        shape_mismatches(expected, &body, "$", &mut failures);
+    }
+    failures
+}
+
+/// `http://host:port/prefix` split into address, host header and path prefix
+fn parse_base_url(base_url: &str) -> Result<(String, String, String), String> {
+    let rest = match base_url.strip_prefix("http://") {
+        Some(rest) => rest,
+        None => {
+            return Err(format!(
+                "Only http:// base urls are supported, got {}",
+                base_url
+            ))
+        }
+    };
+    let (host, prefix) = match rest.find('/') {
+        Some(index) => (&rest[..index], rest[index..].trim_end_matches('/')),
+        None => (rest, ""),
+    };
+    if host.is_empty() {
+        return Err(format!("Missing host in {}", base_url));
+    }
+    let address = match host.contains(':') {
+        true => host.to_owned(),
+        false => format!("{}:80", host),
+    };
+    Ok((address, host.to_owned(), prefix.to_owned()))
+}
+
+fn decode_chunked(body: &[u8]) -> Vec<u8> {
+    let mut decoded = vec![];
+    let mut rest = body;
+    while let Some(end) = rest.windows(2).position(|w| w == b"\r\n") {
+        let size = String::from_utf8_lossy(&rest[..end]);
+        let size =
+            usize::from_str_radix(size.split(';').next().unwrap_or("").trim(), 16).unwrap_or(0);
+        let start = end + 2;
+        if size == 0 || start + size > rest.len() {
+            break;
+        }
+        decoded.extend_from_slice(&rest[start..start + size]);
+        rest = &rest[(start + size + 2).min(rest.len())..];
+    }
+    decoded
+}
+
+fn send(address: &str, host: &str, prefix: &str, call: &Call) -> Result<HttpResponse, String> {
+    let mut stream = TcpStream::connect(address).map_err(|e| format!("{}: {}", address, e))?;
+    stream
+        .set_read_timeout(Some(Duration::from_secs(30)))
+        .map_err(|e| e.to_string())?;
+    let body = call.body.as_deref().unwrap_or("");
+    let mut request = format!(
+        "{} {}{} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\nContent-Length: {}\r\n",
+        call.method,
+        prefix,
+        call.target,
+        host,
+        body.len()
+    );
+    for (name, value) in &call.headers {
+        request.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    request.push_str("\r\n");
+    request.push_str(body);
+    stream
+        .write_all(request.as_bytes())
+        .map_err(|e| e.to_string())?;
+    let mut raw = vec![];
+    stream.read_to_end(&mut raw).map_err(|e| e.to_string())?;
+
+    let head_end = raw
+        .windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .ok_or_else(|| "Invalid HTTP response".to_owned())?;
+    let head = String::from_utf8_lossy(&raw[..head_end]).to_string();
+    let mut lines = head.lines();
+    let status = lines
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|code| code.parse().ok())
+        .ok_or_else(|| "Invalid HTTP status line".to_owned())?;
+    let mut content_type = None;
+    let mut chunked = false;
+    for line in lines {
+        if let Some(index) = line.find(':') {
+            let name = line[..index].trim().to_lowercase();
+            let value = line[index + 1..].trim().to_owned();
+            if name == "content-type" {
+                content_type = Some(value);
+            } else if name == "transfer-encoding" && value.to_lowercase().contains("chunked") {
+                chunked = true;
+            }
+        }
+    }
+    let body = &raw[head_end + 4..];
+    let body = match chunked {
+        true => decode_chunked(body),
+        false => body.to_vec(),
+    };
+    Ok(HttpResponse {
+        status,
+        content_type,
+        body: String::from_utf8_lossy(&body).to_string(),
+    })
+}
+
+/// Calls every planned request on the service, one at a time
+pub fn verify(project: &Project, base_url: &str, mock_examples: bool) -> Result<Report, String> {
+    let (address, host, prefix) = parse_base_url(base_url)?;
+    let mut cases = vec![];
+    for call in plan(project, mock_examples) {
+        let start = Instant::now();
+        let result = send(&address, &host, &prefix, &call);
+        let time = start.elapsed().as_secs_f64();
+        let (status, failures) = match result {
+            Ok(response) => (Some(response.status), check(project, &call, &response)),
+            Err(e) => (None, vec![e]),
+        };
+        cases.push(Case {
+            name: call.name,
+            method: call.method,
+            target: call.target,
+            status,
+            time,
+            failures,
+        });
+    }
+    Ok(Report {
+        title: project.title.to_owned(),
+        base_url: base_url.to_owned(),
+        cases,
+    })
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl Report {
+    pub fn failed(&self) -> usize {
+        self.cases.iter().filter(|c| !c.failures.is_empty()).count()
+    }
+
+    pub fn to_junit(&self) -> String {
+        let time: f64 = self.cases.iter().map(|c| c.time).sum();
+        let title = escape_xml(&self.title);
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        xml.push_str(&format!(
+            "<testsuites tests=\"{}\" failures=\"{}\" time=\"{:.3}\">\n",
+            self.cases.len(),
+            self.failed(),
+            time
+        ));
+        xml.push_str(&format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" time=\"{:.3}\">\n",
+            title,
+            self.cases.len(),
+            self.failed(),
+            time
+        ));
+        for case in &self.cases {
+            xml.push_str(&format!(
+                "    <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\"",
+                escape_xml(&case.name),
+                title,
+                case.time
+            ));
+            if case.failures.is_empty() {
+                xml.push_str("/>\n");
+                continue;
+            }
+            xml.push_str(&format!(
+                ">\n      <failure message=\"{}\">{}</failure>\n    </testcase>\n",
+                escape_xml(&case.failures[0]),
+                escape_xml(&case.failures.join("\n"))
+            ));
+        }
+        xml.push_str("  </testsuite>\n</testsuites>\n");
+        xml
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for case in &self.cases {
+            let status = case.status.map_or("---".to_owned(), |s| s.to_string());
+            let result = match case.failures.is_empty() {
+                true => "PASS",
+                false => "FAIL",
+            };
+            writeln!(f, "{} {} {}", result, status, case.name)?;
+            for failure in &case.failures {
+                writeln!(f, "       {}", failure)?;
+            }
+        }
+        write!(
+            f,
+            "{} passed, {} failed",
+            self.cases.len() - self.failed(),
+            self.failed()
+        )
+    }
+}
+
+#[test]
+fn test_verify_checks() {
+    use crate::mock::{respond, MockRequest};
+
+    let api = "title: \"Pets\"\nheaders:\n  x-auth string required: \"Token\"\nparams:\n  id number: \"Pet id\"\nstatus_codes:\n  424 retryable: \"Try later\"\n\napis:\n  /pets/{id}:\n    get: \"Pet\"\n      operation: getPet\n      headers: x-auth\n      params: id\n      produces: json\n      status_codes: 200 424\n      response: Pet\n      example: pet\n";
+    let models = "struct Pet {\n name string\n age int\n}\n";
+    let examples = "{\"pet\": [{\"response\": {\"name\": \"rex\", \"age\": 3}}]}";
+    let project = Project::new_from_sources(api, Some(models), Some(examples)).unwrap();
+    let calls = plan(&project, false);
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].name, "GET /pets/{id} (getPet)");
+    assert_eq!(calls[0].target, "/pets/1");
+
+    let headers = calls[0].headers.iter().cloned().collect();
+    let mocked = respond(
+        &project,
+        &MockRequest::new("GET", &calls[0].target, headers),
+    );
+    let response = HttpResponse {
+        status: mocked.status,
+        content_type: Some(format!("{}; charset=utf-8", mocked.content_type)),
+        body: mocked.body,
+    };
+    assert!(check(&project, &calls[0], &response).is_empty());
+
+    let drifted = HttpResponse {
+        status: 201,
+        content_type: Some("text/plain".to_owned()),
+        body: "{\"name\": 7}".to_owned(),
+    };
+    assert_eq!(
+        check(&project, &calls[0], &drifted),
+        vec![
+            "Status code 201 is not declared (200, 424)",
+            "Content-Type text/plain is not produced (application/json)",
+            "$.name: 7 is not a string",
+            "$.age: missing, present in the example",
+            "$.name: 7 does not match the example \"rex\"",
+        ]
+    );
+
+    let report = Report {
+        title: project.title.to_owned(),
+        base_url: "http://localhost:8080".to_owned(),
+        cases: vec![Case {
+            name: calls[0].name.to_owned(),
+            method: "GET".to_owned(),
+            target: calls[0].target.to_owned(),
+            status: Some(201),
+            time: 0.0,
+            failures: check(&project, &calls[0], &drifted),
+        }],
+    };
+    let junit = report.to_junit();
+    assert!(junit.contains("<testsuite name=\"Pets\" tests=\"1\" failures=\"1\""));
+    assert!(junit.contains("<failure message=\"Status code 201 is not declared (200, 424)\">"));
+}
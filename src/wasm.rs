@@ -0,0 +1,154 @@
+//! WASM bindings, JavaScript receives every result as a JSON string
+
+use crate::models::{
+    get_models, Constraints, Diagnostic, Entity, Enum, ProjectModel, TypeKind, Union,
+};
+use crate::{compile, Sources};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+
+/// Projection of `models::ProjectModel` for JavaScript consumers, every list is sorted
+/// by name so the output is stable between calls
+#[derive(Serialize)]
+pub struct ModelsProject {
+    pub enums: Vec<Enum>,
+    pub unions: Vec<Union>,
+    pub entities: Vec<ModelEntity>,
+    /// unknown types and required reference cycles, the models are usable anyway
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Projection of `models::Field`
+#[derive(Serialize)]
+pub struct ModelEntityField {
+    pub name: String,
+    pub data_type: String,
+    /// whether `data_type` is a primitive, enum, struct, union or unknown
+    pub type_kind: TypeKind,
+    pub description: String,
+    /// use to represent arrays, for maps it applies to the values
+    pub is_array: bool,
+    pub is_map: bool,
+    pub map_key_type: String,
+    pub is_optional: bool,
+    pub is_nullable: bool,
+    pub is_required: bool,
+    pub default_value: String,
+    /// `example` tag typed after the field: number, bool, array or string
+    pub example: Option<Value>,
+    pub markers: Vec<String>,
+    pub tags: HashMap<String, String>,
+    /// values of the enum used as `data_type`, empty for any other type
+    pub allowed_values: Vec<String>,
+    pub constraints: Constraints,
+    /// struct declaring the field when it comes from a base, empty for own fields
+    pub inherited_from: String,
+}
+
+/// Projection of `models::Entity`, fields keep their declaration order
+#[derive(Serialize)]
+pub struct ModelEntity {
+    pub name: String,
+    pub bases: Vec<String>,
+    pub fields: Vec<ModelEntityField>,
+}
+
+fn to_summarized_entity(from: &Entity) -> ModelEntity {
+    let mut new_fields = Vec::new();
+    for field in from.ordered_fields() {
+        let f = ModelEntityField {
+            name: field.identifier.to_owned(),
+            data_type: field.data_type.to_owned(),
+            type_kind: field.type_kind.clone(),
+            description: field.description.to_owned(),
+            is_array: field.is_array,
+            is_map: field.is_map,
+            map_key_type: field.map_key_type.to_owned(),
+            is_optional: field.is_optional,
+            is_nullable: field.is_nullable,
+            is_required: field.is_required,
+            default_value: field.default_value.to_owned(),
+            example: field.example_value(),
+            markers: field.markers.clone(),
+            tags: field.tags.clone(),
+            allowed_values: field.allowed_values.clone(),
+            constraints: field.constraints.clone(),
+            inherited_from: field.inherited_from.to_owned(),
+        };
+        new_fields.push(f);
+    }
+    ModelEntity {
+        name: from.name.to_owned(),
+        bases: from.bases.clone(),
+        fields: new_fields,
+    }
+}
+
+fn convert(from: ProjectModel) -> ModelsProject {
+    let mut enums: Vec<Enum> = from.enums.into_values().collect();
+    enums.sort_by(|a, b| a.name.cmp(&b.name));
+    let mut unions: Vec<Union> = from.unions.into_values().collect();
+    unions.sort_by(|a, b| a.name.cmp(&b.name));
+    let mut entities: Vec<ModelEntity> = from.entities.values().map(to_summarized_entity).collect();
+    entities.sort_by(|a, b| a.name.cmp(&b.name));
+    ModelsProject {
+        enums,
+        unions,
+        entities,
+        diagnostics: from.diagnostics,
+    }
+}
+
+/// Parses a models file and returns the `ModelsProject` as JSON, throws on invalid models
+#[wasm_bindgen]
+pub fn parse_models(from: &str) -> Result<String, JsValue> {
+    match get_models(from) {
+        Ok(res) => {
+            let project = convert(res);
+            Ok(serde_json::to_string(&project).unwrap())
+        }
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
+fn non_empty(source: &str) -> Option<String> {
+    if source.trim().is_empty() {
+        return None;
+    }
+    Some(source.to_owned())
+}
+
+/// Compiles an API from its sources and returns the `Compilation` as JSON, empty models
+/// or examples are skipped, throws on invalid API or models
+#[wasm_bindgen]
+pub fn compile_api(
+    api_source: &str,
+    models_source: &str,
+    examples_source: &str,
+) -> Result<String, JsValue> {
+    let sources = Sources {
+        api: api_source.to_owned(),
+        models: non_empty(models_source),
+        examples: non_empty(examples_source),
+    };
+    match compile(&sources) {
+        Ok(compilation) => Ok(serde_json::to_string(&compilation).unwrap()),
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
+#[test]
+fn test_models_projection() {
+    let input = "enum Mood { happy, sad }\n\nstruct Person {\n name string required `example: fido`\n age int `example: 7`\n mood Mood\n}\n";
+    let project = convert(get_models(input).unwrap());
+    let person = &project.entities[0];
+
+    let names: Vec<&str> = person.fields.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(names, vec!["name", "age", "mood"]);
+    assert!(person.fields[0].is_required);
+    assert_eq!(person.fields[0].example, Some(Value::from("fido")));
+    assert_eq!(person.fields[1].example, Some(Value::from(7)));
+    assert_eq!(person.fields[2].allowed_values, vec!["happy", "sad"]);
+}